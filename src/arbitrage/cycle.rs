@@ -0,0 +1,237 @@
+use crate::kraken::api::types::AssetPair;
+use crate::tradingbot::{order::OrderSide, order_book::OrderBook};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Conversion of one asset into another through a pair: buying its base with its quote,
+/// or selling its base for its quote.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Leg {
+    pub pair: String,
+    pub side: OrderSide,
+    pub from: String,
+    pub to: String,
+}
+
+/// Three legs leading from an asset back to itself, e.g. ZUSD -> XXBT -> XETH -> ZUSD.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cycle {
+    pub legs: [Leg; 3],
+}
+
+impl Cycle {
+    pub fn pairs(&self) -> impl Iterator<Item = &String> {
+        self.legs.iter().map(|leg| &leg.pair)
+    }
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.legs[0].from)?;
+        for leg in &self.legs {
+            write!(f, " -> {}", leg.to)?;
+        }
+        Ok(())
+    }
+}
+
+/// Every triangular cycle starting and ending with `start`, in both directions.
+pub fn find_cycles(pairs: &HashMap<String, AssetPair>, start: &str) -> Vec<Cycle> {
+    let mut legs: HashMap<&str, Vec<Leg>> = HashMap::new();
+    for (name, pair) in pairs {
+        legs.entry(pair.quote.as_str()).or_default().push(Leg {
+            pair: name.clone(),
+            side: OrderSide::Buy,
+            from: pair.quote.clone(),
+            to: pair.base.clone(),
+        });
+        legs.entry(pair.base.as_str()).or_default().push(Leg {
+            pair: name.clone(),
+            side: OrderSide::Sell,
+            from: pair.base.clone(),
+            to: pair.quote.clone(),
+        });
+    }
+    let from = |asset: &str| legs.get(asset).map(Vec::as_slice).unwrap_or(&[]);
+
+    let mut cycles = Vec::new();
+    for first in from(start) {
+        for second in from(&first.to) {
+            if second.to == start || second.pair == first.pair {
+                continue;
+            }
+            for third in from(&second.to).iter().filter(|leg| leg.to == start) {
+                cycles.push(Cycle {
+                    legs: [first.clone(), second.clone(), third.clone()],
+                });
+            }
+        }
+    }
+    cycles.sort_by_key(|cycle| cycle.to_string());
+    cycles
+}
+
+/// One leg of an opportunity as it would fill against the book.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegFill {
+    /// Base volume traded.
+    pub volume: f32,
+    /// Average price.
+    pub price: f32,
+    /// Price of the last book level reached.
+    pub limit: f32,
+    /// Amount of the next asset received, fee deducted.
+    pub received: f32,
+}
+
+/// A cycle evaluated against order books for a starting amount.
+#[derive(Debug, Clone)]
+pub struct Opportunity {
+    pub cycle: Cycle,
+    pub amount: f32,
+    pub fills: Vec<LegFill>,
+}
+
+impl Opportunity {
+    /// Amount of the start asset at the end of the cycle.
+    pub fn result(&self) -> f32 {
+        self.fills.last().map(|fill| fill.received).unwrap_or(0.0)
+    }
+
+    pub fn profit_pct(&self) -> f32 {
+        (self.result() - self.amount) / self.amount * 100.0
+    }
+}
+
+impl fmt::Display for Opportunity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {:.4} -> {:.4} ({:+.3}%)",
+            self.cycle,
+            self.amount,
+            self.result(),
+            self.profit_pct()
+        )
+    }
+}
+
+/// Walk `amount` of the start asset through `cycle`, taking liquidity from `books` and paying
+/// the taker fee of `fee_pct` percent on every leg. `None` when a book is missing or too thin.
+pub fn evaluate(
+    cycle: &Cycle,
+    amount: f32,
+    fee_pct: f32,
+    books: &HashMap<String, OrderBook>,
+) -> Option<Opportunity> {
+    let keep = 1.0 - fee_pct / 100.0;
+    let mut fills = Vec::with_capacity(3);
+    let mut spend = amount;
+    for leg in &cycle.legs {
+        let book = books.get(&leg.pair)?;
+        let fill = match leg.side {
+            OrderSide::Buy => {
+                // The volume a quote amount buys depends on the levels it reaches.
+                let estimate = book.fill_price(OrderSide::Buy, spend / book.best_ask()?, None)?;
+                let price = book.fill_price(OrderSide::Buy, spend / estimate, None)?;
+                let volume = spend / price;
+                LegFill {
+                    volume,
+                    price,
+                    limit: book.sweep_price(OrderSide::Buy, volume)?,
+                    received: volume * keep,
+                }
+            }
+            OrderSide::Sell => {
+                let price = book.fill_price(OrderSide::Sell, spend, None)?;
+                LegFill {
+                    volume: spend,
+                    price,
+                    limit: book.sweep_price(OrderSide::Sell, spend)?,
+                    received: spend * price * keep,
+                }
+            }
+        };
+        spend = fill.received;
+        fills.push(fill);
+    }
+
+    Some(Opportunity {
+        cycle: cycle.clone(),
+        amount,
+        fills,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs() -> HashMap<String, AssetPair> {
+        let pair = |base: &str, quote: &str| AssetPair {
+            altname: format!("{}{}", base, quote),
+            wsname: None,
+            base: base.to_owned(),
+            quote: quote.to_owned(),
+            pair_decimals: 1,
+            lot_decimals: 8,
+            ordermin: None,
+        };
+        vec![
+            ("XXBTZUSD", pair("XXBT", "ZUSD")),
+            ("XETHZUSD", pair("XETH", "ZUSD")),
+            ("XETHXXBT", pair("XETH", "XXBT")),
+            ("ADAEUR", pair("ADA", "ZEUR")),
+        ]
+        .into_iter()
+        .map(|(name, pair)| (name.to_owned(), pair))
+        .collect()
+    }
+
+    fn book(bid: f32, ask: f32) -> OrderBook {
+        OrderBook {
+            asks: vec![(ask, 10.0), (ask * 1.01, 100.0)],
+            bids: vec![(bid, 10.0), (bid * 0.99, 100.0)],
+        }
+    }
+
+    #[test]
+    fn test_find_cycles() {
+        let cycles = find_cycles(&pairs(), "ZUSD");
+        let names: Vec<String> = cycles.iter().map(Cycle::to_string).collect();
+        assert_eq!(
+            names,
+            vec![
+                "ZUSD -> XETH -> XXBT -> ZUSD",
+                "ZUSD -> XXBT -> XETH -> ZUSD"
+            ]
+        );
+        let sides: Vec<OrderSide> = cycles[1].legs.iter().map(|leg| leg.side).collect();
+        assert_eq!(sides, vec![OrderSide::Buy, OrderSide::Buy, OrderSide::Sell]);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let cycle = find_cycles(&pairs(), "ZUSD").remove(1);
+        let books: HashMap<String, OrderBook> = vec![
+            ("XXBTZUSD".to_owned(), book(9990.0, 10000.0)),
+            ("XETHXXBT".to_owned(), book(0.0499, 0.05)),
+            ("XETHZUSD".to_owned(), book(510.0, 511.0)),
+        ]
+        .into_iter()
+        .collect();
+
+        // 1000 USD -> 0.1 XBT -> 2 ETH -> 1020 USD, before fees.
+        let opportunity = evaluate(&cycle, 1000.0, 0.0, &books).unwrap();
+        assert!((opportunity.result() - 1020.0).abs() < 0.01);
+        assert_eq!(opportunity.fills[0].limit, 10000.0);
+
+        let opportunity = evaluate(&cycle, 1000.0, 0.26, &books).unwrap();
+        assert!((opportunity.profit_pct() - 1.2).abs() < 0.01);
+
+        // About 12 ETH to sell reach the second bid level.
+        let opportunity = evaluate(&cycle, 6000.0, 0.0, &books).unwrap();
+        assert_eq!(opportunity.fills[2].limit, 510.0 * 0.99);
+        assert!(evaluate(&cycle, 1e7, 0.0, &books).is_none());
+    }
+}
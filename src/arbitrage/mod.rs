@@ -0,0 +1,7 @@
+pub mod cycle;
+pub mod scanner;
+pub mod spread;
+
+pub use cycle::{evaluate, find_cycles, Cycle, Leg, LegFill, Opportunity};
+pub use scanner::{ArbitrageConfig, Scanner};
+pub use spread::{normalize_pair, SpreadAlert, SpreadMonitor, Venue};
@@ -0,0 +1,194 @@
+use super::cycle::{evaluate, find_cycles, Cycle, Opportunity};
+use crate::kraken::{api::types::AssetPair, Kraken};
+use crate::tradingbot::{
+    market::Market,
+    order::{
+        round_down, round_up, OrderFlag, OrderRequest, OrderSide, TimeInForce, DRY_RUN_ORDER_PREFIX,
+    },
+    order_book::OrderBook,
+};
+use log::{debug, info};
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+
+/// Settings of the triangular arbitrage scanner.
+#[derive(Debug, Clone)]
+pub struct ArbitrageConfig {
+    /// Asset the cycles start and end with, e.g. "ZUSD".
+    pub start: String,
+    /// Amount of `start` put through a cycle.
+    pub amount: f32,
+    /// Taker fee in percent, paid on every leg.
+    pub fee_pct: f32,
+    /// Smallest profit reported, in percent after fees.
+    pub min_profit_pct: f32,
+    /// Book levels fetched to check the depth of a candidate.
+    pub depth: u32,
+}
+
+impl Default for ArbitrageConfig {
+    fn default() -> Self {
+        ArbitrageConfig {
+            start: "ZUSD".to_owned(),
+            amount: 100.0,
+            fee_pct: 0.26,
+            min_profit_pct: 0.1,
+            depth: 25,
+        }
+    }
+}
+
+/// Evaluates every triangular cycle through the start asset on Kraken.
+pub struct Scanner {
+    kraken: Kraken,
+    config: ArbitrageConfig,
+    pairs: HashMap<String, AssetPair>,
+    cycles: Vec<Cycle>,
+}
+
+impl Scanner {
+    /// Load the pair registry and the cycles through `config.start`.
+    pub async fn new(kraken: Kraken, config: ArbitrageConfig) -> Result<Self, Box<dyn Error>> {
+        let pairs = kraken.get_asset_pairs().await?;
+        let cycles = find_cycles(&pairs, &config.start);
+        if cycles.is_empty() {
+            return Err(format!("no triangular cycle through {}", config.start).into());
+        }
+        Ok(Scanner {
+            kraken,
+            config,
+            pairs,
+            cycles,
+        })
+    }
+
+    pub fn cycles(&self) -> &[Cycle] {
+        &self.cycles
+    }
+
+    /// Screen every cycle on the best bid and ask of its pairs, then evaluate the candidates
+    /// against the depth of their books. Opportunities above the threshold, best first.
+    pub async fn scan(&self) -> Result<Vec<Opportunity>, Box<dyn Error>> {
+        let names: Vec<String> = self
+            .cycles
+            .iter()
+            .flat_map(Cycle::pairs)
+            .cloned()
+            .collect::<BTreeSet<String>>()
+            .into_iter()
+            .collect();
+        // Prices only, the volume at the top of the book is checked with the depth.
+        let tops: HashMap<String, OrderBook> = self
+            .kraken
+            .get_tickers(&names)
+            .await?
+            .into_iter()
+            .map(|(pair, book)| (pair, unlimited(&book)))
+            .collect();
+
+        let candidates: Vec<&Cycle> = self
+            .cycles
+            .iter()
+            .filter(|cycle| self.profitable(cycle, &tops).is_some())
+            .collect();
+        debug!("[ARB] {} candidates", candidates.len());
+
+        let mut books = HashMap::new();
+        for pair in candidates.iter().flat_map(|cycle| cycle.pairs()) {
+            if !books.contains_key(pair) {
+                let book = self.kraken.get_order_book(pair, self.config.depth).await?;
+                books.insert(pair.clone(), book);
+            }
+        }
+
+        let mut opportunities: Vec<Opportunity> = candidates
+            .into_iter()
+            .filter_map(|cycle| self.profitable(cycle, &books))
+            .collect();
+        opportunities.sort_by(|a, b| b.profit_pct().total_cmp(&a.profit_pct()));
+        Ok(opportunities)
+    }
+
+    /// Trade the legs of `opportunity` one after the other as immediate-or-cancel limit orders
+    /// at the deepest level each was evaluated to reach, each leg spending what the previous one
+    /// received. Returns the amount of the start asset got back.
+    pub async fn execute(&self, opportunity: &Opportunity) -> Result<f32, Box<dyn Error>> {
+        let mut spend = opportunity.amount;
+        for (leg, fill) in opportunity.cycle.legs.iter().zip(&opportunity.fills) {
+            let pair = self
+                .pairs
+                .get(&leg.pair)
+                .ok_or_else(|| format!("unknown pair {}", leg.pair))?;
+
+            let (limit, volume, fee_flag) = match leg.side {
+                OrderSide::Buy => {
+                    let limit = round_up(fill.limit, pair.pair_decimals);
+                    // The fee is taken from the base bought, not from the quote spent.
+                    (limit, spend / limit, OrderFlag::FeeInBase)
+                }
+                OrderSide::Sell => (
+                    round_down(fill.limit, pair.pair_decimals),
+                    spend,
+                    OrderFlag::FeeInQuote,
+                ),
+            };
+            let volume = round_down(volume, pair.lot_decimals);
+            if let Some(ordermin) = pair.ordermin.as_ref().and_then(|m| m.parse::<f32>().ok()) {
+                if volume < ordermin {
+                    return Err(format!(
+                        "{} {} is below the minimum of {}, holding {} {}",
+                        volume, leg.pair, ordermin, spend, leg.from
+                    )
+                    .into());
+                }
+            }
+
+            let order = OrderRequest {
+                time_in_force: Some(TimeInForce::ImmediateOrCancel),
+                flags: vec![fee_flag],
+                ..OrderRequest::limit(leg.side, &leg.pair, volume, limit)
+            };
+            let order_id = self.kraken.place_order(&order).await?;
+
+            spend = if order_id.starts_with(DRY_RUN_ORDER_PREFIX) {
+                fill.received
+            } else {
+                let info = self.kraken.query_order(&order_id).await?;
+                let executed: f32 = info.vol_exec.parse()?;
+                if executed <= 0.0 {
+                    return Err(format!(
+                        "{} {:?} not filled, holding {} {}",
+                        leg.pair, leg.side, spend, leg.from
+                    )
+                    .into());
+                }
+                let cost: f32 = info.cost.parse()?;
+                let fee: f32 = info.fee.parse()?;
+                // Kraken reports fees in the quote currency.
+                match leg.side {
+                    OrderSide::Buy => executed - fee / (cost / executed),
+                    OrderSide::Sell => cost - fee,
+                }
+            };
+            info!(
+                "[ARB] {:?} {} {} at {}, got {} {}",
+                leg.side, volume, leg.pair, limit, spend, leg.to
+            );
+        }
+        Ok(spend)
+    }
+
+    fn profitable(&self, cycle: &Cycle, books: &HashMap<String, OrderBook>) -> Option<Opportunity> {
+        evaluate(cycle, self.config.amount, self.config.fee_pct, books)
+            .filter(|opportunity| opportunity.profit_pct() >= self.config.min_profit_pct)
+    }
+}
+
+/// `book` with unlimited volume at each of its prices.
+fn unlimited(book: &OrderBook) -> OrderBook {
+    let unlimited = |levels: &[(f32, f32)]| levels.iter().map(|(p, _)| (*p, f32::MAX)).collect();
+    OrderBook {
+        asks: unlimited(&book.asks),
+        bids: unlimited(&book.bids),
+    }
+}
@@ -0,0 +1,320 @@
+use crate::tradingbot::{config::ConfigError, market::Market, order_book::OrderBook};
+use futures_util::future::join_all;
+use log::{debug, warn};
+use std::fmt;
+
+/// Quote assets recognised at the end of a symbol, longest first.
+const QUOTE_ASSETS: [&str; 14] = [
+    "ZUSD", "ZEUR", "ZGBP", "ZJPY", "USDT", "USDC", "BUSD", "XXBT", "XETH", "USD", "EUR", "GBP",
+    "BTC", "ETH",
+];
+
+/// Common name of an asset across exchanges, e.g. "XXBT" and "XBT" are "BTC".
+pub fn normalize_asset(asset: &str) -> String {
+    // Kraken prefixes its older assets with X (crypto) or Z (fiat).
+    let asset = match asset.len() {
+        4 if asset.starts_with('X') || asset.starts_with('Z') => &asset[1..],
+        _ => asset,
+    };
+    match asset {
+        "XBT" => "BTC",
+        "XDG" => "DOGE",
+        asset => asset,
+    }
+    .to_owned()
+}
+
+/// Common name of an exchange symbol, e.g. "XXBTZUSD", "XBT/USD" and "BTCUSD" are "BTC/USD".
+/// `None` when the quote asset is not recognised.
+pub fn normalize_pair(symbol: &str) -> Option<String> {
+    let symbol = symbol.to_uppercase().replace(&['/', '-', '_'][..], "");
+    let quote = QUOTE_ASSETS
+        .iter()
+        .find(|quote| symbol.len() > quote.len() && symbol.ends_with(*quote))?;
+    let base = &symbol[..symbol.len() - quote.len()];
+    Some(format!(
+        "{}/{}",
+        normalize_asset(base),
+        normalize_asset(quote)
+    ))
+}
+
+/// One market quoting the monitored asset.
+pub struct Venue {
+    /// Name shown in alerts, e.g. "kraken".
+    pub name: String,
+    pub market: Box<dyn Market>,
+    /// Symbol of the asset on this market, e.g. "XXBTZUSD".
+    pub pair: String,
+    /// Taker fee in percent.
+    pub fee_pct: f32,
+}
+
+/// Best bid and ask of a venue.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quote {
+    pub bid: f32,
+    pub ask: f32,
+}
+
+/// Buying on one venue and selling on another earns more than the fees and the threshold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpreadAlert {
+    pub pair: String,
+    pub buy_venue: String,
+    pub ask: f32,
+    pub sell_venue: String,
+    pub bid: f32,
+    /// Bid over ask, in percent.
+    pub spread_pct: f32,
+    /// Taker fees of both venues, in percent.
+    pub fees_pct: f32,
+}
+
+impl fmt::Display for SpreadAlert {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: buy on {} at {}, sell on {} at {}, spread {:.3}% ({:.3}% after fees)",
+            self.pair,
+            self.buy_venue,
+            self.ask,
+            self.sell_venue,
+            self.bid,
+            self.spread_pct,
+            self.spread_pct - self.fees_pct
+        )
+    }
+}
+
+/// Compares the best bid and ask of the same asset on several markets.
+pub struct SpreadMonitor {
+    pair: String,
+    venues: Vec<Venue>,
+    threshold_pct: f32,
+}
+
+impl SpreadMonitor {
+    /// Monitor `venues`, alerting on spreads `threshold_pct` percent above the fees.
+    /// The venue symbols must name the same base asset.
+    pub fn new(venues: Vec<Venue>, threshold_pct: f32) -> Result<Self, ConfigError> {
+        if venues.len() < 2 {
+            return Err(ConfigError(
+                "the spread monitor needs at least two venues".to_owned(),
+            ));
+        }
+        let pairs = venues
+            .iter()
+            .map(|venue| {
+                normalize_pair(&venue.pair)
+                    .ok_or_else(|| ConfigError(format!("unknown quote asset in {}", venue.pair)))
+            })
+            .collect::<Result<Vec<String>, _>>()?;
+
+        let base = |pair: &str| pair.split('/').next().unwrap_or_default().to_owned();
+        if let Some(other) = pairs.iter().find(|pair| base(pair) != base(&pairs[0])) {
+            return Err(ConfigError(format!(
+                "venues quote different assets: {} and {}",
+                pairs[0], other
+            )));
+        }
+        // Quotes may differ, e.g. USD and USDT are compared as one.
+        if pairs.iter().any(|pair| *pair != pairs[0]) {
+            warn!("[SPREAD] comparing {} as one asset", pairs.join(", "));
+        }
+
+        Ok(SpreadMonitor {
+            pair: pairs[0].clone(),
+            venues,
+            threshold_pct,
+        })
+    }
+
+    pub fn venues(&self) -> &[Venue] {
+        &self.venues
+    }
+
+    /// Best bid and ask of every venue, requested concurrently. `None` for venues that failed
+    /// or have an empty book.
+    pub async fn poll(&self) -> Vec<Option<Quote>> {
+        let books = join_all(
+            self.venues
+                .iter()
+                .map(|venue| venue.market.get_order_book(&venue.pair, 1)),
+        )
+        .await;
+
+        books
+            .into_iter()
+            .zip(&self.venues)
+            .map(|(book, venue)| match book {
+                Ok(book) => quote(&book),
+                Err(e) => {
+                    warn!("[SPREAD] {} {} failed: {}", venue.name, venue.pair, e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Every pair of venues where buying on one and selling on the other beats the fees by the
+    /// threshold, widest spread first.
+    pub fn spreads(&self, quotes: &[Option<Quote>]) -> Vec<SpreadAlert> {
+        let mut alerts = Vec::new();
+        for (i, (buy, buy_quote)) in self.venues.iter().zip(quotes).enumerate() {
+            for (j, (sell, sell_quote)) in self.venues.iter().zip(quotes).enumerate() {
+                let (ask, bid) = match (buy_quote, sell_quote) {
+                    (Some(buy_quote), Some(sell_quote)) if i != j => {
+                        (buy_quote.ask, sell_quote.bid)
+                    }
+                    _ => continue,
+                };
+                let spread_pct = (bid - ask) / ask * 100.0;
+                let fees_pct = buy.fee_pct + sell.fee_pct;
+                debug!(
+                    "[SPREAD] {} ask {} / {} bid {}: {:.3}%",
+                    buy.name, ask, sell.name, bid, spread_pct
+                );
+                if spread_pct > fees_pct + self.threshold_pct {
+                    alerts.push(SpreadAlert {
+                        pair: self.pair.clone(),
+                        buy_venue: buy.name.clone(),
+                        ask,
+                        sell_venue: sell.name.clone(),
+                        bid,
+                        spread_pct,
+                        fees_pct,
+                    });
+                }
+            }
+        }
+        alerts.sort_by(|a, b| b.spread_pct.total_cmp(&a.spread_pct));
+        alerts
+    }
+
+    /// Poll every venue and log the spreads worth an alert.
+    pub async fn check(&self) -> Vec<SpreadAlert> {
+        let alerts = self.spreads(&self.poll().await);
+        for alert in &alerts {
+            warn!("[SPREAD] {}", alert);
+        }
+        alerts
+    }
+}
+
+fn quote(book: &OrderBook) -> Option<Quote> {
+    Some(Quote {
+        bid: book.best_bid()?,
+        ask: book.best_ask()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tradingbot::{
+        candle::Candle,
+        order::{OpenOrder, OrderRequest},
+    };
+    use async_trait::async_trait;
+    use std::error::Error;
+
+    /// Stand-in market quoting a fixed bid and ask, or failing when it has none.
+    struct QuoteMarket(Option<Quote>);
+
+    #[async_trait]
+    impl Market for QuoteMarket {
+        async fn get_balances(&self) -> Result<f32, Box<dyn Error>> {
+            Ok(0.0)
+        }
+
+        async fn get_base_balance(&self, _pair: &str) -> Result<f32, Box<dyn Error>> {
+            Ok(0.0)
+        }
+
+        async fn get_market_price(&self, _pair: &str) -> Result<f32, Box<dyn Error>> {
+            Err("not quoted".into())
+        }
+
+        async fn get_candles(
+            &self,
+            _pair: &str,
+            _interval: u32,
+        ) -> Result<Vec<Candle>, Box<dyn Error>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_order_book(
+            &self,
+            _pair: &str,
+            _depth: u32,
+        ) -> Result<OrderBook, Box<dyn Error>> {
+            let quote = self.0.ok_or("market closed")?;
+            Ok(OrderBook {
+                asks: vec![(quote.ask, 1.0)],
+                bids: vec![(quote.bid, 1.0)],
+            })
+        }
+
+        async fn place_order(&self, _order: &OrderRequest) -> Result<String, Box<dyn Error>> {
+            panic!("the monitor must not trade")
+        }
+
+        async fn cancel_order(&self, _order_id: &str) -> Result<(), Box<dyn Error>> {
+            panic!("the monitor must not trade")
+        }
+
+        async fn get_open_orders(&self) -> Result<Vec<OpenOrder>, Box<dyn Error>> {
+            panic!("the monitor must not trade")
+        }
+    }
+
+    fn venue(name: &str, pair: &str, quote: Option<(f32, f32)>) -> Venue {
+        Venue {
+            name: name.to_owned(),
+            market: Box::new(QuoteMarket(quote.map(|(bid, ask)| Quote { bid, ask }))),
+            pair: pair.to_owned(),
+            fee_pct: 0.25,
+        }
+    }
+
+    #[test]
+    fn test_normalize_pair() {
+        assert_eq!(normalize_pair("XXBTZUSD").unwrap(), "BTC/USD");
+        assert_eq!(normalize_pair("XBT/USD").unwrap(), "BTC/USD");
+        assert_eq!(normalize_pair("BTCUSDT").unwrap(), "BTC/USDT");
+        assert_eq!(normalize_pair("XETHXXBT").unwrap(), "ETH/BTC");
+        assert_eq!(normalize_pair("XDGUSD").unwrap(), "DOGE/USD");
+        assert_eq!(normalize_pair("ADAEUR").unwrap(), "ADA/EUR");
+        assert!(normalize_pair("BTCXYZ").is_none());
+
+        let mixed = vec![
+            venue("kraken", "XXBTZUSD", None),
+            venue("binance", "ETHUSDT", None),
+        ];
+        assert!(SpreadMonitor::new(mixed, 0.1).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_check() {
+        let venues = vec![
+            venue("kraken", "XXBTZUSD", Some((10000.0, 10010.0))),
+            venue("binance", "BTCUSDT", Some((10080.0, 10090.0))),
+            venue("closed", "BTCUSD", None),
+        ];
+        let monitor = SpreadMonitor::new(venues, 0.1).unwrap();
+
+        // Buying at 10010 and selling at 10080 earns 0.7%, above 0.5% fees and 0.1%.
+        let alerts = monitor.check().await;
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].buy_venue, "kraken");
+        assert_eq!(alerts[0].sell_venue, "binance");
+        assert!((alerts[0].spread_pct - 0.699).abs() < 0.001);
+
+        let monitor = SpreadMonitor {
+            threshold_pct: 0.3,
+            ..monitor
+        };
+        assert!(monitor.check().await.is_empty());
+    }
+}
@@ -0,0 +1,138 @@
+use crate::kraken::kraken_impl::parse_ohlc;
+use crate::tradingbot::{candle::Candle, config::ConfigError};
+use chrono::{TimeZone, Utc};
+use serde_json::Value;
+use std::{error::Error, fs, path::Path};
+
+/// Header of candle CSV files.
+pub const CSV_HEADER: &str = "time,open,high,low,close,vwap,volume,count";
+
+/// Load candles from a CSV file (`CSV_HEADER` columns, unix time in seconds)
+/// or a JSON file exported from Kraken's `OHLC` endpoint. Candles are sorted oldest first.
+pub fn load_candles(path: &Path) -> Result<Vec<Candle>, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    let mut candles = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("csv") => parse_csv(&content)?,
+        Some("json") => parse_json(&content)?,
+        _ => {
+            return Err(Box::new(ConfigError(format!(
+                "unsupported candle file {}, expected .csv or .json",
+                path.display()
+            ))))
+        }
+    };
+    candles.sort_by_key(|c| c.time);
+    candles.dedup_by_key(|c| c.time);
+    Ok(candles)
+}
+
+pub fn parse_csv(content: &str) -> Result<Vec<Candle>, Box<dyn Error>> {
+    let mut candles = Vec::new();
+    for (line_no, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("time") {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() < 8 {
+            return Err(Box::new(ConfigError(format!(
+                "line {}: expected 8 columns ({}), got {}",
+                line_no + 1,
+                CSV_HEADER,
+                fields.len()
+            ))));
+        }
+        let number = |index: usize| {
+            fields[index].parse::<f32>().map_err(|_| {
+                ConfigError(format!(
+                    "line {}: invalid number {}",
+                    line_no + 1,
+                    fields[index]
+                ))
+            })
+        };
+
+        candles.push(Candle {
+            time: Utc.timestamp(fields[0].parse::<i64>()?, 0),
+            open: number(1)?,
+            high: number(2)?,
+            low: number(3)?,
+            close: number(4)?,
+            vwap: number(5)?,
+            volume: number(6)?,
+            count: fields[7].parse::<u64>()?,
+        });
+    }
+    Ok(candles)
+}
+
+/// Accept a full `OHLC` response (`{"error": [], "result": {"<pair>": [...], "last": n}}`)
+/// or just the array of entries.
+pub fn parse_json(content: &str) -> Result<Vec<Candle>, Box<dyn Error>> {
+    let value: Value = serde_json::from_str(content)?;
+
+    let entries = match &value {
+        Value::Array(entries) => entries,
+        _ => value
+            .get("result")
+            .and_then(Value::as_object)
+            .and_then(|result| {
+                result
+                    .iter()
+                    .find(|(key, _)| key.as_str() != "last")
+                    .and_then(|(_, data)| data.as_array())
+            })
+            .ok_or_else(|| ConfigError("no OHLC data found in JSON".to_owned()))?,
+    };
+
+    entries.iter().map(parse_ohlc).collect()
+}
+
+/// One CSV line for `candle`, without trailing newline.
+pub fn to_csv_line(candle: &Candle) -> String {
+    format!(
+        "{},{},{},{},{},{},{},{}",
+        candle.time.timestamp(),
+        candle.open,
+        candle.high,
+        candle.low,
+        candle.close,
+        candle.vwap,
+        candle.volume,
+        candle.count
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv() {
+        let csv = format!(
+            "{}\n1609027200,26560.5,26600,26500,26580.1,26570,12.5,120\n",
+            CSV_HEADER
+        );
+        let candles = parse_csv(&csv).unwrap();
+        assert_eq!(candles.len(), 1);
+        assert_eq!(candles[0].time.timestamp(), 1609027200);
+        assert_eq!(candles[0].close, 26580.1);
+        assert_eq!(
+            to_csv_line(&candles[0]),
+            "1609027200,26560.5,26600,26500,26580.1,26570,12.5,120"
+        );
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let json = r#"{"error":[],"result":{"XXBTZUSD":[[1609027200,"26560.5","26600.0","26500.0","26580.1","26570.0","12.5",120]],"last":1609027200}}"#;
+        let candles = parse_json(json).unwrap();
+        assert_eq!(candles.len(), 1);
+        assert_eq!(candles[0].volume, 12.5);
+        assert_eq!(candles[0].vwap, 26570.0);
+
+        assert!(parse_json(r#"[[1609027200,"26560.5","26600.0"]]"#).is_err());
+        assert!(parse_json(r#"[[1609027200,"26560.5","26600.0","x","1","1","1",1]]"#).is_err());
+    }
+}
@@ -0,0 +1,106 @@
+use super::{
+    report::BacktestReport,
+    simulated_market::{SimConfig, SimState, SimulatedMarket},
+};
+use crate::tradingbot::{
+    candle::Candle, config::TradingConfig, strategy::Strategy, tradingbot::TradingBot,
+};
+use log::info;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+
+/// Replay `candles` through a `TradingBot` running `strategy`, one trading cycle per candle.
+pub async fn run_backtest(
+    trading_config: TradingConfig,
+    strategy: Box<dyn Strategy>,
+    candles: Vec<Candle>,
+    sim_config: SimConfig,
+) -> Result<BacktestReport, Box<dyn Error>> {
+    if candles.is_empty() {
+        return Err("no candles to backtest".into());
+    }
+
+    info!(
+        "[BACKTEST] {} candles from {} to {}",
+        candles.len(),
+        candles[0].time,
+        candles[candles.len() - 1].time
+    );
+
+    let state = Arc::new(Mutex::new(SimState::new(sim_config, candles)));
+    let market = SimulatedMarket::new(state.clone());
+    let mut bot = TradingBot::new(trading_config, Box::new(market), strategy);
+
+    let mut equity_curve = Vec::new();
+    loop {
+        bot.start().await?;
+
+        let mut state = state.lock().unwrap();
+        equity_curve.push((state.time(), state.equity()));
+        if !state.advance() {
+            break;
+        }
+    }
+
+    let trades = state.lock().unwrap().trades.clone();
+    let report = BacktestReport::new(trades, equity_curve);
+    info!("[BACKTEST] done\n{}", report.stats);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tradingbot::strategy::{Signal, TargetPosition, Tick};
+    use chrono::{TimeZone, Utc};
+
+    /// Goes long on the first tick and stays there.
+    struct BuyAndHold;
+
+    impl Strategy for BuyAndHold {
+        fn name(&self) -> &str {
+            "buy-and-hold"
+        }
+
+        fn on_tick(&mut self, _tick: &Tick) -> Vec<Signal> {
+            vec![Signal::Target(TargetPosition::Long(1.0))]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_buy_and_hold() {
+        let candles: Vec<Candle> = (0..10)
+            .map(|i| Candle {
+                time: Utc.timestamp(1_600_000_000 + i * 3600, 0),
+                open: 100.0 + i as f32,
+                high: 101.0 + i as f32,
+                low: 99.0 + i as f32,
+                close: 100.5 + i as f32,
+                vwap: 100.0 + i as f32,
+                volume: 1.0,
+                count: 1,
+            })
+            .collect();
+        let config = TradingConfig {
+            max_position: 1.0,
+            ..Default::default()
+        };
+        let sim = SimConfig {
+            fees: Default::default(),
+            slippage_pct: 0.0,
+            latency: 1,
+            initial_balance: 1000.0,
+            warmup: 2,
+        };
+
+        let report = run_backtest(config, Box::new(BuyAndHold), candles, sim)
+            .await
+            .unwrap();
+
+        // Bought once at the open of the candle after the first cycle.
+        assert_eq!(report.trades.len(), 1);
+        assert_eq!(report.trades[0].price, 103.0);
+        assert_eq!(report.equity_curve.len(), 8);
+        assert!(report.stats.final_equity > 1000.0);
+    }
+}
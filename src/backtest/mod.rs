@@ -0,0 +1,9 @@
+pub mod data;
+pub mod engine;
+pub mod report;
+pub mod simulated_market;
+pub mod simulated_market_impl;
+
+pub use engine::run_backtest;
+pub use report::{BacktestReport, BacktestStats};
+pub use simulated_market::{FeeModel, SimConfig, SimulatedMarket};
@@ -0,0 +1,191 @@
+use crate::tradingbot::order::{Fill, OrderSide};
+use chrono::{DateTime, Utc};
+use std::{error::Error, fmt, fs, path::Path};
+
+/// Summary statistics of a backtest.
+#[derive(Debug, Clone, Default)]
+pub struct BacktestStats {
+    pub initial_equity: f32,
+    pub final_equity: f32,
+    pub return_pct: f32,
+    pub max_drawdown_pct: f32,
+    pub trades: usize,
+    /// Fills reducing or closing a position.
+    pub closing_trades: usize,
+    pub win_rate_pct: f32,
+    pub realized_pnl: f32,
+    pub fees: f32,
+}
+
+/// Outcome of a backtest: every fill, equity after each candle and statistics.
+#[derive(Debug, Clone)]
+pub struct BacktestReport {
+    pub trades: Vec<Fill>,
+    pub equity_curve: Vec<(DateTime<Utc>, f32)>,
+    pub stats: BacktestStats,
+}
+
+impl BacktestReport {
+    pub fn new(trades: Vec<Fill>, equity_curve: Vec<(DateTime<Utc>, f32)>) -> Self {
+        let stats = compute_stats(&trades, &equity_curve);
+        BacktestReport {
+            trades,
+            equity_curve,
+            stats,
+        }
+    }
+
+    /// Write `trades.csv` and `equity.csv` into `dir`.
+    pub fn write_csv(&self, dir: &Path) -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(dir)?;
+
+        let mut trades = String::from("time,order_id,side,price,volume,fee\n");
+        for fill in &self.trades {
+            let side: &str = fill.side.into();
+            trades.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                fill.time.to_rfc3339(),
+                fill.order_id,
+                side,
+                fill.price,
+                fill.volume,
+                fill.fee
+            ));
+        }
+        fs::write(dir.join("trades.csv"), trades)?;
+
+        let mut equity = String::from("time,equity\n");
+        for (time, value) in &self.equity_curve {
+            equity.push_str(&format!("{},{}\n", time.to_rfc3339(), value));
+        }
+        fs::write(dir.join("equity.csv"), equity)?;
+
+        Ok(())
+    }
+}
+
+fn compute_stats(trades: &[Fill], equity_curve: &[(DateTime<Utc>, f32)]) -> BacktestStats {
+    let initial_equity = equity_curve.first().map(|(_, e)| *e).unwrap_or(0.0);
+    let final_equity = equity_curve.last().map(|(_, e)| *e).unwrap_or(0.0);
+
+    let mut peak = initial_equity;
+    let mut max_drawdown_pct: f32 = 0.0;
+    for (_, equity) in equity_curve {
+        peak = peak.max(*equity);
+        if peak > 0.0 {
+            max_drawdown_pct = max_drawdown_pct.max((peak - equity) / peak * 100.0);
+        }
+    }
+
+    // Average cost accounting, position is signed (negative when short).
+    let mut position: f32 = 0.0;
+    let mut avg_price: f32 = 0.0;
+    let mut realized_pnl = 0.0;
+    let mut closing_trades = 0;
+    let mut wins = 0;
+    for fill in trades {
+        let signed = match fill.side {
+            OrderSide::Buy => fill.volume,
+            OrderSide::Sell => -fill.volume,
+        };
+
+        if position == 0.0 || position.signum() == signed.signum() {
+            avg_price = (avg_price * position.abs() + fill.price * fill.volume)
+                / (position.abs() + fill.volume);
+            position += signed;
+            continue;
+        }
+
+        let closed = fill.volume.min(position.abs());
+        let pnl = (fill.price - avg_price) * closed * position.signum() - fill.fee;
+        realized_pnl += pnl;
+        closing_trades += 1;
+        if pnl > 0.0 {
+            wins += 1;
+        }
+
+        position += signed;
+        if position.abs() < 1e-9 {
+            position = 0.0;
+        } else if position.signum() == signed.signum() {
+            // Flipped: the remainder opens a new position at the fill price.
+            avg_price = fill.price;
+        }
+    }
+
+    BacktestStats {
+        initial_equity,
+        final_equity,
+        return_pct: if initial_equity > 0.0 {
+            (final_equity - initial_equity) / initial_equity * 100.0
+        } else {
+            0.0
+        },
+        max_drawdown_pct,
+        trades: trades.len(),
+        closing_trades,
+        win_rate_pct: if closing_trades > 0 {
+            wins as f32 / closing_trades as f32 * 100.0
+        } else {
+            0.0
+        },
+        realized_pnl,
+        fees: trades.iter().fold(0.0, |sum, f| sum + f.fee),
+    }
+}
+
+impl fmt::Display for BacktestStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Initial equity : {:.2}", self.initial_equity)?;
+        writeln!(f, "Final equity   : {:.2}", self.final_equity)?;
+        writeln!(f, "Return         : {:.2}%", self.return_pct)?;
+        writeln!(f, "Max drawdown   : {:.2}%", self.max_drawdown_pct)?;
+        writeln!(f, "Trades         : {}", self.trades)?;
+        writeln!(f, "Closing trades : {}", self.closing_trades)?;
+        writeln!(f, "Win rate       : {:.1}%", self.win_rate_pct)?;
+        writeln!(f, "Realized PnL   : {:.2}", self.realized_pnl)?;
+        write!(f, "Fees           : {:.2}", self.fees)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn fill(side: OrderSide, price: f32, volume: f32) -> Fill {
+        Fill {
+            trade_id: "T".to_owned(),
+            order_id: "O".to_owned(),
+            pair: "XXBTZUSD".to_owned(),
+            side,
+            price,
+            volume,
+            fee: 0.0,
+            time: Utc.timestamp(0, 0),
+        }
+    }
+
+    #[test]
+    fn test_stats() {
+        let trades = vec![
+            fill(OrderSide::Buy, 100.0, 1.0),
+            fill(OrderSide::Sell, 110.0, 1.0),
+            fill(OrderSide::Sell, 110.0, 1.0),
+            fill(OrderSide::Buy, 120.0, 1.0),
+        ];
+        let equity = vec![
+            (Utc.timestamp(0, 0), 1000.0),
+            (Utc.timestamp(1, 0), 1200.0),
+            (Utc.timestamp(2, 0), 900.0),
+            (Utc.timestamp(3, 0), 1000.0),
+        ];
+        let stats = BacktestReport::new(trades, equity).stats;
+
+        assert_eq!(stats.closing_trades, 2);
+        assert_eq!(stats.win_rate_pct, 50.0);
+        assert_eq!(stats.realized_pnl, 0.0);
+        assert_eq!(stats.return_pct, 0.0);
+        assert_eq!(stats.max_drawdown_pct, 25.0);
+    }
+}
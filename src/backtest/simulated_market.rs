@@ -0,0 +1,79 @@
+use crate::tradingbot::{
+    candle::Candle,
+    order::{Fill, OrderRequest},
+};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Trading fees in percent of the traded value.
+#[derive(Debug, Clone, Copy)]
+pub struct FeeModel {
+    pub maker_pct: f32,
+    pub taker_pct: f32,
+}
+
+impl Default for FeeModel {
+    /// Kraken's entry tier.
+    fn default() -> Self {
+        FeeModel {
+            maker_pct: 0.16,
+            taker_pct: 0.26,
+        }
+    }
+}
+
+/// Execution model of the simulated market.
+#[derive(Debug, Clone)]
+pub struct SimConfig {
+    pub fees: FeeModel,
+    /// Market and triggered stop orders fill this many percent worse than the reference price.
+    pub slippage_pct: f32,
+    /// Candles between placing an order and the first candle it can fill in.
+    pub latency: usize,
+    /// Quote currency balance at the start.
+    pub initial_balance: f32,
+    /// Candles handed to the strategy as history before trading starts.
+    pub warmup: usize,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        SimConfig {
+            fees: FeeModel::default(),
+            slippage_pct: 0.05,
+            latency: 1,
+            initial_balance: 10_000.0,
+            warmup: 50,
+        }
+    }
+}
+
+/// Order resting on the simulated market, prices resolved when placed.
+#[derive(Debug, Clone)]
+pub struct SimOrder {
+    pub id: String,
+    pub request: OrderRequest,
+    pub price: Option<f32>,
+    /// Index of the first candle the order can fill in.
+    pub active_from: usize,
+}
+
+/// Replayed market state: candles up to `cursor` are closed.
+pub struct SimState {
+    pub config: SimConfig,
+    pub candles: Vec<Candle>,
+    pub cursor: usize,
+    pub quote: f32,
+    pub base: f32,
+    pub orders: Vec<SimOrder>,
+    pub trades: Vec<Fill>,
+    pub fills: Option<UnboundedSender<Fill>>,
+    /// Ids of the orders cancelled for lack of funds, not yet reported to the bot.
+    pub closed: Vec<String>,
+    pub next_id: u64,
+}
+
+/// Market replaying historical candles, shared with the backtest driving it.
+pub struct SimulatedMarket {
+    pub state: Arc<Mutex<SimState>>,
+}
@@ -0,0 +1,405 @@
+/// Simulated market implementation
+use super::simulated_market::{SimConfig, SimOrder, SimState, SimulatedMarket};
+use crate::tradingbot::{
+    candle::Candle,
+    market::Market,
+    order::{Fill, InvalidOrder, OpenOrder, OrderRequest, OrderSide, OrderType},
+    order_book::OrderBook,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use log::{debug, warn};
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+/// Most candles returned at once, as Kraken's `OHLC` endpoint.
+const MAX_CANDLES: usize = 720;
+
+impl SimState {
+    pub fn new(config: SimConfig, candles: Vec<Candle>) -> Self {
+        let cursor = config.warmup.min(candles.len().saturating_sub(1));
+        SimState {
+            quote: config.initial_balance,
+            config,
+            candles,
+            cursor,
+            base: 0.0,
+            orders: Vec::new(),
+            trades: Vec::new(),
+            fills: None,
+            closed: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Last closed candle.
+    pub fn current(&self) -> &Candle {
+        &self.candles[self.cursor]
+    }
+
+    pub fn price(&self) -> f32 {
+        self.current().close
+    }
+
+    /// Close time of the current candle.
+    pub fn time(&self) -> DateTime<Utc> {
+        self.current().time + self.interval()
+    }
+
+    pub fn interval(&self) -> Duration {
+        match (self.candles.first(), self.candles.get(1)) {
+            (Some(first), Some(second)) => second.time - first.time,
+            _ => Duration::minutes(1),
+        }
+    }
+
+    /// Quote balance plus base holdings at the current price.
+    pub fn equity(&self) -> f32 {
+        self.quote + self.base * self.price()
+    }
+
+    /// Close the next candle and fill orders against it. Returns false at the end of the data.
+    pub fn advance(&mut self) -> bool {
+        if self.cursor + 1 >= self.candles.len() {
+            return false;
+        }
+        self.cursor += 1;
+
+        let candle = self.current().clone();
+        let cursor = self.cursor;
+        let (active, waiting): (Vec<SimOrder>, Vec<SimOrder>) =
+            self.orders.drain(..).partition(|o| o.active_from <= cursor);
+        self.orders = waiting;
+
+        for order in active {
+            match self.match_order(&order, &candle) {
+                Some((price, maker)) => self.execute(&order, price, maker, &candle),
+                None => self.orders.push(order),
+            }
+        }
+        true
+    }
+
+    pub fn place(&mut self, order: &OrderRequest) -> Result<String, InvalidOrder> {
+        order.validate()?;
+        match order.order_type {
+            OrderType::Market | OrderType::Limit | OrderType::StopLoss | OrderType::TakeProfit => {}
+            other => {
+                return Err(InvalidOrder(format!(
+                    "{:?} orders are not supported by the simulated market",
+                    other
+                )))
+            }
+        }
+
+        let reference = self.price();
+        let id = format!("SIM-{}", self.next_id);
+        self.next_id += 1;
+        self.orders.push(SimOrder {
+            id: id.clone(),
+            request: order.clone(),
+            price: order.price.map(|p| p.resolve(reference)),
+            active_from: self.cursor + self.config.latency.max(1),
+        });
+        debug!("[SIM] placed {} {:?}", id, order);
+        Ok(id)
+    }
+
+    pub fn cancel(&mut self, order_id: &str) {
+        self.orders.retain(|o| o.id != order_id);
+    }
+
+    /// Fill price and whether the order provided liquidity, if it executes in `candle`.
+    fn match_order(&self, order: &SimOrder, candle: &Candle) -> Option<(f32, bool)> {
+        let slip = self.config.slippage_pct / 100.0;
+        let side = order.request.side;
+        let worse = |price: f32| match side {
+            OrderSide::Buy => price * (1.0 + slip),
+            OrderSide::Sell => price * (1.0 - slip),
+        };
+
+        match (order.request.order_type, side, order.price) {
+            (OrderType::Market, _, _) => Some((worse(candle.open), false)),
+            (OrderType::Limit, OrderSide::Buy, Some(p)) if candle.open <= p => {
+                Some((candle.open, false))
+            }
+            (OrderType::Limit, OrderSide::Buy, Some(p)) if candle.low <= p => Some((p, true)),
+            (OrderType::Limit, OrderSide::Sell, Some(p)) if candle.open >= p => {
+                Some((candle.open, false))
+            }
+            (OrderType::Limit, OrderSide::Sell, Some(p)) if candle.high >= p => Some((p, true)),
+            (OrderType::StopLoss, OrderSide::Sell, Some(p)) if candle.low <= p => {
+                Some((worse(candle.open.min(p)), false))
+            }
+            (OrderType::StopLoss, OrderSide::Buy, Some(p)) if candle.high >= p => {
+                Some((worse(candle.open.max(p)), false))
+            }
+            (OrderType::TakeProfit, OrderSide::Sell, Some(p)) if candle.high >= p => {
+                Some((worse(candle.open.max(p)), false))
+            }
+            (OrderType::TakeProfit, OrderSide::Buy, Some(p)) if candle.low <= p => {
+                Some((worse(candle.open.min(p)), false))
+            }
+            _ => None,
+        }
+    }
+
+    fn execute(&mut self, order: &SimOrder, price: f32, maker: bool, candle: &Candle) {
+        let volume = order.request.volume;
+        let fee_pct = if maker {
+            self.config.fees.maker_pct
+        } else {
+            self.config.fees.taker_pct
+        };
+        let cost = price * volume;
+        let fee = cost * fee_pct / 100.0;
+
+        match order.request.side {
+            OrderSide::Buy => {
+                if cost + fee > self.quote {
+                    warn!(
+                        "[SIM] {} cancelled: {} quote needed, {} available",
+                        order.id,
+                        cost + fee,
+                        self.quote
+                    );
+                    self.closed.push(order.id.clone());
+                    return;
+                }
+                self.quote -= cost + fee;
+                self.base += volume;
+            }
+            OrderSide::Sell => {
+                // Short selling needs margin.
+                if volume > self.base + f32::EPSILON && order.request.leverage.is_none() {
+                    warn!(
+                        "[SIM] {} cancelled: {} base needed, {} available",
+                        order.id, volume, self.base
+                    );
+                    self.closed.push(order.id.clone());
+                    return;
+                }
+                self.quote += cost - fee;
+                self.base -= volume;
+            }
+        }
+
+        let fill = Fill {
+            trade_id: format!("SIMT-{}", self.trades.len() + 1),
+            order_id: order.id.clone(),
+            pair: order.request.pair.clone(),
+            side: order.request.side,
+            price,
+            volume,
+            fee,
+            time: candle.time,
+        };
+        debug!("[SIM] fill {:?}", fill);
+        if let Some(fills) = &self.fills {
+            fills.send(fill.clone()).ok();
+        }
+        self.trades.push(fill);
+
+        // The conditional close goes live once its entry is filled.
+        if let Some(close) = &order.request.close {
+            let request = OrderRequest {
+                order_type: close.order_type,
+                price: Some(close.price),
+                price2: close.price2,
+                close: None,
+                side: match order.request.side {
+                    OrderSide::Buy => OrderSide::Sell,
+                    OrderSide::Sell => OrderSide::Buy,
+                },
+                ..OrderRequest::market(order.request.side, &order.request.pair, volume)
+            };
+            let id = format!("SIM-{}", self.next_id);
+            self.next_id += 1;
+            self.orders.push(SimOrder {
+                id,
+                price: Some(close.price.resolve(price)),
+                request,
+                active_from: self.cursor + 1,
+            });
+        }
+    }
+}
+
+impl SimulatedMarket {
+    pub fn new(state: Arc<Mutex<SimState>>) -> Self {
+        SimulatedMarket { state }
+    }
+}
+
+#[async_trait]
+impl Market for SimulatedMarket {
+    async fn get_balances(&self) -> Result<f32, Box<dyn Error>> {
+        Ok(self.state.lock().unwrap().quote)
+    }
+
+    async fn get_base_balance(&self, _pair: &str) -> Result<f32, Box<dyn Error>> {
+        Ok(self.state.lock().unwrap().base)
+    }
+
+    async fn get_market_price(&self, _pair: &str) -> Result<f32, Box<dyn Error>> {
+        Ok(self.state.lock().unwrap().price())
+    }
+
+    /// Candles come from the replayed data whatever `interval` is asked.
+    async fn get_candles(
+        &self,
+        _pair: &str,
+        _interval: u32,
+    ) -> Result<Vec<Candle>, Box<dyn Error>> {
+        let state = self.state.lock().unwrap();
+        let end = state.cursor + 1;
+        let start = end.saturating_sub(MAX_CANDLES);
+        Ok(state.candles[start..end].to_vec())
+    }
+
+    /// Candles carry no depth, the book is a single unlimited level at the last close.
+    async fn get_order_book(&self, _pair: &str, _depth: u32) -> Result<OrderBook, Box<dyn Error>> {
+        let price = self.state.lock().unwrap().price();
+        Ok(OrderBook {
+            asks: vec![(price, f32::MAX)],
+            bids: vec![(price, f32::MAX)],
+        })
+    }
+
+    async fn place_order(&self, order: &OrderRequest) -> Result<String, Box<dyn Error>> {
+        Ok(self.state.lock().unwrap().place(order)?)
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<(), Box<dyn Error>> {
+        self.state.lock().unwrap().cancel(order_id);
+        Ok(())
+    }
+
+    async fn get_open_orders(&self) -> Result<Vec<OpenOrder>, Box<dyn Error>> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .orders
+            .iter()
+            .map(|o| OpenOrder {
+                id: o.id.clone(),
+                pair: o.request.pair.clone(),
+                side: o.request.side,
+                price: o.price.unwrap_or_default(),
+                volume: o.request.volume,
+                volume_executed: 0.0,
+                userref: o.request.userref,
+            })
+            .collect())
+    }
+
+    fn time(&self) -> DateTime<Utc> {
+        self.state.lock().unwrap().time()
+    }
+
+    async fn subscribe_fills(&self) -> Result<Option<UnboundedReceiver<Fill>>, Box<dyn Error>> {
+        let (fills, receiver) = mpsc::unbounded_channel();
+        self.state.lock().unwrap().fills = Some(fills);
+        Ok(Some(receiver))
+    }
+
+    fn take_closed_orders(&self) -> Vec<String> {
+        self.state.lock().unwrap().closed.drain(..).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tradingbot::order::Price;
+    use chrono::TimeZone;
+
+    fn candle(i: i64, open: f32, high: f32, low: f32, close: f32) -> Candle {
+        Candle {
+            time: Utc.timestamp(1_600_000_000 + i * 3600, 0),
+            open,
+            high,
+            low,
+            close,
+            vwap: close,
+            volume: 1.0,
+            count: 1,
+        }
+    }
+
+    fn state() -> SimState {
+        let candles = vec![
+            candle(0, 100.0, 101.0, 99.0, 100.0),
+            candle(1, 100.0, 102.0, 98.0, 101.0),
+            candle(2, 101.0, 110.0, 100.0, 108.0),
+        ];
+        let config = SimConfig {
+            slippage_pct: 0.0,
+            warmup: 0,
+            ..Default::default()
+        };
+        SimState::new(config, candles)
+    }
+
+    #[test]
+    fn test_market_order_fills_next_open() {
+        let mut state = state();
+        state
+            .place(&OrderRequest::market(OrderSide::Buy, "XXBTZUSD", 1.0))
+            .unwrap();
+        assert!(state.advance());
+
+        assert_eq!(state.trades.len(), 1);
+        assert_eq!(state.trades[0].price, 100.0);
+        assert_eq!(state.base, 1.0);
+        assert!((state.quote - (10_000.0 - 100.0 - 0.26)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_limit_order_and_conditional_close() {
+        let mut state = state();
+        let order = OrderRequest::limit(OrderSide::Buy, "XXBTZUSD", 1.0, 98.5)
+            .with_take_profit(Price::Percent(10.0));
+        state.place(&order).unwrap();
+
+        assert!(state.advance());
+        assert_eq!(state.trades[0].price, 98.5);
+        assert_eq!(state.orders.len(), 1);
+
+        // 98.5 * 1.1 = 108.35 reached by the next candle high
+        assert!(state.advance());
+        assert_eq!(state.trades.len(), 2);
+        assert_eq!(state.trades[1].side, OrderSide::Sell);
+        assert!((state.trades[1].price - 108.35).abs() < 1e-3);
+        assert!(!state.advance());
+    }
+
+    #[test]
+    fn test_unfunded_orders_are_closed() {
+        let mut state = state();
+        let sell = OrderRequest::market(OrderSide::Sell, "XXBTZUSD", 1.0);
+        state.place(&sell).unwrap();
+        state
+            .place(&OrderRequest::market(OrderSide::Buy, "XXBTZUSD", 1000.0))
+            .unwrap();
+        assert!(state.advance());
+
+        assert!(state.trades.is_empty());
+        assert_eq!(state.closed, vec!["SIM-1".to_owned(), "SIM-2".to_owned()]);
+        assert_eq!(state.quote, 10_000.0);
+        assert_eq!(state.base, 0.0);
+    }
+
+    #[test]
+    fn test_unsupported_order_type() {
+        let mut state = state();
+        let order = OrderRequest {
+            order_type: OrderType::TrailingStop,
+            price: Some(Price::Offset(5.0)),
+            ..OrderRequest::market(OrderSide::Sell, "XXBTZUSD", 1.0)
+        };
+        assert!(state.place(&order).is_err());
+    }
+}
@@ -1,11 +1,33 @@
 use chrono::prelude::*;
-use clap::{crate_authors, crate_description, crate_name, crate_version, App, Arg};
+use chrono::Duration;
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App, Arg, ArgMatches, SubCommand,
+};
 use dotenv::dotenv;
 use log::{info, warn};
+use std::collections::HashMap;
 use std::env;
+use std::error::Error;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::signal::{self, unix::SignalKind};
 use tokio::time;
 use tokio::time::Instant;
-use trade_bot::{self, Kraken, TradingBot, TradingConfig};
+use trade_bot::{
+    self,
+    arbitrage::{ArbitrageConfig, Scanner, SpreadMonitor, Venue},
+    backtest::{self, data::load_candles, FeeModel, SimConfig},
+    kraken::api::api::KrakenAPI,
+    marketdata::{self, CandleStore},
+    settings::{BotSettings, Exchange, Settings},
+    supervisor::Supervisor,
+    tradingbot::{strategy, Market, Strategy, TradingMode},
+    Binance, Kraken, PaperMarket, TradingBot, TradingConfig,
+};
+
+/// Consecutive failed cycles after which a bot is stopped.
+const MAX_BOT_FAILURES: u32 = 5;
 
 #[tokio::main]
 async fn main() {
@@ -39,43 +61,430 @@ async fn main() {
                 .help("Set TRADIND_CADENCE. Can also set with env variable: TRADIND_CADENCE. This commandline argument take precedence")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("dry_run")
+                .long("dry_run")
+                .help("Validate every order with the exchange without submitting it"),
+        )
+        .arg(
+            Arg::with_name("mode")
+                .long("mode")
+                .value_name("MODE")
+                .possible_values(&["live", "paper"])
+                .help("live submits orders to the exchange, paper fills them locally against the live order book with a virtual balance")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("exchange")
+                .long("exchange")
+                .value_name("EXCHANGE")
+                .possible_values(&["kraken", "binance"])
+                .help("Exchange to trade on, pairs use its symbols, e.g. XXBTZUSD on kraken and BTCUSDT on binance. Can also set with env variable: EXCHANGE. This commandline argument take precedence")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("config")
+                .long("config")
+                .value_name("CONFIG")
+                .help("Load settings from a TOML or YAML file. Can also set with env variable: CONFIG. Command line arguments and env variables take precedence over the file")
+                .takes_value(true),
+        )
+        .subcommand(
+            SubCommand::with_name("check-config")
+                .about("Validate the settings and exit"),
+        )
+        .subcommand(
+            SubCommand::with_name("fetch-candles")
+                .about("Download historical candles into a local append-only store")
+                .arg(
+                    Arg::with_name("pair")
+                        .long("pair")
+                        .value_name("PAIR")
+                        .help("Pair to download [default: the configured pair]")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("interval")
+                        .long("interval")
+                        .value_name("MINUTES")
+                        .help("Candle interval: 1, 5, 15, 30, 60, 240, 1440, 10080 or 21600")
+                        .takes_value(true)
+                        .default_value("60"),
+                )
+                .arg(
+                    Arg::with_name("since")
+                        .long("since")
+                        .value_name("TIME")
+                        .help("Unix time or RFC 3339 date of the first candle of a new store [default: 720 candles ago]. An existing store continues after its last candle")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("dir")
+                        .long("dir")
+                        .value_name("DIR")
+                        .help("Store directory, candles are written to DIR/<pair>_<interval>.csv")
+                        .takes_value(true)
+                        .default_value("candles"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("backtest")
+                .about("Replay historical candles through the configured strategy")
+                .arg(
+                    Arg::with_name("candles")
+                        .long("candles")
+                        .value_name("FILE")
+                        .help("Candle file, CSV (time,open,high,low,close,vwap,volume,count) or JSON exported from Kraken's OHLC endpoint")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("out")
+                        .long("out")
+                        .value_name("DIR")
+                        .help("Write trades.csv and equity.csv into DIR")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("balance")
+                        .long("balance")
+                        .value_name("QUOTE")
+                        .help("Initial quote balance [default: 10000]")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("fee_maker")
+                        .long("fee_maker")
+                        .value_name("PCT")
+                        .help("Maker fee in percent [default: 0.16]")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("fee_taker")
+                        .long("fee_taker")
+                        .value_name("PCT")
+                        .help("Taker fee in percent [default: 0.26]")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("slippage")
+                        .long("slippage")
+                        .value_name("PCT")
+                        .help("Slippage on market and stop orders in percent [default: 0.05]")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("latency")
+                        .long("latency")
+                        .value_name("CANDLES")
+                        .help("Candles before a placed order can fill [default: 1]")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("warmup")
+                        .long("warmup")
+                        .value_name("CANDLES")
+                        .help("Candles of history available before the first cycle [default: 50]")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("arbitrage")
+                .about("Scan triangular cycles across Kraken pairs, e.g. USD -> XBT -> ETH -> USD, for profits after fees")
+                .arg(
+                    Arg::with_name("start")
+                        .long("start")
+                        .value_name("ASSET")
+                        .help("Kraken asset the cycles start and end with")
+                        .takes_value(true)
+                        .default_value("ZUSD"),
+                )
+                .arg(
+                    Arg::with_name("amount")
+                        .long("amount")
+                        .value_name("AMOUNT")
+                        .help("Amount of the start asset put through a cycle [default: 100]")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("fee")
+                        .long("fee")
+                        .value_name("PCT")
+                        .help("Taker fee paid on every leg in percent [default: 0.26]")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("min_profit")
+                        .long("min_profit")
+                        .value_name("PCT")
+                        .help("Report cycles earning at least this much after fees, in percent [default: 0.1]")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("depth")
+                        .long("depth")
+                        .value_name("LEVELS")
+                        .help("Order book levels checked for each candidate [default: 25]")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("interval")
+                        .long("interval")
+                        .value_name("SECONDS")
+                        .help("Seconds between scans [default: 10]")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("execute")
+                        .long("execute")
+                        .help("Trade the best opportunity of each scan as a sequence of immediate-or-cancel orders. Needs API keys, --dry_run only validates the orders"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("spread")
+                .about("Compare the best bid and ask of one asset across exchanges and report spreads above the fees")
+                .arg(
+                    Arg::with_name("venue")
+                        .long("venue")
+                        .value_name("EXCHANGE:PAIR[:FEE]")
+                        .help("Market to compare, e.g. kraken:XXBTZUSD or binance:BTCUSDT:0.1. The taker fee in percent defaults to the base tier of the exchange")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("threshold")
+                        .long("threshold")
+                        .value_name("PCT")
+                        .help("Report spreads exceeding the fees of both venues by this much, in percent [default: 0.1]")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("interval")
+                        .long("interval")
+                        .value_name("SECONDS")
+                        .help("Seconds between polls [default: 10]")
+                        .takes_value(true),
+                ),
+        )
         .get_matches();
 
     env_logger::init();
 
+    // Precedence: command line > env variables > config file > defaults
+    let config_path = matches
+        .value_of("config")
+        .map(|s| s.to_owned())
+        .or(env::var("CONFIG").ok());
+
+    let settings = match config_path {
+        Some(path) => Settings::load(Path::new(&path)).unwrap_or_else(|e| {
+            println!("{}", e);
+            std::process::exit(1);
+        }),
+        None => Settings::default(),
+    };
+
     let api_key = matches
         .value_of("api_key")
         .map(|s| s.to_owned())
-        .or(env::var("API_KEY").ok());
+        .or(env::var("API_KEY").ok())
+        .or(settings.api_key);
 
     let api_secret = matches
         .value_of("api_secret")
         .map(|s| s.to_owned())
-        .or(env::var("API_SECRET").ok());
+        .or(env::var("API_SECRET").ok())
+        .or(settings.api_secret);
 
-    let trading_cadence = matches
-        .value_of("trading_cadence")
+    let exchange = match matches
+        .value_of("exchange")
         .map(|s| s.to_owned())
-        .or(env::var("TRADIND_CADENCE").ok())
-        .unwrap_or_else(|| "10".to_owned())
-        .parse::<u64>()
-        .unwrap();
+        .or(env::var("EXCHANGE").ok())
+    {
+        Some(exchange) => exchange.parse::<Exchange>().unwrap_or_else(|e| {
+            println!("{}", e);
+            std::process::exit(1);
+        }),
+        None => settings.exchange,
+    };
+
+    let bots = settings.bots;
+    let mut config = settings.trading;
 
-    if api_key.is_none() || api_secret.is_none() {
+    let overrides = ConfigOverrides {
+        trading_cadence: matches
+            .value_of("trading_cadence")
+            .map(|s| s.to_owned())
+            .or(env::var("TRADIND_CADENCE").ok())
+            .map(|trading_cadence| {
+                trading_cadence.parse::<u64>().unwrap_or_else(|_| {
+                    println!("TRADIND_CADENCE should be a number of seconds");
+                    std::process::exit(1);
+                })
+            }),
+        mode: matches.value_of("mode").map(|mode| {
+            mode.parse::<TradingMode>().unwrap_or_else(|e| {
+                println!("{}", e);
+                std::process::exit(1);
+            })
+        }),
+        dry_run: matches.is_present("dry_run"),
+    };
+    overrides.apply(&mut config);
+
+    if let Err(e) = config.validate() {
+        println!("{}", e);
+        std::process::exit(1);
+    }
+    let strategy = match strategy::from_config(&config) {
+        Ok(strategy) => strategy,
+        Err(e) => {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if exchange != Exchange::Kraken
+        && (matches.subcommand_matches("fetch-candles").is_some()
+            || matches.subcommand_matches("arbitrage").is_some())
+    {
+        println!("fetch-candles and arbitrage only support kraken");
+        std::process::exit(1);
+    }
+
+    if let Some(fetch_matches) = matches.subcommand_matches("fetch-candles") {
+        let pair = fetch_matches.value_of("pair").unwrap_or(&config.pair);
+        if let Err(e) = fetch_candles(fetch_matches, pair).await {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(backtest_matches) = matches.subcommand_matches("backtest") {
+        let defaults = SimConfig::default();
+        let sim_config = SimConfig {
+            fees: FeeModel {
+                maker_pct: number_arg(backtest_matches, "fee_maker", defaults.fees.maker_pct),
+                taker_pct: number_arg(backtest_matches, "fee_taker", defaults.fees.taker_pct),
+            },
+            slippage_pct: number_arg(backtest_matches, "slippage", defaults.slippage_pct),
+            latency: number_arg(backtest_matches, "latency", defaults.latency),
+            initial_balance: number_arg(backtest_matches, "balance", defaults.initial_balance),
+            warmup: number_arg(backtest_matches, "warmup", defaults.warmup),
+        };
+
+        let candles = load_candles(Path::new(backtest_matches.value_of("candles").unwrap()))
+            .unwrap_or_else(|e| {
+                println!("{}", e);
+                std::process::exit(1);
+            });
+        let report = backtest::run_backtest(config, strategy, candles, sim_config)
+            .await
+            .unwrap_or_else(|e| {
+                println!("{}", e);
+                std::process::exit(1);
+            });
+
+        println!("{}", report.stats);
+        if let Some(out) = backtest_matches.value_of("out") {
+            if let Err(e) = report.write_csv(Path::new(out)) {
+                println!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(arbitrage_matches) = matches.subcommand_matches("arbitrage") {
+        let execute = arbitrage_matches.is_present("execute");
+        if execute && (api_key.is_none() || api_secret.is_none()) {
+            println!("API_KEY and API_SECRET are required to execute");
+            std::process::exit(1);
+        }
+        let mut kraken = Kraken::new(
+            &api_key.unwrap_or_default(),
+            &api_secret.unwrap_or_default(),
+        );
+        kraken.set_dry_run(config.dry_run);
+        if let Err(e) = arbitrage(arbitrage_matches, kraken, execute).await {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(spread_matches) = matches.subcommand_matches("spread") {
+        if let Err(e) = spread(spread_matches).await {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if !bots.is_empty() {
+        let setups =
+            bot_setups(bots, exchange, &api_key, &api_secret, &overrides).unwrap_or_else(|e| {
+                println!("{}", e);
+                std::process::exit(1);
+            });
+        if matches.subcommand_matches("check-config").is_some() {
+            for setup in &setups {
+                println!("Config OK for {}: {:#?}", setup.name, setup.config);
+            }
+            return;
+        }
+
+        let supervisor = build_supervisor(setups).await.unwrap_or_else(|e| {
+            println!("{}", e);
+            std::process::exit(1);
+        });
+        for (name, exit) in supervisor.run(shutdown_signal()).await {
+            println!("Bot {} {}", name, exit);
+        }
+        return;
+    }
+
+    // Paper trading only reads public market data.
+    if config.mode == TradingMode::Live && (api_key.is_none() || api_secret.is_none()) {
         println!("API_KEY and API_SECRET are required");
         std::process::exit(1);
     }
 
-    let config = TradingConfig {};
-    let kraken = Kraken::new(&api_key.unwrap(), &api_secret.unwrap());
-    // intialize the TradingBot for kraken context
-    let mut kraken_bot = TradingBot::new(config, Box::new(kraken));
+    if matches.subcommand_matches("check-config").is_some() {
+        println!("Config OK: {:#?}", config);
+        return;
+    }
 
-    let mut interval = time::interval(time::Duration::from_secs(trading_cadence));
+    let market = trading_market(
+        exchange,
+        &api_key.unwrap_or_default(),
+        &api_secret.unwrap_or_default(),
+        &config,
+        &mut HashMap::new(),
+    )
+    .await
+    .unwrap_or_else(|e| {
+        println!("{}", e);
+        std::process::exit(1);
+    });
+    let mut interval = time::interval(time::Duration::from_secs(config.trading_cadence));
+    // intialize the TradingBot for kraken context
+    let mut kraken_bot = TradingBot::new(config, market, strategy);
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    let mut failures = 0;
 
     loop {
-        // wait every 20s
-        interval.tick().await;
+        // wait every 20s, a cycle in flight finishes before shutting down
+        tokio::select! {
+            // Shutdown first, a tick already due must not start one more cycle.
+            biased;
+            _ = &mut shutdown => break,
+            _ = interval.tick() => {}
+        }
 
         // trading start time
         let start = Instant::now();
@@ -83,14 +492,319 @@ async fn main() {
 
         // trading kick off
         warn!("[TRADE] start at {:?}", now);
-        kraken_bot
-            .start()
-            .await
-            .expect("Fail to start a trade process");
+        if let Err(e) = kraken_bot.start().await {
+            failures += 1;
+            warn!(
+                "[TRADE] cycle failed ({}/{}): {}",
+                failures, MAX_BOT_FAILURES, e
+            );
+            if failures >= MAX_BOT_FAILURES {
+                break;
+            }
+            continue;
+        }
+        failures = 0;
 
         // trading end time
         let duration = start.elapsed();
         info!("[TRADE] end elapsed : {:?}", duration);
         info!(">>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>");
     }
+
+    warn!("[TRADE] shutting down");
+    println!("Stopped {}", kraken_bot.shutdown().await);
+}
+
+/// Resolves on SIGINT (Ctrl-C) or SIGTERM.
+async fn shutdown_signal() {
+    let mut terminate =
+        signal::unix::signal(SignalKind::terminate()).expect("fail to listen for SIGTERM");
+    tokio::select! {
+        _ = signal::ctrl_c() => {}
+        _ = terminate.recv() => {}
+    }
+}
+
+/// Market of `exchange` trading `config`, wrapped in a paper market in paper mode.
+/// Kraken markets of the same account share their client from `kraken_clients`.
+async fn trading_market(
+    exchange: Exchange,
+    api_key: &str,
+    api_secret: &str,
+    config: &TradingConfig,
+    kraken_clients: &mut HashMap<String, Arc<KrakenAPI>>,
+) -> Result<Box<dyn Market>, Box<dyn Error>> {
+    let market: Box<dyn Market> = match exchange {
+        Exchange::Kraken => {
+            let client = kraken_clients.entry(api_key.to_owned()).or_insert_with(|| {
+                Arc::new(KrakenAPI::new(api_key.to_owned(), api_secret.to_owned()))
+            });
+            Box::new(Kraken::with_client(client.clone()))
+        }
+        Exchange::Binance => {
+            let mut binance = Binance::new(api_key, api_secret);
+            // Balances are reported in the quote asset of the traded symbol.
+            binance.quote_asset = binance.get_symbol(&config.pair).await?.quote_asset;
+            Box::new(binance)
+        }
+    };
+
+    Ok(match config.mode {
+        TradingMode::Live => market,
+        TradingMode::Paper => {
+            warn!(
+                "[PAPER] {} with a virtual balance of {}",
+                config.pair, config.paper_balance
+            );
+            Box::new(PaperMarket::new(
+                market,
+                config.paper_balance,
+                FeeModel::default(),
+            ))
+        }
+    })
+}
+
+/// Trading settings given on the command line or in the environment. They take precedence over
+/// the config file for the top-level bot and every `[[bots]]` entry.
+struct ConfigOverrides {
+    trading_cadence: Option<u64>,
+    mode: Option<TradingMode>,
+    dry_run: bool,
+}
+
+impl ConfigOverrides {
+    fn apply(&self, config: &mut TradingConfig) {
+        if let Some(trading_cadence) = self.trading_cadence {
+            config.trading_cadence = trading_cadence;
+        }
+        if let Some(mode) = self.mode {
+            config.mode = mode;
+        }
+        config.dry_run = config.dry_run || self.dry_run;
+    }
+}
+
+/// A `[[bots]]` entry resolved against the top-level settings.
+struct BotSetup {
+    name: String,
+    exchange: Exchange,
+    api_key: String,
+    api_secret: String,
+    config: TradingConfig,
+    strategy: Box<dyn Strategy>,
+}
+
+/// Validate every `[[bots]]` entry, filling in the top-level exchange and credentials and
+/// applying the `overrides`.
+fn bot_setups(
+    bots: Vec<BotSettings>,
+    exchange: Exchange,
+    api_key: &Option<String>,
+    api_secret: &Option<String>,
+    overrides: &ConfigOverrides,
+) -> Result<Vec<BotSetup>, Box<dyn Error>> {
+    bots.into_iter()
+        .enumerate()
+        .map(|(i, bot)| {
+            let name = if bot.name.is_empty() {
+                format!("bot{}", i + 1)
+            } else {
+                bot.name
+            };
+            let mut config = bot.trading;
+            overrides.apply(&mut config);
+            config
+                .validate()
+                .map_err(|e| format!("bot {}: {}", name, e))?;
+            let strategy =
+                strategy::from_config(&config).map_err(|e| format!("bot {}: {}", name, e))?;
+
+            let api_key = bot.api_key.or_else(|| api_key.clone());
+            let api_secret = bot.api_secret.or_else(|| api_secret.clone());
+            if config.mode == TradingMode::Live && (api_key.is_none() || api_secret.is_none()) {
+                return Err(format!("bot {}: API_KEY and API_SECRET are required", name).into());
+            }
+
+            Ok(BotSetup {
+                name,
+                exchange: bot.exchange.unwrap_or(exchange),
+                api_key: api_key.unwrap_or_default(),
+                api_secret: api_secret.unwrap_or_default(),
+                config,
+                strategy,
+            })
+        })
+        .collect()
+}
+
+/// Supervisor of the bots, those trading on the same Kraken account share its client and
+/// rate limits.
+async fn build_supervisor(setups: Vec<BotSetup>) -> Result<Supervisor, Box<dyn Error>> {
+    let mut kraken_clients = HashMap::new();
+    let mut supervisor = Supervisor::new(MAX_BOT_FAILURES);
+    for setup in setups {
+        let market = trading_market(
+            setup.exchange,
+            &setup.api_key,
+            &setup.api_secret,
+            &setup.config,
+            &mut kraken_clients,
+        )
+        .await
+        .map_err(|e| format!("bot {}: {}", setup.name, e))?;
+        supervisor.add(
+            &setup.name,
+            TradingBot::new(setup.config, market, setup.strategy),
+        );
+    }
+    Ok(supervisor)
+}
+
+/// Value of the numeric argument `name`, or `default` when absent. Exits on invalid input.
+fn number_arg<T: FromStr>(matches: &ArgMatches, name: &str, default: T) -> T {
+    match matches.value_of(name) {
+        Some(value) => value.parse::<T>().unwrap_or_else(|_| {
+            println!("--{} should be a number", name);
+            std::process::exit(1);
+        }),
+        None => default,
+    }
+}
+
+/// Scan for triangular arbitrage until interrupted, trading the best opportunity of each scan
+/// when `execute` is set.
+async fn arbitrage(
+    matches: &ArgMatches<'_>,
+    kraken: Kraken,
+    execute: bool,
+) -> Result<(), Box<dyn Error>> {
+    let defaults = ArbitrageConfig::default();
+    let config = ArbitrageConfig {
+        start: matches.value_of("start").unwrap().to_owned(),
+        amount: number_arg(matches, "amount", defaults.amount),
+        fee_pct: number_arg(matches, "fee", defaults.fee_pct),
+        min_profit_pct: number_arg(matches, "min_profit", defaults.min_profit_pct),
+        depth: number_arg(matches, "depth", defaults.depth),
+    };
+    let start = config.start.clone();
+    let scanner = Scanner::new(kraken, config).await?;
+    println!(
+        "Scanning {} triangular cycles through {}",
+        scanner.cycles().len(),
+        start
+    );
+
+    let mut interval = time::interval(time::Duration::from_secs(number_arg(
+        matches, "interval", 10,
+    )));
+    loop {
+        interval.tick().await;
+        let opportunities = match scanner.scan().await {
+            Ok(opportunities) => opportunities,
+            Err(e) => {
+                warn!("Scan failed: {}", e);
+                continue;
+            }
+        };
+        for opportunity in &opportunities {
+            println!("[{}] {}", Local::now().format("%H:%M:%S"), opportunity);
+        }
+
+        if let Some(best) = opportunities.first().filter(|_| execute) {
+            match scanner.execute(best).await {
+                Ok(result) => println!("Executed {}: got {} {}", best.cycle, result, start),
+                Err(e) => println!("Execution of {} stopped: {}", best.cycle, e),
+            }
+        }
+    }
+}
+
+/// Report cross-exchange spreads of one asset until interrupted.
+async fn spread(matches: &ArgMatches<'_>) -> Result<(), Box<dyn Error>> {
+    let venues = matches
+        .values_of("venue")
+        .unwrap()
+        .map(parse_venue)
+        .collect::<Result<Vec<Venue>, _>>()?;
+    let monitor = SpreadMonitor::new(venues, number_arg(matches, "threshold", 0.1))?;
+    let names: Vec<String> = monitor
+        .venues()
+        .iter()
+        .map(|venue| format!("{} {}", venue.name, venue.pair))
+        .collect();
+    println!("Comparing {}", names.join(", "));
+
+    let mut interval = time::interval(time::Duration::from_secs(number_arg(
+        matches, "interval", 10,
+    )));
+    loop {
+        interval.tick().await;
+        for alert in monitor.check().await {
+            println!("[{}] {}", Local::now().format("%H:%M:%S"), alert);
+        }
+    }
+}
+
+/// Venue of a `--venue EXCHANGE:PAIR[:FEE]` argument, reading public market data only.
+fn parse_venue(arg: &str) -> Result<Venue, Box<dyn Error>> {
+    let parts: Vec<&str> = arg.split(':').collect();
+    let (name, pair) = match parts.as_slice() {
+        [name, pair] | [name, pair, _] => (*name, *pair),
+        _ => return Err(format!("--venue {} should be EXCHANGE:PAIR[:FEE]", arg).into()),
+    };
+    let exchange = name.parse::<Exchange>()?;
+    let fee_pct = match parts.get(2) {
+        Some(fee) => fee
+            .parse::<f32>()
+            .map_err(|_| format!("fee of --venue {} should be a number", arg))?,
+        None => exchange.taker_fee_pct(),
+    };
+    let market: Box<dyn Market> = match exchange {
+        Exchange::Kraken => Box::new(Kraken::new("", "")),
+        Exchange::Binance => Box::new(Binance::new("", "")),
+    };
+    Ok(Venue {
+        name: name.to_owned(),
+        market,
+        pair: pair.to_owned(),
+        fee_pct,
+    })
+}
+
+/// Download candles of `pair` into the store and report its gaps.
+async fn fetch_candles(matches: &ArgMatches<'_>, pair: &str) -> Result<(), Box<dyn Error>> {
+    let interval = number_arg(matches, "interval", 60u32);
+    let store = CandleStore::open(Path::new(matches.value_of("dir").unwrap()), pair, interval)?;
+
+    let since = match store.last_time()? {
+        Some(last) => {
+            if matches.is_present("since") {
+                warn!(
+                    "{} is append-only, continuing after {}",
+                    store.path.display(),
+                    last
+                );
+            }
+            last + Duration::minutes(i64::from(interval))
+        }
+        None => match matches.value_of("since") {
+            Some(since) => match since.parse::<i64>() {
+                Ok(epoch) => Utc.timestamp(epoch, 0),
+                Err(_) => DateTime::parse_from_rfc3339(since)?.with_timezone(&Utc),
+            },
+            None => Utc::now() - Duration::minutes(i64::from(interval) * 720),
+        },
+    };
+
+    // Public endpoints only.
+    let kraken = Kraken::new("", "");
+    let candles = marketdata::download_candles(&kraken, pair, interval, since).await?;
+    let written = store.append(&candles)?;
+    println!("{} candles appended to {}", written, store.path.display());
+
+    for (from, to) in store.gaps()? {
+        println!("Gap: no candle from {} to {}", from, to);
+    }
+    Ok(())
 }
@@ -0,0 +1,194 @@
+/// Binance REST API
+use super::types::BinanceError;
+use crate::kraken::api::{rate_limiter::RateLimiter, utils::get_hmac_sha256};
+use chrono::Utc;
+use data_encoding::HEXLOWER;
+use log::trace;
+use reqwest::header::{HeaderMap, USER_AGENT};
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use std::error::Error;
+
+const API_URL: &str = "https://api.binance.com";
+const API_USER_AGENT: &str = "Binance Rust API Agent";
+/// Milliseconds a signed request stays valid after its timestamp.
+const RECV_WINDOW: u32 = 5000;
+/// Request weight allowed per minute: up to 1200, decaying by 20 per second.
+const WEIGHT_LIMIT: (f32, f32) = (1200.0, 20.0);
+
+/// Binance API Struct
+pub struct BinanceAPI {
+    pub api_key: String,
+    pub secret: String,
+    pub client: reqwest::blocking::Client,
+    /// Request weight shared by public and signed endpoints.
+    pub weight_limit: RateLimiter,
+}
+
+impl BinanceAPI {
+    pub fn new(api_key: String, secret: String) -> BinanceAPI {
+        BinanceAPI {
+            api_key,
+            secret,
+            client: reqwest::blocking::Client::new(),
+            weight_limit: RateLimiter::new(WEIGHT_LIMIT.0, WEIGHT_LIMIT.1),
+        }
+    }
+
+    /// query public endpoints, e.g. "/api/v3/depth"
+    pub async fn query_public<T>(
+        &self,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> Result<T, Box<dyn Error>>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        self.weight_limit
+            .acquire(request_weight(&Method::GET, path, params))
+            .await;
+        let url = format!("{}{}?{}", API_URL, path, query_string(params));
+        trace!("Query request url: {}", url);
+
+        self.do_request(Method::GET, &url, &HeaderMap::new()).await
+    }
+
+    /// query endpoints signed with the api secret
+    pub async fn query_signed<T>(
+        &self,
+        method: Method,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> Result<T, Box<dyn Error>>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        self.weight_limit
+            .acquire(request_weight(&method, path, params))
+            .await;
+        let mut params = params.to_vec();
+        params.push(("recvWindow", RECV_WINDOW.to_string()));
+        params.push(("timestamp", Utc::now().timestamp_millis().to_string()));
+        let query = query_string(&params);
+        let signature = create_signature(&query, self.secret.as_bytes());
+        let url = format!("{}{}?{}&signature={}", API_URL, path, query, signature);
+
+        let mut header_map = HeaderMap::new();
+        header_map.insert(
+            "X-MBX-APIKEY",
+            self.api_key.parse().expect("fail to parse api key"),
+        );
+
+        trace!("Query request method: {} {}", method, path);
+        trace!("Query request params: {:?}", params);
+
+        self.do_request(method, &url, &header_map).await
+    }
+
+    /// Send Http reqeust and parse the response, on the blocking thread pool as the client
+    /// is blocking.
+    async fn do_request<T>(
+        &self,
+        method: Method,
+        url: &str,
+        headers: &HeaderMap,
+    ) -> Result<T, Box<dyn Error>>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let mut header_map = HeaderMap::new();
+        header_map.insert(USER_AGENT, API_USER_AGENT.parse().unwrap());
+
+        // Additional headers.
+        for (n, v) in headers {
+            header_map.insert(n, v.to_owned());
+        }
+
+        let request = self.client.request(method, url).headers(header_map);
+        let res =
+            tokio::task::spawn_blocking(move || -> Result<T, Box<dyn Error + Send + Sync>> {
+                let res = request.send()?;
+
+                if !res.status().is_success() {
+                    let status_code = res.status();
+                    let mut error: BinanceError = res.json()?;
+                    error.status_code = Some(status_code);
+                    return Err(Box::new(error));
+                }
+
+                Ok(res.json()?)
+            })
+            .await?;
+
+        res.map_err(|e| -> Box<dyn Error> { e })
+    }
+}
+
+/// Hex encoded HMAC-SHA256 of the query string.
+pub fn create_signature(query: &str, secret: &[u8]) -> String {
+    HEXLOWER.encode(get_hmac_sha256(query.as_bytes(), secret).as_ref())
+}
+
+/// Weight of a request, as listed in the API docs for the endpoints used.
+fn request_weight(method: &Method, path: &str, params: &[(&str, String)]) -> f32 {
+    let param = |key: &str| {
+        params
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, val)| val.as_str())
+    };
+    let get = *method == Method::GET;
+    match path {
+        "/api/v3/exchangeInfo" | "/api/v3/account" | "/api/v3/myTrades" => 10.0,
+        "/api/v3/depth" => match param("limit").and_then(|limit| limit.parse().ok()) {
+            Some(limit) if limit <= 100 => 1.0,
+            Some(500) => 5.0,
+            Some(1000) => 10.0,
+            _ => 50.0,
+        },
+        "/api/v3/ticker/price" if param("symbol").is_none() => 2.0,
+        "/api/v3/openOrders" if get && param("symbol").is_none() => 40.0,
+        "/api/v3/openOrders" if get => 3.0,
+        "/api/v3/order" if get => 2.0,
+        _ => 1.0,
+    }
+}
+
+/// Parameters in order, the signature covers the query string as sent.
+fn query_string(params: &[(&str, String)]) -> String {
+    params
+        .iter()
+        .map(|(key, val)| format!("{}={}", key, urlencoding::encode(val)))
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_weight() {
+        let symbol = [("symbol", "BTCUSDT".to_owned())];
+        assert_eq!(
+            request_weight(&Method::GET, "/api/v3/openOrders", &symbol),
+            3.0
+        );
+        assert_eq!(
+            request_weight(&Method::GET, "/api/v3/openOrders", &[]),
+            40.0
+        );
+        assert_eq!(
+            request_weight(&Method::DELETE, "/api/v3/order", &symbol),
+            1.0
+        );
+        assert_eq!(
+            request_weight(
+                &Method::GET,
+                "/api/v3/depth",
+                &[("limit", "500".to_owned())]
+            ),
+            5.0
+        );
+    }
+}
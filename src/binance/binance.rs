@@ -0,0 +1,36 @@
+use super::{api::BinanceAPI, types::SymbolInfo};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// Order polled for fills by the feed of `subscribe_fills`.
+#[derive(Debug, Clone)]
+pub struct WatchedOrder {
+    pub symbol: SymbolInfo,
+    pub order_id: u64,
+    /// Ids of the trades already reported.
+    pub trades: HashSet<u64>,
+    /// Quantity executed when last polled.
+    pub executed: f32,
+    /// Trades up to this time, in milliseconds since the epoch, predate the watch and are not
+    /// reported.
+    pub since: i64,
+}
+
+/// Binance spot market.
+///
+/// Binance order ids are only unique per symbol, ids returned by `place_order` are
+/// "<symbol>:<orderId>", e.g. "BTCUSDT:28457".
+pub struct Binance {
+    /// Shared with the fill feed.
+    pub api_client: Arc<BinanceAPI>,
+    /// Asset reported by `get_balances`, e.g. "USDT".
+    pub quote_asset: String,
+    /// Symbols looked up by `get_symbol`, they do not change while running.
+    pub symbols: Mutex<HashMap<String, SymbolInfo>>,
+    /// Our orders still open, by id, polled by the fill feed.
+    pub watched: Arc<Mutex<HashMap<String, WatchedOrder>>>,
+    /// Ids of the orders the fill feed saw closed before they were fully filled.
+    pub closed: Arc<Mutex<Vec<String>>>,
+    /// Submit orders to the test endpoint only.
+    pub dry_run: bool,
+}
@@ -0,0 +1,707 @@
+use super::{
+    api::BinanceAPI,
+    binance::{Binance, WatchedOrder},
+    types::{
+        AccountInfo, Depth, ExchangeInfo, MyTrade, NewOrderResult, OrderInfo, SymbolFilter,
+        SymbolInfo, TickerPrice,
+    },
+};
+use crate::marketdata::Timeframe;
+use crate::tradingbot::{
+    candle::Candle,
+    market::Market,
+    order::{
+        Fill, InvalidOrder, OpenOrder, OrderFlag, OrderRequest, OrderSide, OrderTime, OrderType,
+        Price, TimeInForce, DRY_RUN_ORDER_PREFIX,
+    },
+    order_book::OrderBook,
+};
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use log::{debug, info, warn};
+use rand::Rng;
+use reqwest::Method;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::time;
+
+/// Prefix of the client order ids carrying a `userref`, e.g. "tb4712_1F3A9C2B".
+const USERREF_PREFIX: &str = "tb";
+/// Book sizes the depth endpoint accepts.
+const DEPTH_LIMITS: [u32; 8] = [5, 10, 20, 50, 100, 500, 1000, 5000];
+/// Candles requested at once, as many as Kraken serves.
+const KLINES_LIMIT: u32 = 720;
+/// Seconds between two polls of the fill feed.
+const FILL_POLL_SECS: u64 = 5;
+/// Most recent trades fetched per symbol by the fill feed, the most the endpoint serves.
+const TRADES_LIMIT: u32 = 1000;
+
+impl Binance {
+    pub fn new(api_key: &str, secret: &str) -> Self {
+        Binance {
+            api_client: Arc::new(BinanceAPI::new(api_key.to_owned(), secret.to_owned())),
+            quote_asset: "USDT".to_owned(),
+            symbols: Mutex::new(HashMap::new()),
+            watched: Arc::new(Mutex::new(HashMap::new())),
+            closed: Arc::new(Mutex::new(Vec::new())),
+            dry_run: false,
+        }
+    }
+
+    /// Details of `symbol`, e.g. its base and quote assets. Looked up once, then cached.
+    pub async fn get_symbol(&self, symbol: &str) -> Result<SymbolInfo, Box<dyn Error>> {
+        if let Some(info) = self.symbols.lock().unwrap().get(symbol) {
+            return Ok(info.clone());
+        }
+
+        let info = self
+            .api_client
+            .query_public::<ExchangeInfo>("/api/v3/exchangeInfo", &[("symbol", symbol.to_owned())])
+            .await?
+            .symbols
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("unknown symbol {}", symbol))?;
+        self.symbols
+            .lock()
+            .unwrap()
+            .insert(symbol.to_owned(), info.clone());
+        Ok(info)
+    }
+
+    /// Balance of `asset`, free and locked in orders. Zero when the account never held it.
+    pub async fn get_asset_balance(&self, asset: &str) -> Result<f32, Box<dyn Error>> {
+        let account = self
+            .api_client
+            .query_signed::<AccountInfo>(Method::GET, "/api/v3/account", &[])
+            .await?;
+
+        match account
+            .balances
+            .iter()
+            .find(|balance| balance.asset == asset)
+        {
+            Some(balance) => Ok(balance.free.parse::<f32>()? + balance.locked.parse::<f32>()?),
+            None => Ok(0.0),
+        }
+    }
+}
+
+#[async_trait]
+impl Market for Binance {
+    async fn get_balances(&self) -> Result<f32, Box<dyn Error>> {
+        self.get_asset_balance(&self.quote_asset).await
+    }
+
+    async fn get_base_balance(&self, pair: &str) -> Result<f32, Box<dyn Error>> {
+        let symbol = self.get_symbol(pair).await?;
+        self.get_asset_balance(&symbol.base_asset).await
+    }
+
+    async fn get_market_price(&self, pair: &str) -> Result<f32, Box<dyn Error>> {
+        let ticker = self
+            .api_client
+            .query_public::<TickerPrice>("/api/v3/ticker/price", &[("symbol", pair.to_owned())])
+            .await?;
+        info!("latest {} price is {}", ticker.symbol, ticker.price);
+
+        Ok(ticker.price.parse()?)
+    }
+
+    async fn get_candles(&self, pair: &str, interval: u32) -> Result<Vec<Candle>, Box<dyn Error>> {
+        let timeframe = Timeframe::from_minutes(interval)
+            .ok_or_else(|| format!("unsupported candle interval {}", interval))?;
+        let params = [
+            ("symbol", pair.to_owned()),
+            ("interval", timeframe.to_string()),
+            ("limit", KLINES_LIMIT.to_string()),
+        ];
+        let klines = self
+            .api_client
+            .query_public::<Vec<serde_json::Value>>("/api/v3/klines", &params)
+            .await?;
+
+        let mut candles = klines
+            .iter()
+            .map(parse_kline)
+            .collect::<Result<Vec<Candle>, _>>()?;
+        // The last entry is the candle still in progress.
+        candles.pop();
+        Ok(candles)
+    }
+
+    async fn get_order_book(&self, pair: &str, depth: u32) -> Result<OrderBook, Box<dyn Error>> {
+        let limit = DEPTH_LIMITS
+            .iter()
+            .find(|limit| **limit >= depth)
+            .unwrap_or(&DEPTH_LIMITS[DEPTH_LIMITS.len() - 1]);
+        let params = [("symbol", pair.to_owned()), ("limit", limit.to_string())];
+        let book = self
+            .api_client
+            .query_public::<Depth>("/api/v3/depth", &params)
+            .await?;
+
+        let levels = |side: Vec<(String, String)>| -> Result<Vec<(f32, f32)>, Box<dyn Error>> {
+            side.into_iter()
+                .take(depth as usize)
+                .map(|(price, volume)| Ok((price.parse::<f32>()?, volume.parse::<f32>()?)))
+                .collect()
+        };
+
+        Ok(OrderBook {
+            asks: levels(book.asks)?,
+            bids: levels(book.bids)?,
+        })
+    }
+
+    /// Quantities are rounded down to the `LOT_SIZE` step and prices to the `PRICE_FILTER`
+    /// tick of the symbol first.
+    async fn place_order(&self, order: &OrderRequest) -> Result<String, Box<dyn Error>> {
+        let symbol = self.get_symbol(&order.pair).await?;
+        let mut order = order.clone();
+        if let Some(lot_decimals) = symbol.lot_decimals() {
+            order = order.with_lot_decimals(lot_decimals);
+        }
+        if let Some(price_decimals) = symbol.price_decimals() {
+            order = order.with_price_decimals(price_decimals);
+        }
+        order.validate()?;
+        let mut params = new_order_params(&order)?;
+        if let Some(userref) = order.userref {
+            params.push((
+                "newClientOrderId",
+                format!(
+                    "{}{}_{:08X}",
+                    USERREF_PREFIX,
+                    userref,
+                    rand::thread_rng().gen::<u32>()
+                ),
+            ));
+        }
+
+        if self.dry_run {
+            self.api_client
+                .query_signed::<serde_json::Value>(Method::POST, "/api/v3/order/test", &params)
+                .await?;
+            info!("[DRY RUN] would place: {:?}", params);
+            return Ok(format!(
+                "{}{:08X}",
+                DRY_RUN_ORDER_PREFIX,
+                rand::thread_rng().gen::<u32>()
+            ));
+        }
+
+        let res = self
+            .api_client
+            .query_signed::<NewOrderResult>(Method::POST, "/api/v3/order", &params)
+            .await?;
+        info!("Order placed: {} {:?}", res.order_id, params);
+        let order_id = format!("{}:{}", res.symbol, res.order_id);
+        self.watched.lock().unwrap().insert(
+            order_id.clone(),
+            WatchedOrder {
+                symbol,
+                order_id: res.order_id,
+                trades: HashSet::new(),
+                executed: 0.0,
+                since: 0,
+            },
+        );
+        Ok(order_id)
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<(), Box<dyn Error>> {
+        if order_id.starts_with(DRY_RUN_ORDER_PREFIX) {
+            info!("[DRY RUN] would cancel: {}", order_id);
+            return Ok(());
+        }
+
+        let mut parts = order_id.splitn(2, ':');
+        let (symbol, id) = match (parts.next(), parts.next()) {
+            (Some(symbol), Some(id)) => (symbol, id),
+            _ => return Err(format!("invalid Binance order id {}", order_id).into()),
+        };
+        let params = [("symbol", symbol.to_owned()), ("orderId", id.to_owned())];
+        self.api_client
+            .query_signed::<serde_json::Value>(Method::DELETE, "/api/v3/order", &params)
+            .await?;
+        info!("Order {} cancelled", order_id);
+        Ok(())
+    }
+
+    /// The orders are watched by the fill feed from then on, their earlier trades are not
+    /// reported.
+    async fn get_open_orders(&self) -> Result<Vec<OpenOrder>, Box<dyn Error>> {
+        let orders = self
+            .api_client
+            .query_signed::<Vec<OrderInfo>>(Method::GET, "/api/v3/openOrders", &[])
+            .await?;
+
+        let mut open_orders = Vec::new();
+        for info in orders {
+            // Stop orders without a limit report a price of 0.
+            let price: f32 = info.price.parse()?;
+            let price = match &info.stop_price {
+                Some(stop_price) if price == 0.0 => stop_price.parse()?,
+                _ => price,
+            };
+            let order = OpenOrder {
+                id: format!("{}:{}", info.symbol, info.order_id),
+                pair: info.symbol.clone(),
+                side: match info.side.as_str() {
+                    "BUY" => OrderSide::Buy,
+                    _ => OrderSide::Sell,
+                },
+                price,
+                volume: info.orig_qty.parse()?,
+                volume_executed: info.executed_qty.parse()?,
+                userref: parse_userref(&info.client_order_id),
+            };
+
+            let symbol = self.get_symbol(&info.symbol).await?;
+            self.watched
+                .lock()
+                .unwrap()
+                .entry(order.id.clone())
+                .or_insert(WatchedOrder {
+                    symbol,
+                    order_id: info.order_id,
+                    trades: HashSet::new(),
+                    executed: order.volume_executed,
+                    since: info.update_time,
+                });
+            open_orders.push(order);
+        }
+        Ok(open_orders)
+    }
+
+    fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+
+    /// Binance pushes executions on a user data stream only, the orders placed or listed are
+    /// polled instead every `FILL_POLL_SECS` until closed.
+    async fn subscribe_fills(&self) -> Result<Option<UnboundedReceiver<Fill>>, Box<dyn Error>> {
+        let (fills, receiver) = mpsc::unbounded_channel();
+        let api_client = self.api_client.clone();
+        let watched = self.watched.clone();
+        let closed = self.closed.clone();
+        tokio::spawn(async move {
+            let mut interval = time::interval(time::Duration::from_secs(FILL_POLL_SECS));
+            while !fills.is_closed() {
+                interval.tick().await;
+                poll_fills(&api_client, &watched, &closed, &fills).await;
+            }
+            debug!("Fill receiver dropped, polling stopped");
+        });
+        Ok(Some(receiver))
+    }
+
+    fn take_closed_orders(&self) -> Vec<String> {
+        self.closed.lock().unwrap().drain(..).collect()
+    }
+}
+
+/// Send the new trades of every watched order to `fills`, forgetting the orders closed.
+async fn poll_fills(
+    api_client: &BinanceAPI,
+    watched: &Mutex<HashMap<String, WatchedOrder>>,
+    closed: &Mutex<Vec<String>>,
+    fills: &UnboundedSender<Fill>,
+) {
+    let mut by_symbol: HashMap<String, Vec<(String, WatchedOrder)>> = HashMap::new();
+    for (id, order) in watched.lock().unwrap().iter() {
+        by_symbol
+            .entry(order.symbol.symbol.clone())
+            .or_default()
+            .push((id.clone(), order.clone()));
+    }
+
+    for (symbol, orders) in by_symbol {
+        if let Err(e) = poll_symbol(api_client, &symbol, orders, watched, closed, fills).await {
+            warn!("Unable to poll orders of {}: {}", symbol, e);
+        }
+    }
+}
+
+/// Poll the watched `orders` of `symbol` with a single listing of its open orders. Trades are
+/// only fetched when one of the orders executed more or left the book.
+async fn poll_symbol(
+    api_client: &BinanceAPI,
+    symbol: &str,
+    orders: Vec<(String, WatchedOrder)>,
+    watched: &Mutex<HashMap<String, WatchedOrder>>,
+    closed: &Mutex<Vec<String>>,
+    fills: &UnboundedSender<Fill>,
+) -> Result<(), Box<dyn Error>> {
+    let open = api_client
+        .query_signed::<Vec<OrderInfo>>(
+            Method::GET,
+            "/api/v3/openOrders",
+            &[("symbol", symbol.to_owned())],
+        )
+        .await?;
+    let mut executed = HashMap::new();
+    for info in open {
+        executed.insert(info.order_id, info.executed_qty.parse::<f32>()?);
+    }
+
+    // Status of the orders gone from the book first, the trades then include every one of them.
+    let mut statuses = HashMap::new();
+    for (id, order) in &orders {
+        if executed.contains_key(&order.order_id) {
+            continue;
+        }
+        let params = [
+            ("symbol", symbol.to_owned()),
+            ("orderId", order.order_id.to_string()),
+        ];
+        let info = api_client
+            .query_signed::<OrderInfo>(Method::GET, "/api/v3/order", &params)
+            .await?;
+        statuses.insert(id.clone(), info.status);
+    }
+    let traded = orders.iter().any(|(_, order)| {
+        !executed
+            .get(&order.order_id)
+            .is_some_and(|qty| *qty <= order.executed)
+    });
+    if !traded {
+        return Ok(());
+    }
+
+    let params = [
+        ("symbol", symbol.to_owned()),
+        ("limit", TRADES_LIMIT.to_string()),
+    ];
+    let trades = api_client
+        .query_signed::<Vec<MyTrade>>(Method::GET, "/api/v3/myTrades", &params)
+        .await?;
+
+    let mut watched = watched.lock().unwrap();
+    for (id, order) in orders {
+        let (order_id, since) = (order.order_id, order.since);
+        let mut seen = order.trades;
+        let new_trades = trades
+            .iter()
+            .filter(|trade| trade.order_id == order_id && trade.time > since);
+        for trade in new_trades {
+            if !seen.insert(trade.id) {
+                continue;
+            }
+            match trade_fill(trade, &order.symbol) {
+                Ok(fill) => {
+                    fills.send(fill).ok();
+                }
+                Err(e) => warn!("Invalid trade {:?}: {}", trade, e),
+            }
+        }
+
+        match statuses.get(&id).map(String::as_str) {
+            None | Some("NEW") | Some("PARTIALLY_FILLED") | Some("PENDING_NEW") => {
+                if let Some(watched) = watched.get_mut(&id) {
+                    watched.trades = seen;
+                    if let Some(qty) = executed.get(&order_id) {
+                        watched.executed = *qty;
+                    }
+                }
+            }
+            Some("FILLED") => {
+                watched.remove(&id);
+            }
+            Some(status) => {
+                info!("Order {} closed: {}", id, status);
+                watched.remove(&id);
+                closed.lock().unwrap().push(id);
+            }
+        }
+    }
+    Ok(())
+}
+
+impl SymbolInfo {
+    /// Decimals of the `LOT_SIZE` step, quantities with more are rejected.
+    pub fn lot_decimals(&self) -> Option<u32> {
+        self.filters.iter().find_map(|filter| match filter {
+            SymbolFilter::LotSize { step_size } => step_decimals(step_size),
+            _ => None,
+        })
+    }
+
+    /// Decimals of the `PRICE_FILTER` tick, prices with more are rejected.
+    pub fn price_decimals(&self) -> Option<u32> {
+        self.filters.iter().find_map(|filter| match filter {
+            SymbolFilter::PriceFilter { tick_size } => step_decimals(tick_size),
+            _ => None,
+        })
+    }
+}
+
+/// Decimals of a power of ten step, e.g. 3 for "0.00100000".
+fn step_decimals(step: &str) -> Option<u32> {
+    let step = step.parse::<f64>().ok().filter(|step| *step > 0.0)?;
+    Some((-step.log10()).round().max(0.0) as u32)
+}
+
+fn trade_fill(trade: &MyTrade, symbol: &SymbolInfo) -> Result<Fill, Box<dyn Error>> {
+    let price: f32 = trade.price.parse()?;
+    let commission: f32 = trade.commission.parse()?;
+    let fee = if trade.commission_asset == symbol.quote_asset {
+        commission
+    } else if trade.commission_asset == symbol.base_asset {
+        commission * price
+    } else {
+        // Paid in BNB, not part of the traded pair.
+        0.0
+    };
+    Ok(Fill {
+        trade_id: trade.id.to_string(),
+        order_id: format!("{}:{}", trade.symbol, trade.order_id),
+        pair: trade.symbol.clone(),
+        side: if trade.is_buyer {
+            OrderSide::Buy
+        } else {
+            OrderSide::Sell
+        },
+        price,
+        volume: trade.qty.parse()?,
+        fee,
+        time: Utc.timestamp_millis(trade.time),
+    })
+}
+
+/// Map an order to Binance `POST /api/v3/order` parameters. Binance spot trading has no
+/// relative prices, leverage, conditional closes or scheduled orders.
+pub fn new_order_params(order: &OrderRequest) -> Result<Vec<(&'static str, String)>, InvalidOrder> {
+    if order.leverage.is_some() {
+        return Err(InvalidOrder(
+            "Binance spot orders do not support leverage".to_owned(),
+        ));
+    }
+    if order.close.is_some() {
+        return Err(InvalidOrder(
+            "Binance does not support conditional closes".to_owned(),
+        ));
+    }
+    if order
+        .start_time
+        .filter(|time| *time != OrderTime::Now)
+        .is_some()
+        || order.expire_time.is_some()
+    {
+        return Err(InvalidOrder(
+            "Binance does not support scheduled orders".to_owned(),
+        ));
+    }
+
+    let absolute = |price: Option<Price>| match price {
+        Some(Price::Absolute(price)) => Ok(price),
+        price => Err(InvalidOrder(format!(
+            "Binance needs absolute prices, got {:?}",
+            price
+        ))),
+    };
+    let post_only = order.flags.contains(&OrderFlag::PostOnly);
+
+    let mut params = vec![
+        ("symbol", order.pair.clone()),
+        (
+            "side",
+            match order.side {
+                OrderSide::Buy => "BUY".to_owned(),
+                OrderSide::Sell => "SELL".to_owned(),
+            },
+        ),
+    ];
+    // Kraken semantics: `price` is the trigger of stop orders, `price2` their limit.
+    let (order_type, price, stop_price) = match order.order_type {
+        OrderType::Market => ("MARKET", None, None),
+        OrderType::Limit if post_only => ("LIMIT_MAKER", Some(absolute(order.price)?), None),
+        OrderType::Limit => ("LIMIT", Some(absolute(order.price)?), None),
+        OrderType::StopLoss => ("STOP_LOSS", None, Some(absolute(order.price)?)),
+        OrderType::TakeProfit => ("TAKE_PROFIT", None, Some(absolute(order.price)?)),
+        OrderType::StopLossLimit => (
+            "STOP_LOSS_LIMIT",
+            Some(absolute(order.price2)?),
+            Some(absolute(order.price)?),
+        ),
+        OrderType::TakeProfitLimit => (
+            "TAKE_PROFIT_LIMIT",
+            Some(absolute(order.price2)?),
+            Some(absolute(order.price)?),
+        ),
+        order_type => {
+            return Err(InvalidOrder(format!(
+                "Binance does not support {:?} orders",
+                order_type
+            )))
+        }
+    };
+    params.push(("type", order_type.to_owned()));
+    params.push(("quantity", order.volume.to_string()));
+
+    if let Some(price) = price {
+        params.push(("price", price.to_string()));
+        // Limit makers rest until cancelled, the other limit orders need a time in force.
+        if order_type != "LIMIT_MAKER" {
+            let time_in_force = match order.time_in_force {
+                None | Some(TimeInForce::GoodTillCancelled) => "GTC",
+                Some(TimeInForce::ImmediateOrCancel) => "IOC",
+                Some(TimeInForce::GoodTillDate) => {
+                    return Err(InvalidOrder(
+                        "Binance does not support GTD orders".to_owned(),
+                    ))
+                }
+            };
+            params.push(("timeInForce", time_in_force.to_owned()));
+        }
+    }
+    if let Some(stop_price) = stop_price {
+        params.push(("stopPrice", stop_price.to_string()));
+    }
+    Ok(params)
+}
+
+/// `userref` carried by a client order id placed by `place_order`.
+fn parse_userref(client_order_id: &str) -> Option<i32> {
+    client_order_id
+        .strip_prefix(USERREF_PREFIX)?
+        .split('_')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Parse one kline: [open time (ms), open, high, low, close, volume, close time,
+/// quote volume, trades, ...]
+pub fn parse_kline(val: &serde_json::Value) -> Result<Candle, Box<dyn Error>> {
+    let invalid = || format!("invalid kline {}", val);
+    let number = |index: usize| -> Result<f32, Box<dyn Error>> {
+        Ok(val
+            .get(index)
+            .and_then(|n| n.as_str())
+            .ok_or_else(invalid)?
+            .parse::<f32>()?)
+    };
+    let integer = |index: usize| val.get(index).and_then(|n| n.as_i64()).ok_or_else(invalid);
+
+    let volume = number(5)?;
+    let close = number(4)?;
+    Ok(Candle {
+        time: Utc.timestamp_millis(integer(0)?),
+        open: number(1)?,
+        high: number(2)?,
+        low: number(3)?,
+        close,
+        vwap: if volume > 0.0 {
+            number(7)? / volume
+        } else {
+            close
+        },
+        volume,
+        count: integer(8)? as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_order_params() {
+        let order = OrderRequest {
+            flags: vec![OrderFlag::PostOnly],
+            userref: Some(-7),
+            ..OrderRequest::limit(OrderSide::Buy, "BTCUSDT", 0.5, 20000.5)
+        };
+        let params: HashMap<_, _> = new_order_params(&order).unwrap().into_iter().collect();
+        assert_eq!(params["side"], "BUY");
+        assert_eq!(params["type"], "LIMIT_MAKER");
+        assert_eq!(params["price"], "20000.5");
+        assert!(!params.contains_key("timeInForce"));
+
+        let stop = OrderRequest {
+            order_type: OrderType::StopLossLimit,
+            price2: Some(Price::Absolute(18900.0)),
+            ..OrderRequest::limit(OrderSide::Sell, "BTCUSDT", 0.5, 19000.0)
+        };
+        let params: HashMap<_, _> = new_order_params(&stop).unwrap().into_iter().collect();
+        assert_eq!(params["type"], "STOP_LOSS_LIMIT");
+        assert_eq!(params["stopPrice"], "19000");
+        assert_eq!(params["price"], "18900");
+        assert_eq!(params["timeInForce"], "GTC");
+
+        let relative = OrderRequest {
+            price: Some(Price::Percent(-2.0)),
+            ..OrderRequest::limit(OrderSide::Buy, "BTCUSDT", 0.5, 0.0)
+        };
+        assert!(new_order_params(&relative).is_err());
+        let margin = OrderRequest {
+            leverage: Some(2),
+            ..OrderRequest::market(OrderSide::Sell, "BTCUSDT", 0.5)
+        };
+        assert!(new_order_params(&margin).is_err());
+
+        assert_eq!(parse_userref("tb-7_1F3A9C2B"), Some(-7));
+        assert_eq!(parse_userref("web_5a1b"), None);
+    }
+
+    #[test]
+    fn test_trade_fill() {
+        let trade: MyTrade = serde_json::from_str(
+            r#"{"symbol":"BTCUSDT","id":28457,"orderId":100234,"orderListId":-1,"price":"4.00000100","qty":"12.00000000","quoteQty":"48.000012","commission":"10.10000000","commissionAsset":"BTC","time":1499865549590,"isBuyer":true,"isMaker":false,"isBestMatch":true}"#,
+        )
+        .unwrap();
+        let symbol = SymbolInfo {
+            symbol: "BTCUSDT".to_owned(),
+            status: "TRADING".to_owned(),
+            base_asset: "BTC".to_owned(),
+            quote_asset: "USDT".to_owned(),
+            filters: Vec::new(),
+        };
+
+        let fill = trade_fill(&trade, &symbol).unwrap();
+        assert_eq!(fill.order_id, "BTCUSDT:100234");
+        assert_eq!(fill.side, OrderSide::Buy);
+        assert_eq!(fill.volume, 12.0);
+        assert!((fill.fee - 40.4).abs() < 1e-3);
+        assert_eq!(fill.time.timestamp_millis(), 1499865549590);
+    }
+
+    #[test]
+    fn test_filter_decimals() {
+        let symbol: SymbolInfo = serde_json::from_str(
+            r#"{"symbol":"BTCUSDT","status":"TRADING","baseAsset":"BTC","quoteAsset":"USDT","filters":[{"filterType":"PRICE_FILTER","minPrice":"0.01000000","maxPrice":"1000000.00000000","tickSize":"0.01000000"},{"filterType":"LOT_SIZE","minQty":"0.00001000","maxQty":"9000.00000000","stepSize":"0.00001000"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(symbol.lot_decimals(), Some(5));
+        assert_eq!(symbol.price_decimals(), Some(2));
+        assert_eq!(step_decimals("1.00000000"), Some(0));
+        assert_eq!(step_decimals("0"), None);
+    }
+
+    #[test]
+    fn test_parse_kline() {
+        let kline = serde_json::json!([
+            1609459200000i64,
+            "29000.0",
+            "29400.0",
+            "28800.0",
+            "29300.0",
+            "10.0",
+            1609462799999i64,
+            "291000.0",
+            1200,
+            "5.0",
+            "145500.0",
+            "0"
+        ]);
+        let candle = parse_kline(&kline).unwrap();
+        assert_eq!(candle.time, Utc.timestamp(1609459200, 0));
+        assert_eq!(candle.close, 29300.0);
+        assert_eq!(candle.vwap, 29100.0);
+        assert_eq!(candle.count, 1200);
+    }
+}
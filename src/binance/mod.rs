@@ -0,0 +1,6 @@
+pub mod api;
+pub mod binance;
+pub mod binance_impl;
+pub mod types;
+
+pub use binance::Binance;
@@ -0,0 +1,126 @@
+use reqwest::StatusCode;
+use serde_derive::Deserialize;
+use std::{error::Error, fmt};
+
+/// Error body returned by Binance, e.g. `{"code":-1121,"msg":"Invalid symbol."}`.
+#[derive(Deserialize, Debug)]
+pub struct BinanceError {
+    #[serde(skip)]
+    pub status_code: Option<StatusCode>,
+    pub code: i64,
+    pub msg: String,
+}
+
+impl Error for BinanceError {}
+
+impl fmt::Display for BinanceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Status code: {}. {} ({})",
+            self.status_code.unwrap_or(StatusCode::BAD_REQUEST),
+            self.msg,
+            self.code
+        )
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolInfo {
+    pub symbol: String,
+    pub status: String,
+    pub base_asset: String,
+    pub quote_asset: String,
+    #[serde(default)]
+    pub filters: Vec<SymbolFilter>,
+}
+
+/// Trading rules of a symbol, those orders are not adjusted to are left out.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "filterType")]
+pub enum SymbolFilter {
+    /// Quantities are multiples of `step_size`, e.g. "0.00001000".
+    #[serde(rename = "LOT_SIZE", rename_all = "camelCase")]
+    LotSize { step_size: String },
+    /// Prices are multiples of `tick_size`, e.g. "0.01000000".
+    #[serde(rename = "PRICE_FILTER", rename_all = "camelCase")]
+    PriceFilter { tick_size: String },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ExchangeInfo {
+    pub symbols: Vec<SymbolInfo>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TickerPrice {
+    pub symbol: String,
+    pub price: String,
+}
+
+/// Order book levels as [price, quantity].
+#[derive(Deserialize, Debug)]
+pub struct Depth {
+    pub bids: Vec<(String, String)>,
+    pub asks: Vec<(String, String)>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AccountInfo {
+    pub balances: Vec<AssetBalance>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AssetBalance {
+    pub asset: String,
+    pub free: String,
+    pub locked: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NewOrderResult {
+    pub symbol: String,
+    pub order_id: u64,
+    pub client_order_id: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderInfo {
+    pub symbol: String,
+    pub order_id: u64,
+    pub client_order_id: String,
+    pub price: String,
+    pub orig_qty: String,
+    pub executed_qty: String,
+    pub side: String,
+    #[serde(rename = "type")]
+    pub order_type: String,
+    #[serde(default)]
+    pub stop_price: Option<String>,
+    /// e.g. "NEW", "PARTIALLY_FILLED", "FILLED" or "CANCELED".
+    pub status: String,
+    /// Milliseconds since the epoch of the last fill or status change.
+    #[serde(default)]
+    pub update_time: i64,
+}
+
+/// One of our trades, from `/api/v3/myTrades`.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MyTrade {
+    pub symbol: String,
+    pub id: u64,
+    pub order_id: u64,
+    pub price: String,
+    pub qty: String,
+    pub commission: String,
+    pub commission_asset: String,
+    /// Milliseconds since the epoch.
+    pub time: i64,
+    pub is_buyer: bool,
+}
@@ -0,0 +1,75 @@
+use super::Indicator;
+use crate::tradingbot::candle::Candle;
+
+/// Average true range with Wilder's smoothing.
+#[derive(Debug, Clone)]
+pub struct Atr {
+    period: usize,
+    previous_close: Option<f32>,
+    /// True ranges seen while seeding the average.
+    seen: usize,
+    value: f32,
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "period must be positive");
+        Atr {
+            period,
+            previous_close: None,
+            seen: 0,
+            value: 0.0,
+        }
+    }
+}
+
+impl Indicator for Atr {
+    type Output = f32;
+
+    fn update(&mut self, candle: &Candle) -> Option<f32> {
+        let range = candle.high - candle.low;
+        let true_range = match self.previous_close.replace(candle.close) {
+            Some(close) => range
+                .max((candle.high - close).abs())
+                .max((candle.low - close).abs()),
+            None => range,
+        };
+
+        let period = self.period as f32;
+        if self.seen < self.period {
+            self.value += true_range / period;
+            self.seen += 1;
+            if self.seen < self.period {
+                return None;
+            }
+        } else {
+            self.value = (self.value * (period - 1.0) + true_range) / period;
+        }
+        Some(self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::candle;
+
+    #[test]
+    fn test_atr() {
+        let candles = [
+            candle(10.0, 8.0, 9.0, 1.0),
+            candle(11.0, 9.0, 10.0, 1.0),
+            candle(12.0, 10.0, 11.5, 1.0),
+            // gap down, true range reaches the previous close
+            candle(12.0, 9.0, 9.5, 1.0),
+            candle(11.0, 8.0, 10.0, 1.0),
+        ];
+        let mut atr = Atr::new(3);
+        let values: Vec<Option<f32>> = candles.iter().map(|c| atr.update(c)).collect();
+
+        assert_eq!(values[1], None);
+        assert!((values[2].unwrap() - 2.0).abs() < 1e-5);
+        assert!((values[3].unwrap() - 7.0 / 3.0).abs() < 1e-5);
+        assert!((values[4].unwrap() - 23.0 / 9.0).abs() < 1e-5);
+    }
+}
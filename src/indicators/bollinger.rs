@@ -0,0 +1,78 @@
+use super::Indicator;
+use crate::tradingbot::candle::Candle;
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bands {
+    pub upper: f32,
+    /// SMA of the closes.
+    pub middle: f32,
+    pub lower: f32,
+}
+
+/// Bollinger bands, `multiplier` population standard deviations around the SMA of
+/// `period` closes. Usually `Bollinger::new(20, 2.0)`.
+#[derive(Debug, Clone)]
+pub struct Bollinger {
+    period: usize,
+    multiplier: f32,
+    window: VecDeque<f32>,
+}
+
+impl Bollinger {
+    pub fn new(period: usize, multiplier: f32) -> Self {
+        assert!(period > 0, "period must be positive");
+        Bollinger {
+            period,
+            multiplier,
+            window: VecDeque::with_capacity(period),
+        }
+    }
+
+    pub fn next(&mut self, value: f32) -> Option<Bands> {
+        self.window.push_back(value);
+        if self.window.len() > self.period {
+            self.window.pop_front();
+        }
+        if self.window.len() < self.period {
+            return None;
+        }
+
+        let period = self.period as f32;
+        let mean = self.window.iter().sum::<f32>() / period;
+        let variance = self.window.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / period;
+        let width = self.multiplier * variance.sqrt();
+        Some(Bands {
+            upper: mean + width,
+            middle: mean,
+            lower: mean - width,
+        })
+    }
+}
+
+impl Indicator for Bollinger {
+    type Output = Bands;
+
+    fn update(&mut self, candle: &Candle) -> Option<Bands> {
+        self.next(candle.close)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bollinger() {
+        let mut bollinger = Bollinger::new(5, 2.0);
+        for value in &[1.0, 2.0, 3.0, 4.0] {
+            assert_eq!(bollinger.next(*value), None);
+        }
+
+        // mean 3, standard deviation sqrt(2)
+        let bands = bollinger.next(5.0).unwrap();
+        assert_eq!(bands.middle, 3.0);
+        assert!((bands.upper - 5.828_427).abs() < 1e-5);
+        assert!((bands.lower - 0.171_573).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,79 @@
+use super::{moving_average::Ema, Indicator};
+use crate::tradingbot::candle::Candle;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MacdValue {
+    /// Fast EMA minus slow EMA.
+    pub macd: f32,
+    /// EMA of the MACD line.
+    pub signal: f32,
+    pub histogram: f32,
+}
+
+/// Moving average convergence divergence, usually `Macd::new(12, 26, 9)`.
+#[derive(Debug, Clone)]
+pub struct Macd {
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+}
+
+impl Macd {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        Macd {
+            fast: Ema::new(fast),
+            slow: Ema::new(slow),
+            signal: Ema::new(signal),
+        }
+    }
+
+    pub fn next(&mut self, value: f32) -> Option<MacdValue> {
+        // Both averages see every value, whichever is ready first.
+        let fast = self.fast.next(value);
+        let slow = self.slow.next(value);
+        let macd = fast? - slow?;
+        let signal = self.signal.next(macd)?;
+        Some(MacdValue {
+            macd,
+            signal,
+            histogram: macd - signal,
+        })
+    }
+}
+
+impl Indicator for Macd {
+    type Output = MacdValue;
+
+    fn update(&mut self, candle: &Candle) -> Option<MacdValue> {
+        self.next(candle.close)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_macd() {
+        let closes = [
+            10.0, 11.0, 12.0, 11.0, 13.0, 14.0, 13.0, 15.0, 16.0, 15.0, 17.0, 18.0,
+        ];
+        let mut macd = Macd::new(3, 6, 3);
+        let values: Vec<Option<MacdValue>> = closes.iter().map(|c| macd.next(*c)).collect();
+
+        assert_eq!(values[6], None);
+        let expected = [
+            (1.02381, 1.00794),
+            (1.15986, 1.08390),
+            (0.82847, 0.95619),
+            (1.02034, 0.98826),
+            (1.15738, 1.07282),
+        ];
+        for (value, (line, signal)) in values[7..].iter().zip(expected.iter()) {
+            let value = value.unwrap();
+            assert!((value.macd - line).abs() < 1e-4, "{:?}", value);
+            assert!((value.signal - signal).abs() < 1e-4, "{:?}", value);
+            assert!((value.histogram - (line - signal)).abs() < 1e-4);
+        }
+    }
+}
@@ -0,0 +1,47 @@
+pub mod atr;
+pub mod bollinger;
+pub mod macd;
+pub mod moving_average;
+pub mod obv;
+pub mod rsi;
+pub mod stochastic;
+pub mod vwap;
+
+pub use atr::Atr;
+pub use bollinger::{Bands, Bollinger};
+pub use macd::{Macd, MacdValue};
+pub use moving_average::{Ema, Sma, Wma};
+pub use obv::Obv;
+pub use rsi::Rsi;
+pub use stochastic::{Stochastic, StochasticValue};
+pub use vwap::Vwap;
+
+use crate::tradingbot::candle::Candle;
+
+/// Streaming indicator, fed one closed candle at a time.
+pub trait Indicator {
+    type Output;
+
+    /// Consume the next candle, returning the value once enough candles were seen.
+    fn update(&mut self, candle: &Candle) -> Option<Self::Output>;
+
+    /// Consume `candles` in order, returning the latest value.
+    fn update_all(&mut self, candles: &[Candle]) -> Option<Self::Output> {
+        candles.iter().fold(None, |_, candle| self.update(candle))
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn candle(high: f32, low: f32, close: f32, volume: f32) -> Candle {
+    use chrono::{TimeZone, Utc};
+    Candle {
+        time: Utc.timestamp(0, 0),
+        open: close,
+        high,
+        low,
+        close,
+        vwap: close,
+        volume,
+        count: 1,
+    }
+}
@@ -0,0 +1,178 @@
+use super::Indicator;
+use crate::tradingbot::candle::Candle;
+use std::collections::VecDeque;
+
+/// Simple moving average of the last `period` closes.
+#[derive(Debug, Clone)]
+pub struct Sma {
+    period: usize,
+    window: VecDeque<f32>,
+    /// Running sum of the window, in f64 so rounding does not build up over long streams.
+    sum: f64,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "period must be positive");
+        Sma {
+            period,
+            window: VecDeque::with_capacity(period),
+            sum: 0.0,
+        }
+    }
+
+    pub fn next(&mut self, value: f32) -> Option<f32> {
+        self.window.push_back(value);
+        self.sum += value as f64;
+        if self.window.len() > self.period {
+            self.sum -= self.window.pop_front().unwrap_or_default() as f64;
+        }
+
+        if self.window.len() == self.period {
+            Some((self.sum / self.period as f64) as f32)
+        } else {
+            None
+        }
+    }
+}
+
+impl Indicator for Sma {
+    type Output = f32;
+
+    fn update(&mut self, candle: &Candle) -> Option<f32> {
+        self.next(candle.close)
+    }
+}
+
+/// Exponential moving average with `2 / (period + 1)` smoothing, seeded with the SMA of the
+/// first `period` closes.
+#[derive(Debug, Clone)]
+pub struct Ema {
+    alpha: f32,
+    seed: Sma,
+    value: Option<f32>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        Ema {
+            alpha: 2.0 / (period as f32 + 1.0),
+            seed: Sma::new(period),
+            value: None,
+        }
+    }
+
+    pub fn next(&mut self, value: f32) -> Option<f32> {
+        self.value = match self.value {
+            Some(ema) => Some(ema + self.alpha * (value - ema)),
+            None => self.seed.next(value),
+        };
+        self.value
+    }
+}
+
+impl Indicator for Ema {
+    type Output = f32;
+
+    fn update(&mut self, candle: &Candle) -> Option<f32> {
+        self.next(candle.close)
+    }
+}
+
+/// Linearly weighted moving average, the latest close weighs `period`, the oldest 1.
+#[derive(Debug, Clone)]
+pub struct Wma {
+    period: usize,
+    window: VecDeque<f32>,
+}
+
+impl Wma {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "period must be positive");
+        Wma {
+            period,
+            window: VecDeque::with_capacity(period),
+        }
+    }
+
+    pub fn next(&mut self, value: f32) -> Option<f32> {
+        self.window.push_back(value);
+        if self.window.len() > self.period {
+            self.window.pop_front();
+        }
+        if self.window.len() < self.period {
+            return None;
+        }
+
+        let weighted: f32 = self
+            .window
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (i + 1) as f32 * value)
+            .sum();
+        let weights = (self.period * (self.period + 1) / 2) as f32;
+        Some(weighted / weights)
+    }
+}
+
+impl Indicator for Wma {
+    type Output = f32;
+
+    fn update(&mut self, candle: &Candle) -> Option<f32> {
+        self.next(candle.close)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sma() {
+        let mut sma = Sma::new(3);
+        let values: Vec<Option<f32>> = [1.0, 2.0, 3.0, 4.0, 5.0]
+            .iter()
+            .map(|v| sma.next(*v))
+            .collect();
+        assert_eq!(values, vec![None, None, Some(2.0), Some(3.0), Some(4.0)]);
+
+        // A long stream at BTC prices does not drift from the average of its window.
+        let mut sma = Sma::new(200);
+        let price = |i: usize| 30_000.0 + ((i * 7919) % 10_007) as f32 * 0.37;
+        let mut last = None;
+        for i in 0..1_000_000 {
+            last = sma.next(price(i));
+        }
+        let expected = (1_000_000 - 200..1_000_000)
+            .map(|i| price(i) as f64)
+            .sum::<f64>()
+            / 200.0;
+        assert!((last.unwrap() as f64 - expected).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_ema() {
+        // 10-day EMA example from StockCharts' ChartSchool
+        let closes = [
+            22.27, 22.19, 22.08, 22.17, 22.18, 22.13, 22.23, 22.43, 22.24, 22.29, 22.15, 22.39,
+        ];
+        let mut ema = Ema::new(10);
+        let values: Vec<Option<f32>> = closes.iter().map(|c| ema.next(*c)).collect();
+
+        assert_eq!(values[8], None);
+        assert!((values[9].unwrap() - 22.22).abs() < 0.01);
+        assert!((values[10].unwrap() - 22.21).abs() < 0.01);
+        assert!((values[11].unwrap() - 22.24).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_wma() {
+        let mut wma = Wma::new(3);
+        assert_eq!(wma.next(1.0), None);
+        assert_eq!(wma.next(2.0), None);
+        // (1 * 1 + 2 * 2 + 3 * 3) / 6
+        assert!((wma.next(3.0).unwrap() - 14.0 / 6.0).abs() < 1e-6);
+        // (2 * 1 + 3 * 2 + 6 * 3) / 6
+        assert!((wma.next(6.0).unwrap() - 26.0 / 6.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,52 @@
+use super::Indicator;
+use crate::tradingbot::candle::Candle;
+
+/// On-balance volume: volume added on up closes and subtracted on down closes,
+/// starting from 0.
+#[derive(Debug, Clone, Default)]
+pub struct Obv {
+    previous_close: Option<f32>,
+    value: f32,
+}
+
+impl Obv {
+    pub fn new() -> Self {
+        Obv::default()
+    }
+}
+
+impl Indicator for Obv {
+    type Output = f32;
+
+    fn update(&mut self, candle: &Candle) -> Option<f32> {
+        if let Some(close) = self.previous_close.replace(candle.close) {
+            if candle.close > close {
+                self.value += candle.volume;
+            } else if candle.close < close {
+                self.value -= candle.volume;
+            }
+        }
+        Some(self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::candle;
+
+    #[test]
+    fn test_obv() {
+        let candles = [
+            candle(10.0, 10.0, 10.0, 100.0),
+            candle(11.0, 11.0, 11.0, 200.0),
+            candle(10.5, 10.5, 10.5, 150.0),
+            candle(10.5, 10.5, 10.5, 50.0),
+            candle(12.0, 12.0, 12.0, 300.0),
+        ];
+        let mut obv = Obv::new();
+        let values: Vec<f32> = candles.iter().filter_map(|c| obv.update(c)).collect();
+        assert_eq!(values, vec![0.0, 200.0, 50.0, 50.0, 350.0]);
+        assert_eq!(Obv::new().update_all(&candles), Some(350.0));
+    }
+}
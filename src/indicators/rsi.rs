@@ -0,0 +1,82 @@
+use super::Indicator;
+use crate::tradingbot::candle::Candle;
+
+/// Relative strength index with Wilder's smoothing, between 0 and 100.
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    period: usize,
+    previous: Option<f32>,
+    /// Changes seen while seeding the averages.
+    seen: usize,
+    avg_gain: f32,
+    avg_loss: f32,
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Self {
+        assert!(period > 0, "period must be positive");
+        Rsi {
+            period,
+            previous: None,
+            seen: 0,
+            avg_gain: 0.0,
+            avg_loss: 0.0,
+        }
+    }
+
+    pub fn next(&mut self, value: f32) -> Option<f32> {
+        let previous = self.previous.replace(value)?;
+        let change = value - previous;
+        let gain = change.max(0.0);
+        let loss = (-change).max(0.0);
+
+        let period = self.period as f32;
+        if self.seen < self.period {
+            // First averages are plain means of `period` changes.
+            self.avg_gain += gain / period;
+            self.avg_loss += loss / period;
+            self.seen += 1;
+            if self.seen < self.period {
+                return None;
+            }
+        } else {
+            self.avg_gain = (self.avg_gain * (period - 1.0) + gain) / period;
+            self.avg_loss = (self.avg_loss * (period - 1.0) + loss) / period;
+        }
+
+        if self.avg_loss == 0.0 {
+            return Some(100.0);
+        }
+        Some(100.0 - 100.0 / (1.0 + self.avg_gain / self.avg_loss))
+    }
+}
+
+impl Indicator for Rsi {
+    type Output = f32;
+
+    fn update(&mut self, candle: &Candle) -> Option<f32> {
+        self.next(candle.close)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rsi() {
+        // 14-day RSI example from StockCharts' ChartSchool, without its rounding of the averages
+        let closes = [
+            44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08, 45.89, 46.03,
+            45.61, 46.28, 46.28, 46.00, 46.03, 46.41, 46.22, 45.64,
+        ];
+        let mut rsi = Rsi::new(14);
+        let values: Vec<Option<f32>> = closes.iter().map(|c| rsi.next(*c)).collect();
+
+        assert_eq!(values[13], None);
+        let expected = [70.46, 66.25, 66.48, 69.35, 66.29, 57.92];
+        for (value, expected) in values[14..].iter().zip(expected.iter()) {
+            assert!((value.unwrap() - expected).abs() < 0.01, "{:?}", value);
+        }
+    }
+}
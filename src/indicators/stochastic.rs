@@ -0,0 +1,82 @@
+use super::{moving_average::Sma, Indicator};
+use crate::tradingbot::candle::Candle;
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StochasticValue {
+    /// Close within the high-low range of the last `k_period` candles, between 0 and 100.
+    pub k: f32,
+    /// SMA of %K over `d_period` candles.
+    pub d: f32,
+}
+
+/// Stochastic oscillator, usually `Stochastic::new(14, 3)`.
+#[derive(Debug, Clone)]
+pub struct Stochastic {
+    k_period: usize,
+    window: VecDeque<(f32, f32)>,
+    d: Sma,
+}
+
+impl Stochastic {
+    pub fn new(k_period: usize, d_period: usize) -> Self {
+        assert!(k_period > 0, "period must be positive");
+        Stochastic {
+            k_period,
+            window: VecDeque::with_capacity(k_period),
+            d: Sma::new(d_period),
+        }
+    }
+}
+
+impl Indicator for Stochastic {
+    type Output = StochasticValue;
+
+    fn update(&mut self, candle: &Candle) -> Option<StochasticValue> {
+        self.window.push_back((candle.high, candle.low));
+        if self.window.len() > self.k_period {
+            self.window.pop_front();
+        }
+        if self.window.len() < self.k_period {
+            return None;
+        }
+
+        let highest = self.window.iter().map(|(h, _)| *h).fold(f32::MIN, f32::max);
+        let lowest = self.window.iter().map(|(_, l)| *l).fold(f32::MAX, f32::min);
+        let k = if highest > lowest {
+            (candle.close - lowest) / (highest - lowest) * 100.0
+        } else {
+            50.0
+        };
+        let d = self.d.next(k)?;
+        Some(StochasticValue { k, d })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::candle;
+
+    #[test]
+    fn test_stochastic() {
+        let candles = [
+            candle(10.0, 8.0, 9.0, 1.0),
+            candle(11.0, 9.0, 10.0, 1.0),
+            candle(12.0, 10.0, 11.5, 1.0),
+            candle(12.0, 9.0, 9.5, 1.0),
+            candle(11.0, 8.0, 10.0, 1.0),
+        ];
+        let mut stochastic = Stochastic::new(3, 2);
+        let values: Vec<Option<StochasticValue>> =
+            candles.iter().map(|c| stochastic.update(c)).collect();
+
+        assert_eq!(values[2], None);
+        let value = values[3].unwrap();
+        assert!((value.k - 100.0 / 6.0).abs() < 1e-4);
+        assert!((value.d - (87.5 + 100.0 / 6.0) / 2.0).abs() < 1e-4);
+        let value = values[4].unwrap();
+        assert!((value.k - 50.0).abs() < 1e-4);
+        assert!((value.d - (100.0 / 6.0 + 50.0) / 2.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,54 @@
+use super::Indicator;
+use crate::tradingbot::candle::Candle;
+
+/// Volume weighted average of the typical price `(high + low + close) / 3` since the
+/// last `reset`, e.g. at the start of each session.
+#[derive(Debug, Clone, Default)]
+pub struct Vwap {
+    value: f32,
+    volume: f32,
+}
+
+impl Vwap {
+    pub fn new() -> Self {
+        Vwap::default()
+    }
+
+    pub fn reset(&mut self) {
+        *self = Vwap::default();
+    }
+}
+
+impl Indicator for Vwap {
+    type Output = f32;
+
+    fn update(&mut self, candle: &Candle) -> Option<f32> {
+        let typical = (candle.high + candle.low + candle.close) / 3.0;
+        self.value += typical * candle.volume;
+        self.volume += candle.volume;
+
+        if self.volume > 0.0 {
+            Some(self.value / self.volume)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::candle;
+
+    #[test]
+    fn test_vwap() {
+        let mut vwap = Vwap::new();
+        assert_eq!(vwap.update(&candle(10.0, 10.0, 10.0, 0.0)), None);
+        assert_eq!(vwap.update(&candle(12.0, 9.0, 9.0, 1.0)), Some(10.0));
+        // (10 * 1 + 13 * 3) / 4
+        assert_eq!(vwap.update(&candle(14.0, 12.0, 13.0, 3.0)), Some(12.25));
+
+        vwap.reset();
+        assert_eq!(vwap.update(&candle(14.0, 12.0, 13.0, 3.0)), Some(13.0));
+    }
+}
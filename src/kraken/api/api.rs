@@ -1,6 +1,18 @@
+use super::rate_limiter::RateLimiter;
+use std::sync::atomic::AtomicU64;
+use tokio::sync::Mutex;
+
 /// Krakne API Struct
 pub struct KrakenAPI {
     pub api_key: String,
     pub secret: String,
     pub client: reqwest::blocking::Client,
+    /// Private endpoints, counted per account.
+    pub private_limit: RateLimiter,
+    /// Public endpoints, counted per IP address.
+    pub public_limit: RateLimiter,
+    /// Last nonce used, nonces of an API key must increase.
+    pub nonce: AtomicU64,
+    /// Held from signing a private call to its reply, so nonces reach Kraken in order.
+    pub private_lock: Mutex<()>,
 }
@@ -3,19 +3,27 @@ use crate::kraken::api::utils::create_signature;
 use chrono::Utc;
 use data_encoding::BASE64;
 use log::trace;
-use reqwest::blocking::Response;
 use reqwest::header::USER_AGENT;
 use serde::de::DeserializeOwned;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::{collections::HashMap, error::Error};
+use tokio::sync::Mutex;
 
 use reqwest::header::HeaderMap;
 
-use super::{api::KrakenAPI, error::KrakenError, methods::Method, types::KrakenResponse};
+use super::{
+    api::KrakenAPI, error::KrakenError, methods::Method, rate_limiter::RateLimiter,
+    types::KrakenResponse,
+};
 
 const API_URL: &str = "https://api.kraken.com";
 const API_VERSION: &str = "0";
 const API_USER_AGENT: &str = "Kraken Rust API Agent";
 pub const BTCUSD: &str = "XXBTZUSD";
+/// Call counter of a starter account: up to 15, decaying by 0.33 per second.
+const PRIVATE_LIMIT: (f32, f32) = (15.0, 0.33);
+/// Kraken allows about one public call per second.
+const PUBLIC_LIMIT: (f32, f32) = (3.0, 1.0);
 
 /// Kraken API
 impl KrakenAPI {
@@ -24,9 +32,26 @@ impl KrakenAPI {
             api_key,
             secret,
             client: reqwest::blocking::Client::new(),
+            private_limit: RateLimiter::new(PRIVATE_LIMIT.0, PRIVATE_LIMIT.1),
+            public_limit: RateLimiter::new(PUBLIC_LIMIT.0, PUBLIC_LIMIT.1),
+            nonce: AtomicU64::new(0),
+            private_lock: Mutex::new(()),
         }
     }
 
+    /// Current time in microseconds, or one more than the last nonce when calls share a
+    /// microsecond.
+    fn next_nonce(&self) -> u64 {
+        let now = Utc::now().timestamp_nanos() as u64 / 1000;
+        let last = self
+            .nonce
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+                Some(now.max(last + 1))
+            })
+            .unwrap_or_default();
+        now.max(last + 1)
+    }
+
     /// query private endpoints
     pub async fn query_private<T>(
         &self,
@@ -34,15 +59,17 @@ impl KrakenAPI {
         params: &mut HashMap<String, String>,
     ) -> Result<KrakenResponse<T>, Box<dyn Error>>
     where
-        T: DeserializeOwned + 'static,
+        T: DeserializeOwned + Send + 'static,
     {
+        self.private_limit.acquire(method.cost()).await;
+        let _lock = self.private_lock.lock().await;
         let method: &str = method.into();
         let url_path = format!("/{}/private/{}", API_VERSION, method);
         let url = format!("{}{}", API_URL, url_path);
         let secret_bytes = BASE64
             .decode(&self.secret.as_bytes())
             .expect("Not able to decode Kraken api secret");
-        let nonce = format!("{}", Utc::now().timestamp_millis() * 1000);
+        let nonce = self.next_nonce().to_string();
         params.insert("nonce".to_owned(), nonce.to_owned());
 
         let sig = create_signature(&url_path, params, &secret_bytes)?;
@@ -63,8 +90,7 @@ impl KrakenAPI {
         trace!("Query with nonce: {}", nonce);
         trace!("Query request params: {:?}", params);
 
-        let res: KrakenResponse<T> = self.do_request(&url, params, &header_map).await?.json()?;
-        Ok(res)
+        self.do_request(&url, params, &header_map).await
     }
 
     /// query public endpoints
@@ -74,28 +100,29 @@ impl KrakenAPI {
         params: &HashMap<String, String>,
     ) -> Result<KrakenResponse<T>, Box<dyn Error>>
     where
-        T: DeserializeOwned + 'static,
+        T: DeserializeOwned + Send + 'static,
     {
+        self.public_limit.acquire(1.0).await;
         let method: &str = method.into();
         let url = format!("{}/{}/public/{}", API_URL, API_VERSION, method);
 
         trace!("Query request url: {}", url);
         trace!("Query request method: {}", method);
 
-        let res: KrakenResponse<T> = self
-            .do_request(&url, params, &HeaderMap::new())
-            .await?
-            .json()?;
-        Ok(res)
+        self.do_request(&url, params, &HeaderMap::new()).await
     }
 
-    /// Send Http reqeust.
-    async fn do_request(
+    /// Send Http reqeust and parse the response. The blocking client runs on the blocking
+    /// thread pool so the runtime keeps driving other bots meanwhile.
+    async fn do_request<T>(
         &self,
         url: &str,
         params: &HashMap<String, String>,
         headers: &HeaderMap,
-    ) -> Result<Response, Box<dyn Error>> {
+    ) -> Result<KrakenResponse<T>, Box<dyn Error>>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
         let mut header_map = HeaderMap::new();
         header_map.insert(USER_AGENT, API_USER_AGENT.parse().unwrap());
 
@@ -104,21 +131,36 @@ impl KrakenAPI {
             header_map.insert(n, v.to_owned());
         }
 
-        let res = self
-            .client
-            .post(url)
-            .form(params)
-            .headers(header_map)
-            .send()?;
+        let request = self.client.post(url).form(params).headers(header_map);
+        let res = tokio::task::spawn_blocking(
+            move || -> Result<KrakenResponse<T>, Box<dyn Error + Send + Sync>> {
+                let res = request.send()?;
 
-        let ok = res.status().is_success();
+                let ok = res.status().is_success();
 
-        if !ok {
-            let status_code = res.status();
-            let res: KrakenResponse<serde_json::Value> = res.json()?;
-            return Err(Box::new(KrakenError::new(Some(status_code), res.error)));
-        }
+                if !ok {
+                    let status_code = res.status();
+                    let res: KrakenResponse<serde_json::Value> = res.json()?;
+                    return Err(Box::new(KrakenError::new(Some(status_code), res.error)));
+                }
+
+                Ok(res.json()?)
+            },
+        )
+        .await?;
+
+        res.map_err(|e| -> Box<dyn Error> { e })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        Ok(res)
+    #[test]
+    fn test_nonces_increase() {
+        let api = KrakenAPI::new(String::new(), String::new());
+        let nonces: Vec<u64> = (0..1000).map(|_| api.next_nonce()).collect();
+        assert!(nonces.windows(2).all(|pair| pair[1] > pair[0]));
     }
 }
@@ -2,9 +2,17 @@ pub enum Method {
     Balance,
     Time,
     Assets,
+    AssetPairs,
     TradesHistory,
     QueryOrders,
+    OpenOrders,
     OHLC,
+    Depth,
+    Trades,
+    Ticker,
+    GetWebSocketsToken,
+    AddOrder,
+    CancelOrder,
 }
 
 impl From<Method> for &str {
@@ -13,9 +21,29 @@ impl From<Method> for &str {
             Method::Balance => "Balance",
             Method::Time => "Time",
             Method::Assets => "Assets",
+            Method::AssetPairs => "AssetPairs",
             Method::TradesHistory => "TradesHistory",
             Method::QueryOrders => "QueryOrders",
+            Method::OpenOrders => "OpenOrders",
             Method::OHLC => "OHLC",
+            Method::Depth => "Depth",
+            Method::Trades => "Trades",
+            Method::Ticker => "Ticker",
+            Method::GetWebSocketsToken => "GetWebSocketsToken",
+            Method::AddOrder => "AddOrder",
+            Method::CancelOrder => "CancelOrder",
+        }
+    }
+}
+
+impl Method {
+    /// Calls counted by the private rate limiter. Orders have their own limits on the matching
+    /// engine.
+    pub fn cost(&self) -> f32 {
+        match self {
+            Method::TradesHistory => 2.0,
+            Method::AddOrder | Method::CancelOrder => 0.0,
+            _ => 1.0,
         }
     }
 }
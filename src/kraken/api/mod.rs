@@ -2,7 +2,8 @@ pub mod api;
 pub mod api_impl;
 pub mod error;
 pub mod methods;
+pub mod rate_limiter;
 pub mod types;
-mod utils;
+pub(crate) mod utils;
 
 use api::KrakenAPI;
@@ -0,0 +1,57 @@
+use std::sync::Mutex;
+use tokio::time::{self, Duration, Instant};
+
+/// Kraken style call counter: every call adds its cost, the counter decays over time and calls
+/// wait while it would exceed the maximum.
+pub struct RateLimiter {
+    max: f32,
+    decay_per_sec: f32,
+    counter: Mutex<(f32, Instant)>,
+}
+
+impl RateLimiter {
+    pub fn new(max: f32, decay_per_sec: f32) -> Self {
+        RateLimiter {
+            max,
+            decay_per_sec,
+            counter: Mutex::new((0.0, Instant::now())),
+        }
+    }
+
+    /// Wait until a call of `cost` fits under the maximum, then count it.
+    pub async fn acquire(&self, cost: f32) {
+        loop {
+            let wait = {
+                let mut counter = self.counter.lock().unwrap();
+                let now = Instant::now();
+                let decayed = now.duration_since(counter.1).as_secs_f32() * self.decay_per_sec;
+                let value = (counter.0 - decayed).max(0.0);
+                if value + cost <= self.max {
+                    *counter = (value + cost, now);
+                    return;
+                }
+                *counter = (value, now);
+                (value + cost - self.max) / self.decay_per_sec
+            };
+            time::sleep(Duration::from_secs_f32(wait)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_acquire_waits_for_decay() {
+        let limiter = RateLimiter::new(2.0, 100.0);
+        let start = Instant::now();
+        limiter.acquire(1.0).await;
+        limiter.acquire(1.0).await;
+        assert!(start.elapsed() < Duration::from_millis(5));
+
+        // The counter is full, the next call waits for it to decay by one.
+        limiter.acquire(1.0).await;
+        assert!(start.elapsed() >= Duration::from_millis(9));
+    }
+}
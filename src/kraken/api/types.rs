@@ -1,4 +1,6 @@
+use super::error::KrakenError;
 use serde_derive::Deserialize;
+use std::collections::HashMap;
 
 #[derive(Deserialize, Debug)]
 pub struct KrakenResponse<T> {
@@ -6,6 +8,16 @@ pub struct KrakenResponse<T> {
     pub result: Option<T>,
 }
 
+impl<T> KrakenResponse<T> {
+    /// The result, or the errors Kraken returned instead.
+    pub fn into_result(self) -> Result<T, KrakenError> {
+        match self.result {
+            Some(result) if self.error.is_empty() => Ok(result),
+            _ => Err(KrakenError::new(None, self.error)),
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 pub struct ServerTime {
     pub unixtime: u32,
@@ -18,3 +30,80 @@ pub struct AssetInfo {
     pub decimals: u32,
     pub display_decimals: u32,
 }
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AssetPair {
+    pub altname: String,
+    pub wsname: Option<String>,
+    pub base: String,
+    pub quote: String,
+    pub pair_decimals: u32,
+    pub lot_decimals: u32,
+    pub ordermin: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct WebSocketsToken {
+    pub token: String,
+    pub expires: u32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OrderDescription {
+    pub order: String,
+    pub close: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AddOrderResult {
+    pub descr: OrderDescription,
+    /// Absent when the order was only validated.
+    pub txid: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CancelOrderResult {
+    pub count: u32,
+}
+
+/// Book side entries of the `Depth` endpoint: `[price, volume, timestamp]`.
+#[derive(Deserialize, Debug)]
+pub struct DepthEntry {
+    pub asks: Vec<(String, String, u64)>,
+    pub bids: Vec<(String, String, u64)>,
+}
+
+/// Best levels of the `Ticker` endpoint: `[price, whole lot volume, lot volume]`.
+#[derive(Deserialize, Debug)]
+pub struct TickerInfo {
+    pub a: (String, String, String),
+    pub b: (String, String, String),
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OpenOrdersResult {
+    pub open: HashMap<String, OrderInfo>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OrderInfo {
+    pub userref: Option<i32>,
+    pub status: String,
+    pub descr: OrderInfoDescription,
+    pub vol: String,
+    pub vol_exec: String,
+    /// Quote value and fee of the executed volume.
+    #[serde(default)]
+    pub cost: String,
+    #[serde(default)]
+    pub fee: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OrderInfoDescription {
+    pub pair: String,
+    #[serde(rename = "type")]
+    pub side: String,
+    pub ordertype: String,
+    pub price: String,
+}
@@ -50,6 +50,11 @@ pub fn get_hmac_sha512(message: &[u8], secret: &[u8]) -> Tag {
     hmac::sign(&key, message)
 }
 
+pub fn get_hmac_sha256(message: &[u8], secret: &[u8]) -> Tag {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret);
+    hmac::sign(&key, message)
+}
+
 pub fn urlencode_hashmap(params: &HashMap<String, String>) -> String {
     params
         .iter()
@@ -103,6 +108,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_hmac256() {
+        // Example of the Binance API documentation.
+        let msg = "symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1&recvWindow=5000&timestamp=1499827319559";
+        let secret = "NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j";
+        assert_eq!(
+            HEXUPPER.encode(get_hmac_sha256(msg.as_bytes(), secret.as_bytes()).as_ref()),
+            "C8DB56825AE71D6D79447849E617115F4A920FA2ACDCAB2B053C4B2838BD6B71"
+        );
+    }
+
     #[test]
     fn test_hmac521_base64_key() {
         let msg = "hello";
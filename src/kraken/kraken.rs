@@ -1,8 +1,21 @@
-use super::api::api::KrakenAPI;
+use super::api::{api::KrakenAPI, types::AssetPair};
+use super::ws::{KrakenBookFeed, KrakenPrivateWs, OrderTracker};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 /// Kraken Struct
 pub struct Kraken {
     pub api_key: String,
     pub secret: String,
-    pub api_client: KrakenAPI,
+    /// Shared by the bots trading on the same account.
+    pub api_client: Arc<KrakenAPI>,
+    pub private_ws: Mutex<Option<Arc<KrakenPrivateWs>>>,
+    /// Our orders and trades seen on the private socket, kept across reconnects.
+    pub order_tracker: Arc<Mutex<OrderTracker>>,
+    /// Local books by pair, served instead of the REST order book once synced.
+    pub book_feeds: Mutex<HashMap<String, Arc<KrakenBookFeed>>>,
+    /// Pairs looked up by `get_asset_pair`, they do not change while running.
+    pub asset_pairs: Mutex<HashMap<String, AssetPair>>,
+    /// Submit orders with `validate=true` only.
+    pub dry_run: bool,
 }
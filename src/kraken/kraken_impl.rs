@@ -3,12 +3,22 @@ use super::{
         api::KrakenAPI,
         error::KrakenError,
         methods::Method,
-        types::{AssetInfo, KrakenResponse, ServerTime},
+        types::{
+            AddOrderResult, AssetInfo, AssetPair, CancelOrderResult, DepthEntry, OpenOrdersResult,
+            OrderInfo, ServerTime, TickerInfo, WebSocketsToken,
+        },
     },
     kraken::Kraken,
+    order_params::{add_order_params, ws_add_order_params},
+    ws::{KrakenBookFeed, KrakenPrivateWs, OrderTracker},
+};
+use crate::marketdata::Trade;
+use crate::tradingbot::{
+    candle::Candle,
+    market::Market,
+    order::{Fill, OpenOrder, OrderRequest, OrderSide, DRY_RUN_ORDER_PREFIX},
+    order_book::OrderBook,
 };
-use crate::kraken::api::api_impl::BTCUSD;
-use crate::tradingbot::market::Market;
 use async_trait::async_trait;
 use chrono::DateTime;
 use chrono::{TimeZone, Utc};
@@ -16,14 +26,293 @@ use log::info;
 use rand::Rng;
 use std::collections::HashMap;
 use std::error::Error;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+/// Depths the public `book` channel accepts.
+const BOOK_DEPTHS: [u32; 5] = [10, 25, 100, 500, 1000];
 
 impl Kraken {
     pub fn new(api_key: &str, secret: &str) -> Self {
+        Kraken::with_client(Arc::new(KrakenAPI::new(
+            api_key.to_string(),
+            secret.to_string(),
+        )))
+    }
+
+    /// Kraken market sharing the client, and so the rate limits, of another one.
+    pub fn with_client(api_client: Arc<KrakenAPI>) -> Self {
         Kraken {
-            api_key: api_key.to_owned(),
-            secret: secret.to_owned(),
-            api_client: KrakenAPI::new(api_key.to_string(), secret.to_string()),
+            api_key: api_client.api_key.clone(),
+            secret: api_client.secret.clone(),
+            api_client,
+            private_ws: Mutex::new(None),
+            order_tracker: Arc::new(Mutex::new(OrderTracker::default())),
+            book_feeds: Mutex::new(HashMap::new()),
+            asset_pairs: Mutex::new(HashMap::new()),
+            dry_run: false,
+        }
+    }
+
+    /// Get OHLC candles of `pair`, oldest first. The last one is still in progress.
+    pub async fn get_ohlc(&self, pair: &str, interval: u32) -> Result<Vec<Candle>, Box<dyn Error>> {
+        Ok(self.get_ohlc_since(pair, interval, None).await?.0)
+    }
+
+    /// Get OHLC candles of `pair` after `since` (unix seconds), oldest first, with the `last`
+    /// cursor to pass as `since` for the next page. Kraken only serves the 720 most recent candles.
+    pub async fn get_ohlc_since(
+        &self,
+        pair: &str,
+        interval: u32,
+        since: Option<i64>,
+    ) -> Result<(Vec<Candle>, i64), Box<dyn Error>> {
+        // https://api.kraken.com/0/public/OHLC?pair=TBTCUSD&interval=60&since=1607023200
+        let mut params = HashMap::new();
+        params.insert("pair".into(), pair.into());
+        params.insert("interval".into(), interval.to_string());
+        if let Some(since) = since {
+            params.insert("since".into(), since.to_string());
+        }
+        let res = self
+            .api_client
+            .query_public::<HashMap<String, serde_json::Value>>(Method::OHLC, &params)
+            .await?;
+
+        let result = res.into_result()?;
+
+        let data = result
+            .get(pair)
+            .and_then(|data| data.as_array())
+            .ok_or_else(|| format!("no OHLC data returned for {}", pair))?;
+        let last = result
+            .get("last")
+            .and_then(|last| last.as_i64())
+            .unwrap_or_default();
+
+        let candles = data.iter().map(parse_ohlc).collect::<Result<_, _>>()?;
+        Ok((candles, last))
+    }
+
+    /// Get up to 1000 public trades of `pair` after `since` (nanosecond cursor), oldest first,
+    /// with the `last` cursor of the next page.
+    pub async fn get_trades(
+        &self,
+        pair: &str,
+        since: Option<&str>,
+    ) -> Result<(Vec<Trade>, String), Box<dyn Error>> {
+        let mut params = HashMap::new();
+        params.insert("pair".into(), pair.into());
+        if let Some(since) = since {
+            params.insert("since".into(), since.into());
+        }
+        let res = self
+            .api_client
+            .query_public::<HashMap<String, serde_json::Value>>(Method::Trades, &params)
+            .await?;
+
+        let result = res.into_result()?;
+
+        let data = result
+            .get(pair)
+            .and_then(|data| data.as_array())
+            .ok_or_else(|| format!("no trades returned for {}", pair))?;
+        let last = match result.get("last") {
+            Some(serde_json::Value::String(last)) => last.clone(),
+            Some(last) => last.to_string(),
+            None => return Err("no last cursor in Trades response".into()),
+        };
+
+        Ok((
+            data.iter().map(parse_trade).collect::<Result<_, _>>()?,
+            last,
+        ))
+    }
+
+    /// Details of `pair`, by name or altname, e.g. its base and quote assets. Looked up once,
+    /// then cached.
+    pub async fn get_asset_pair(&self, pair: &str) -> Result<AssetPair, Box<dyn Error>> {
+        if let Some(asset_pair) = self.asset_pairs.lock().unwrap().get(pair) {
+            return Ok(asset_pair.clone());
+        }
+
+        let mut params = HashMap::new();
+        params.insert("pair".to_owned(), pair.to_owned());
+        let asset_pair = self
+            .api_client
+            .query_public::<HashMap<String, AssetPair>>(Method::AssetPairs, &params)
+            .await?
+            .into_result()?
+            .into_values()
+            .next()
+            .ok_or_else(|| format!("unknown pair {}", pair))?;
+        self.asset_pairs
+            .lock()
+            .unwrap()
+            .insert(pair.to_owned(), asset_pair.clone());
+        Ok(asset_pair)
+    }
+
+    /// Every tradeable pair by name. Dark pool pairs (".d") are left out.
+    pub async fn get_asset_pairs(&self) -> Result<HashMap<String, AssetPair>, Box<dyn Error>> {
+        let mut pairs = self
+            .api_client
+            .query_public::<HashMap<String, AssetPair>>(Method::AssetPairs, &HashMap::new())
+            .await?
+            .into_result()?;
+        pairs.retain(|name, _| !name.ends_with(".d"));
+
+        self.asset_pairs.lock().unwrap().extend(
+            pairs
+                .iter()
+                .map(|(name, pair)| (name.clone(), pair.clone())),
+        );
+        Ok(pairs)
+    }
+
+    /// Name of the pair order descriptions call by its `altname`, e.g. "XXBTZUSD" for "XBTUSD".
+    pub async fn pair_by_altname(&self, altname: &str) -> Result<String, Box<dyn Error>> {
+        let find = |pairs: &HashMap<String, AssetPair>| {
+            pairs
+                .iter()
+                .find(|(_, pair)| pair.altname == altname)
+                .map(|(name, _)| name.clone())
+        };
+        let cached = find(&self.asset_pairs.lock().unwrap());
+        match cached {
+            Some(name) => Ok(name),
+            None => find(&self.get_asset_pairs().await?)
+                .ok_or_else(|| format!("unknown pair {}", altname).into()),
+        }
+    }
+
+    /// Best bid and ask of each of `pairs` in a single request, as one level books.
+    pub async fn get_tickers(
+        &self,
+        pairs: &[String],
+    ) -> Result<HashMap<String, OrderBook>, Box<dyn Error>> {
+        let mut params = HashMap::new();
+        params.insert("pair".to_owned(), pairs.join(","));
+        let tickers = self
+            .api_client
+            .query_public::<HashMap<String, TickerInfo>>(Method::Ticker, &params)
+            .await?
+            .into_result()?;
+
+        let level =
+            |(price, _, volume): (String, String, String)| -> Result<(f32, f32), Box<dyn Error>> {
+                Ok((price.parse::<f32>()?, volume.parse::<f32>()?))
+            };
+        tickers
+            .into_iter()
+            .map(|(pair, ticker)| {
+                let book = OrderBook {
+                    asks: vec![level(ticker.a)?],
+                    bids: vec![level(ticker.b)?],
+                };
+                Ok((pair, book))
+            })
+            .collect()
+    }
+
+    /// Status of one of our orders, open or closed.
+    pub async fn query_order(&self, order_id: &str) -> Result<OrderInfo, Box<dyn Error>> {
+        let mut params = HashMap::new();
+        params.insert("txid".to_owned(), order_id.to_owned());
+        self.api_client
+            .query_private::<HashMap<String, OrderInfo>>(Method::QueryOrders, &mut params)
+            .await?
+            .into_result()?
+            .remove(order_id)
+            .ok_or_else(|| format!("unknown order {}", order_id).into())
+    }
+
+    /// Balance of `asset`, e.g. "ZUSD" or "XXBT". Zero when the account never held it.
+    pub async fn get_asset_balance(&self, asset: &str) -> Result<f32, Box<dyn Error>> {
+        let balances = self
+            .api_client
+            .query_private::<HashMap<String, String>>(Method::Balance, &mut HashMap::new())
+            .await?
+            .into_result()?;
+
+        match balances.get(asset) {
+            Some(balance) => Ok(balance.parse::<f32>()?),
+            None => Ok(0.0),
+        }
+    }
+
+    /// Get a token to authenticate on the private WebSocket API.
+    pub async fn get_websockets_token(&self) -> Result<String, Box<dyn Error>> {
+        let res = self
+            .api_client
+            .query_private::<WebSocketsToken>(Method::GetWebSocketsToken, &mut HashMap::new())
+            .await?;
+
+        Ok(res.into_result()?.token)
+    }
+
+    /// Open the private WebSocket, replacing any previous connection.
+    /// Our executions are pushed to the returned receiver, with their REST pair name,
+    /// including those missed while reconnecting.
+    pub async fn connect_private_ws(&self) -> Result<UnboundedReceiver<Fill>, Box<dyn Error>> {
+        let pair_names = self
+            .get_asset_pairs()
+            .await?
+            .into_iter()
+            .filter_map(|(name, pair)| Some((pair.wsname?, name)))
+            .collect();
+        let token = self.get_websockets_token().await?;
+        let (fills, receiver) = mpsc::unbounded_channel();
+        let ws =
+            KrakenPrivateWs::connect(token, fills, pair_names, self.order_tracker.clone()).await?;
+        *self.private_ws.lock().unwrap() = Some(Arc::new(ws));
+        Ok(receiver)
+    }
+
+    /// Private WebSocket connection, if `connect_private_ws` was called.
+    pub fn private_ws(&self) -> Option<Arc<KrakenPrivateWs>> {
+        self.private_ws.lock().unwrap().clone()
+    }
+
+    /// Maintain a local, checksum-verified order book of `pair` at least `depth` levels deep,
+    /// then served by `get_order_book` and `get_market_price`. The book resubscribes on its own
+    /// whenever a checksum does not match. A shallower feed of `pair` is closed.
+    pub async fn subscribe_book_feed(
+        &self,
+        pair: &str,
+        depth: u32,
+    ) -> Result<Arc<KrakenBookFeed>, Box<dyn Error>> {
+        if let Some(feed) = self.book_feed(pair) {
+            if feed.depth >= depth {
+                return Ok(feed);
+            }
         }
+        let wsname = self
+            .get_asset_pair(pair)
+            .await?
+            .wsname
+            .ok_or_else(|| format!("{} has no websocket name", pair))?;
+        let depth = BOOK_DEPTHS
+            .iter()
+            .copied()
+            .find(|allowed| *allowed >= depth)
+            .unwrap_or(BOOK_DEPTHS[BOOK_DEPTHS.len() - 1]);
+
+        let feed = Arc::new(KrakenBookFeed::subscribe(&wsname, depth));
+        let replaced = self
+            .book_feeds
+            .lock()
+            .unwrap()
+            .insert(pair.to_owned(), feed.clone());
+        if let Some(replaced) = replaced {
+            replaced.close();
+        }
+        Ok(feed)
+    }
+
+    /// Local order book feed of `pair`, if `subscribe_book_feed` was called.
+    pub fn book_feed(&self, pair: &str) -> Option<Arc<KrakenBookFeed>> {
+        self.book_feeds.lock().unwrap().get(pair).cloned()
     }
 
     async fn example_calls(&self) -> Result<(), Box<dyn Error>> {
@@ -60,118 +349,252 @@ impl Kraken {
 }
 
 /// Kraken Market implement  for Kraken
-#[async_trait(?Send)]
+#[async_trait]
 impl Market for Kraken {
     async fn get_balances(&self) -> Result<f32, Box<dyn Error>> {
-        let res: KrakenResponse<HashMap<String, String>> = self
+        self.get_asset_balance("ZUSD").await
+    }
+
+    async fn get_base_balance(&self, pair: &str) -> Result<f32, Box<dyn Error>> {
+        let asset_pair = self.get_asset_pair(pair).await?;
+        self.get_asset_balance(&asset_pair.base).await
+    }
+
+    /// Mid price of the local book when one is synced, else the last close.
+    async fn get_market_price(&self, pair: &str) -> Result<f32, Box<dyn Error>> {
+        if let Some(price) = self
+            .book_feed(pair)
+            .and_then(|feed| feed.order_book(1))
+            .and_then(|book| book.mid_price())
+        {
+            return Ok(price);
+        }
+
+        let ohlc = self.get_ohlc(pair, 240).await?;
+        let latest = ohlc.last().ok_or_else(|| {
+            KrakenError::new(None, vec![format!("no OHLC data returned for {}", pair)])
+        })?;
+        info!("latest OHLC is {:?}", latest);
+
+        Ok(latest.close)
+    }
+
+    async fn get_candles(&self, pair: &str, interval: u32) -> Result<Vec<Candle>, Box<dyn Error>> {
+        let mut ohlc = self.get_ohlc(pair, interval).await?;
+        // The last entry is the candle still in progress.
+        ohlc.pop();
+        Ok(ohlc)
+    }
+
+    /// Served from the local book when one is synced deep enough.
+    async fn get_order_book(&self, pair: &str, depth: u32) -> Result<OrderBook, Box<dyn Error>> {
+        if let Some(book) = self.book_feed(pair).and_then(|feed| feed.order_book(depth)) {
+            return Ok(book);
+        }
+
+        let mut params = HashMap::new();
+        params.insert("pair".into(), pair.into());
+        params.insert("count".into(), depth.to_string());
+        let res = self
             .api_client
-            .query_private::<HashMap<String, String>>(Method::Balance, &mut HashMap::new())
+            .query_public::<HashMap<String, DepthEntry>>(Method::Depth, &params)
             .await?;
 
-        let result = res.result.expect("Balance map return from api");
+        let entry = res
+            .into_result()?
+            .remove(pair)
+            .ok_or_else(|| format!("no order book returned for {}", pair))?;
+
+        let levels = |side: Vec<(String, String, u64)>| -> Result<Vec<(f32, f32)>, Box<dyn Error>> {
+            side.into_iter()
+                .map(|(price, volume, _)| Ok((price.parse::<f32>()?, volume.parse::<f32>()?)))
+                .collect()
+        };
+
+        Ok(OrderBook {
+            asks: levels(entry.asks)?,
+            bids: levels(entry.bids)?,
+        })
+    }
+
+    /// Volumes are rounded down to the lot decimals and prices to the pair decimals first.
+    async fn place_order(&self, order: &OrderRequest) -> Result<String, Box<dyn Error>> {
+        let asset_pair = self.get_asset_pair(&order.pair).await?;
+        let order = &order
+            .clone()
+            .with_lot_decimals(asset_pair.lot_decimals)
+            .with_price_decimals(asset_pair.pair_decimals);
+        order.validate()?;
+        let mut params = add_order_params(order);
+
+        if self.dry_run {
+            params.insert("validate".to_owned(), "true".to_owned());
+            let res = self
+                .api_client
+                .query_private::<AddOrderResult>(Method::AddOrder, &mut params)
+                .await?
+                .into_result()?;
+            info!("[DRY RUN] would place: {}", res.descr.order);
+            if let Some(close) = &res.descr.close {
+                info!("[DRY RUN] with conditional close: {}", close);
+            }
+            return Ok(format!(
+                "{}{:08X}",
+                DRY_RUN_ORDER_PREFIX,
+                rand::thread_rng().gen::<u32>()
+            ));
+        }
+
+        // Prefer the private socket when connected, it does not count against the REST limits.
+        // It names pairs by their WebSocket name, pairs without one go through REST.
+        if let Some(ws) = self.private_ws() {
+            if let Some(wsname) = &asset_pair.wsname {
+                let txid = ws.add_order(&ws_add_order_params(order, wsname)).await?;
+                info!("Order placed over websocket: {}", txid);
+                self.order_tracker.lock().unwrap().open.insert(txid.clone());
+                return Ok(txid);
+            }
+        }
 
-        if let Some(b) = result.get("ZUSD") {
-            Ok(b.parse::<f32>().expect("Balance should be number"))
-        } else {
-            Ok(0.0)
+        let res = self
+            .api_client
+            .query_private::<AddOrderResult>(Method::AddOrder, &mut params)
+            .await?
+            .into_result()?;
+        info!("Order placed: {}", res.descr.order);
+        if let Some(close) = &res.descr.close {
+            info!("Conditional close: {}", close);
         }
+
+        let txid = res
+            .txid
+            .and_then(|txids| txids.into_iter().next())
+            .ok_or_else(|| KrakenError::new(None, vec!["AddOrder returned no txid".to_owned()]))?;
+        self.order_tracker.lock().unwrap().open.insert(txid.clone());
+        Ok(txid)
     }
 
-    async fn get_market_price(&self) -> Result<f32, Box<dyn Error>> {
-        // https://api.kraken.com/0/public/OHLC?pair=TBTCUSD&interval=60&since=1607023200
+    async fn cancel_order(&self, order_id: &str) -> Result<(), Box<dyn Error>> {
+        if order_id.starts_with(DRY_RUN_ORDER_PREFIX) {
+            info!("[DRY RUN] would cancel: {}", order_id);
+            return Ok(());
+        }
+
+        if let Some(ws) = self.private_ws() {
+            return ws.cancel_order(&[order_id.to_owned()]).await;
+        }
+
         let mut params = HashMap::new();
-        params.insert("pair".into(), BTCUSD.into());
-        params.insert("interval".into(), "240".into());
+        params.insert("txid".to_owned(), order_id.to_owned());
         let res = self
             .api_client
-            .query_public::<HashMap<String, serde_json::Value>>(Method::OHLC, &params)
-            .await?;
+            .query_private::<CancelOrderResult>(Method::CancelOrder, &mut params)
+            .await?
+            .into_result()?;
+        info!("Order {} cancelled ({} orders)", order_id, res.count);
+        Ok(())
+    }
 
-        let result = res.result.expect("Should have result");
+    /// Resting orders, followed on the private socket from then on in case they get adopted.
+    async fn get_open_orders(&self) -> Result<Vec<OpenOrder>, Box<dyn Error>> {
+        let res = self
+            .api_client
+            .query_private::<OpenOrdersResult>(Method::OpenOrders, &mut HashMap::new())
+            .await?
+            .into_result()?;
 
-        let data = result
-            .get(BTCUSD)
-            .expect("OHLC data should exist for the queried pair");
-
-        let data = data.as_array().expect("should in array of OHLC");
-        let length = data.len();
-        let data = &data[length - 2..length];
-
-        let ohlc: (chrono::DateTime<chrono::Utc>, f32, f32, f32, f32, f32, u64) = data.iter().map(|val| {
-            let val = val.as_array()
-            .expect("OHLC should in array format: [1609027200,\"26560.5\",\"26560.5\",\"26560.5\",\"26560.5\",\"0.0\",\"0.00000000\",0]");
-
-            let epoch = val
-                .get(0)
-                .expect("has epoch")
-                .as_i64()
-                .expect("should be int");
-            let open = val
-                .get(1)
-                .expect("has open")
-                .as_str()
-                .expect("should be string")
-                .parse::<f32>()
-                .expect("should be float");
-            let high = val
-                .get(2)
-                .expect("has high")
-                .as_str()
-                .expect("should be string")
-                .parse::<f32>()
-                .expect("should be float");
-            let low = val
-                .get(3)
-                .expect("has low")
-                .as_str()
-                .expect("should be string")
-                .parse::<f32>()
-                .expect("should be float");
-            let close = val
-                .get(4)
-                .expect("has close")
-                .as_str()
-                .expect("should be string")
-                .parse::<f32>()
-                .expect("should be float");
-            let vwap = val
-                .get(6)
-                .expect("has vwap")
-                .as_str()
-                .expect("should be string")
-                .parse::<f32>()
-                .expect("should be float");
-            let volume = val
-                .get(6)
-                .expect("has vwap")
-                .as_str()
-                .expect("should be string")
-                .parse::<f32>()
-                .expect("should be float");
-            let count = val
-                .get(7)
-                .expect("has count")
-                .as_u64()
-                .expect("should be int");
-
-            let timestamp = Utc.timestamp(epoch, 0);
-
-            let ohlc = (timestamp, open, high, low, close, volume, count);
-            ohlc
-        }).next().expect("OHLC should exit");
-
-        info!("latest OHLC is {:?}", ohlc);
-
-        Ok(0.0)
-    }
-
-    async fn place_sell_order(&self, amount: f32) -> Result<f32, Box<dyn Error>> {
-        // coinbase API call
-        Ok(amount)
-    }
-
-    async fn place_buy_order(&self, amount: f32) -> Result<f32, Box<dyn Error>> {
-        // coinbase API call
-        Ok(amount)
+        self.order_tracker
+            .lock()
+            .unwrap()
+            .open
+            .extend(res.open.keys().cloned());
+        let mut orders = Vec::new();
+        for (id, info) in res.open {
+            orders.push(OpenOrder {
+                id,
+                pair: self.pair_by_altname(&info.descr.pair).await?,
+                side: match info.descr.side.as_str() {
+                    "buy" => OrderSide::Buy,
+                    _ => OrderSide::Sell,
+                },
+                price: info.descr.price.parse()?,
+                volume: info.vol.parse()?,
+                volume_executed: info.vol_exec.parse()?,
+                userref: info.userref,
+            });
+        }
+        Ok(orders)
     }
+
+    fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+
+    async fn subscribe_fills(&self) -> Result<Option<UnboundedReceiver<Fill>>, Box<dyn Error>> {
+        Ok(Some(self.connect_private_ws().await?))
+    }
+
+    fn take_closed_orders(&self) -> Vec<String> {
+        self.order_tracker
+            .lock()
+            .unwrap()
+            .closed
+            .drain(..)
+            .collect()
+    }
+
+    async fn subscribe_book(&self, pair: &str, depth: u32) -> Result<(), Box<dyn Error>> {
+        self.subscribe_book_feed(pair, depth).await?;
+        Ok(())
+    }
+}
+
+/// Parse a `Trades` entry: `[price, volume, time, side, type, misc, ...]`.
+pub fn parse_trade(val: &serde_json::Value) -> Result<Trade, Box<dyn Error>> {
+    let number = |index: usize| -> Result<f64, Box<dyn Error>> {
+        match val.get(index) {
+            Some(serde_json::Value::String(s)) => Ok(s.parse::<f64>()?),
+            Some(serde_json::Value::Number(n)) => Ok(n.as_f64().unwrap_or_default()),
+            _ => Err(format!("invalid trade entry {}", val).into()),
+        }
+    };
+
+    let time = number(2)?;
+    Ok(Trade {
+        time: Utc.timestamp(time.trunc() as i64, (time.fract() * 1e9) as u32),
+        price: number(0)? as f32,
+        volume: number(1)? as f32,
+    })
+}
+
+/// Parse one OHLC entry: [time, open, high, low, close, vwap, volume, count]
+pub fn parse_ohlc(val: &serde_json::Value) -> Result<Candle, Box<dyn Error>> {
+    let invalid = || format!("invalid OHLC entry {}", val);
+    let number = |index: usize| -> Result<f32, Box<dyn Error>> {
+        Ok(val
+            .get(index)
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(invalid)?
+            .parse::<f32>()?)
+    };
+
+    let epoch = val
+        .get(0)
+        .and_then(serde_json::Value::as_i64)
+        .ok_or_else(invalid)?;
+    let count = val
+        .get(7)
+        .and_then(serde_json::Value::as_u64)
+        .ok_or_else(invalid)?;
+
+    Ok(Candle {
+        time: Utc.timestamp(epoch, 0),
+        open: number(1)?,
+        high: number(2)?,
+        low: number(3)?,
+        close: number(4)?,
+        vwap: number(5)?,
+        volume: number(6)?,
+        count,
+    })
 }
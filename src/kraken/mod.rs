@@ -1,5 +1,7 @@
 pub mod api;
 pub mod kraken;
 pub mod kraken_impl;
+pub mod order_params;
+pub mod ws;
 
 pub use kraken::Kraken;
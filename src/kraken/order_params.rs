@@ -0,0 +1,126 @@
+use crate::tradingbot::order::OrderRequest;
+use std::collections::HashMap;
+
+/// Map an order to Kraken `AddOrder` parameters.
+pub fn add_order_params(order: &OrderRequest) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+    let side: &str = order.side.into();
+    let order_type: &str = order.order_type.into();
+
+    params.insert("pair".to_owned(), order.pair.clone());
+    params.insert("type".to_owned(), side.to_owned());
+    params.insert("ordertype".to_owned(), order_type.to_owned());
+    params.insert("volume".to_owned(), order.volume.to_string());
+
+    if let Some(price) = order.price {
+        params.insert("price".to_owned(), price.to_string());
+    }
+    if let Some(price2) = order.price2 {
+        params.insert("price2".to_owned(), price2.to_string());
+    }
+    if let Some(leverage) = order.leverage {
+        params.insert("leverage".to_owned(), leverage.to_string());
+    }
+    if !order.flags.is_empty() {
+        let flags: Vec<&str> = order.flags.iter().map(|f| (*f).into()).collect();
+        params.insert("oflags".to_owned(), flags.join(","));
+    }
+    if let Some(time_in_force) = order.time_in_force {
+        let time_in_force: &str = time_in_force.into();
+        params.insert("timeinforce".to_owned(), time_in_force.to_owned());
+    }
+    if let Some(start_time) = order.start_time {
+        params.insert("starttm".to_owned(), start_time.to_string());
+    }
+    if let Some(expire_time) = order.expire_time {
+        params.insert("expiretm".to_owned(), expire_time.to_string());
+    }
+    if let Some(userref) = order.userref {
+        params.insert("userref".to_owned(), userref.to_string());
+    }
+    if let Some(close) = &order.close {
+        let close_type: &str = close.order_type.into();
+        params.insert("close[ordertype]".to_owned(), close_type.to_owned());
+        params.insert("close[price]".to_owned(), close.price.to_string());
+        if let Some(price2) = close.price2 {
+            params.insert("close[price2]".to_owned(), price2.to_string());
+        }
+    }
+
+    params
+}
+
+/// Map an order to private WebSocket `addOrder` fields: the `AddOrder` ones with the pair under
+/// its WebSocket name, e.g. "XBT/USD".
+pub fn ws_add_order_params(order: &OrderRequest, wsname: &str) -> HashMap<String, String> {
+    let mut params = add_order_params(order);
+    params.insert("pair".to_owned(), wsname.to_owned());
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tradingbot::order::{
+        OrderFlag, OrderSide, OrderTime, OrderType, Price, TimeInForce,
+    };
+
+    #[test]
+    fn test_market_order_params() {
+        let params = add_order_params(&OrderRequest::market(OrderSide::Buy, "XXBTZUSD", 0.5));
+        assert_eq!(params.len(), 4);
+        assert_eq!(params["type"], "buy");
+        assert_eq!(params["ordertype"], "market");
+        assert_eq!(params["volume"], "0.5");
+    }
+
+    #[test]
+    fn test_full_order_params() {
+        let order = OrderRequest {
+            order_type: OrderType::StopLossLimit,
+            price: Some(Price::Percent(-5.0)),
+            price2: Some(Price::Offset(-10.0)),
+            leverage: Some(2),
+            flags: vec![OrderFlag::PostOnly, OrderFlag::FeeInQuote],
+            time_in_force: Some(TimeInForce::GoodTillDate),
+            start_time: Some(OrderTime::Now),
+            expire_time: Some(OrderTime::In(3600)),
+            userref: Some(42),
+            ..OrderRequest::market(OrderSide::Sell, "XXBTZUSD", 1.0)
+        };
+        let params = add_order_params(&order);
+
+        assert_eq!(params["type"], "sell");
+        assert_eq!(params["ordertype"], "stop-loss-limit");
+        assert_eq!(params["price"], "-5%");
+        assert_eq!(params["price2"], "-10");
+        assert_eq!(params["leverage"], "2");
+        assert_eq!(params["oflags"], "post,fciq");
+        assert_eq!(params["timeinforce"], "GTD");
+        assert_eq!(params["starttm"], "0");
+        assert_eq!(params["expiretm"], "+3600");
+        assert_eq!(params["userref"], "42");
+    }
+
+    #[test]
+    fn test_conditional_close_params() {
+        let order = OrderRequest::limit(OrderSide::Buy, "XXBTZUSD", 0.1, 20000.0)
+            .with_take_profit(Price::Absolute(22000.0));
+        let params = add_order_params(&order);
+
+        assert_eq!(params["close[ordertype]"], "take-profit");
+        assert_eq!(params["close[price]"], "22000");
+        assert!(!params.contains_key("close[price2]"));
+    }
+
+    #[test]
+    fn test_ws_order_params() {
+        let order = OrderRequest::limit(OrderSide::Buy, "XXBTZUSD", 0.1, 20000.0);
+        let params = ws_add_order_params(&order, "XBT/USD");
+
+        assert_eq!(params["pair"], "XBT/USD");
+        assert_eq!(params["ordertype"], "limit");
+        assert_eq!(params["price"], "20000");
+        assert_eq!(params.len(), add_order_params(&order).len());
+    }
+}
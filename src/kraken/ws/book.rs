@@ -0,0 +1,239 @@
+use crate::tradingbot::order_book::OrderBook;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// One price level, kept as the exchange's strings since the checksum is computed over them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Level {
+    pub price: String,
+    pub volume: String,
+}
+
+/// Local L2 order book maintained from the `book` channel.
+#[derive(Debug, Default)]
+pub struct LocalBook {
+    pub depth: usize,
+    /// Asks keyed by scaled price, lowest first.
+    pub asks: BTreeMap<u64, Level>,
+    /// Bids keyed by scaled price, lowest first (best bid is the last entry).
+    pub bids: BTreeMap<u64, Level>,
+    /// Whether a snapshot was received since the last reset.
+    pub synced: bool,
+}
+
+/// Result of applying an update to the book.
+#[derive(Debug, PartialEq)]
+pub enum BookUpdate {
+    Applied,
+    ChecksumMismatch { expected: u32, computed: u32 },
+}
+
+impl LocalBook {
+    pub fn new(depth: usize) -> Self {
+        LocalBook {
+            depth,
+            ..Default::default()
+        }
+    }
+
+    /// Drop every level, waiting for a new snapshot.
+    pub fn reset(&mut self) {
+        self.asks.clear();
+        self.bids.clear();
+        self.synced = false;
+    }
+
+    /// Apply a snapshot payload: `{"as": [[price, volume, timestamp], ...], "bs": [...]}`.
+    pub fn apply_snapshot(&mut self, payload: &Value) {
+        self.reset();
+        for level in levels(payload, "as") {
+            insert_level(&mut self.asks, level);
+        }
+        for level in levels(payload, "bs") {
+            insert_level(&mut self.bids, level);
+        }
+        self.truncate();
+        self.synced = true;
+    }
+
+    /// Apply the update payloads of one message and verify the checksum it carries.
+    pub fn apply_update(&mut self, payloads: &[&Value]) -> BookUpdate {
+        let mut expected = None;
+
+        for payload in payloads {
+            for level in levels(payload, "a") {
+                insert_level(&mut self.asks, level);
+            }
+            for level in levels(payload, "b") {
+                insert_level(&mut self.bids, level);
+            }
+            if let Some(c) = payload.get("c").and_then(Value::as_str) {
+                expected = c.parse::<u32>().ok();
+            }
+        }
+        self.truncate();
+
+        match expected {
+            Some(expected) => {
+                let computed = self.checksum();
+                if computed == expected {
+                    BookUpdate::Applied
+                } else {
+                    BookUpdate::ChecksumMismatch { expected, computed }
+                }
+            }
+            None => BookUpdate::Applied,
+        }
+    }
+
+    /// CRC32 over the top 10 asks (best first) then the top 10 bids (best first).
+    pub fn checksum(&self) -> u32 {
+        let mut input = String::new();
+        for level in self.asks.values().take(10) {
+            push_level(&mut input, level);
+        }
+        for level in self.bids.values().rev().take(10) {
+            push_level(&mut input, level);
+        }
+        crc32fast::hash(input.as_bytes())
+    }
+
+    pub fn best_ask(&self) -> Option<f32> {
+        self.asks.values().next().and_then(|l| l.price.parse().ok())
+    }
+
+    pub fn best_bid(&self) -> Option<f32> {
+        self.bids
+            .values()
+            .next_back()
+            .and_then(|l| l.price.parse().ok())
+    }
+
+    /// Best `depth` levels of each side, best first.
+    pub fn order_book(&self, depth: usize) -> OrderBook {
+        let level = |l: &Level| Some((l.price.parse::<f32>().ok()?, l.volume.parse::<f32>().ok()?));
+        OrderBook {
+            asks: self.asks.values().take(depth).filter_map(level).collect(),
+            bids: self
+                .bids
+                .values()
+                .rev()
+                .take(depth)
+                .filter_map(level)
+                .collect(),
+        }
+    }
+
+    fn truncate(&mut self) {
+        while self.asks.len() > self.depth {
+            let worst = *self.asks.keys().next_back().unwrap();
+            self.asks.remove(&worst);
+        }
+        while self.bids.len() > self.depth {
+            let worst = *self.bids.keys().next().unwrap();
+            self.bids.remove(&worst);
+        }
+    }
+}
+
+fn levels<'a>(payload: &'a Value, side: &str) -> impl Iterator<Item = Level> + 'a {
+    payload
+        .get(side)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|level| {
+            Some(Level {
+                price: level.get(0)?.as_str()?.to_owned(),
+                volume: level.get(1)?.as_str()?.to_owned(),
+            })
+        })
+}
+
+fn insert_level(side: &mut BTreeMap<u64, Level>, level: Level) {
+    let key = match level.price.parse::<f64>() {
+        Ok(price) => (price * 1e8).round() as u64,
+        Err(_) => return,
+    };
+
+    if level.volume.parse::<f64>().unwrap_or(0.0) == 0.0 {
+        side.remove(&key);
+    } else {
+        side.insert(key, level);
+    }
+}
+
+fn push_level(input: &mut String, level: &Level) {
+    input.push_str(&checksum_format(&level.price));
+    input.push_str(&checksum_format(&level.volume));
+}
+
+/// Remove the decimal point and leading zeros, e.g. "0.00000500" -> "500".
+fn checksum_format(value: &str) -> String {
+    value.replace('.', "").trim_start_matches('0').to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn snapshot() -> Value {
+        json!({
+            "as": [
+                ["5541.30000", "2.50700000", "1534614248.123678"],
+                ["5541.80000", "0.33000000", "1534614098.345543"],
+                ["5542.70000", "0.64700000", "1534614244.654432"]
+            ],
+            "bs": [
+                ["5541.20000", "1.52900000", "1534614248.765567"],
+                ["5539.90000", "0.30000000", "1534614241.769870"],
+                ["5539.50000", "5.00000000", "1534613831.243486"]
+            ]
+        })
+    }
+
+    #[test]
+    fn test_checksum_format() {
+        assert_eq!(checksum_format("0.00000500"), "500");
+        assert_eq!(checksum_format("5541.30000"), "554130000");
+    }
+
+    #[test]
+    fn test_checksum() {
+        let mut book = LocalBook::new(10);
+        book.apply_snapshot(&snapshot());
+
+        // crc32 of "554130000250700000554180000330000005542700006470000055412000015290000055399000030000000553950000500000000"
+        assert_eq!(book.checksum(), 1_710_400_350);
+        assert_eq!(book.best_ask(), Some(5541.3));
+        assert_eq!(book.best_bid(), Some(5541.2));
+
+        let order_book = book.order_book(2);
+        assert_eq!(order_book.asks, vec![(5541.3, 2.507), (5541.8, 0.33)]);
+        assert_eq!(order_book.bids, vec![(5541.2, 1.529), (5539.9, 0.3)]);
+    }
+
+    #[test]
+    fn test_update_removes_and_truncates() {
+        let mut book = LocalBook::new(2);
+        book.apply_snapshot(&snapshot());
+        assert_eq!(book.asks.len(), 2);
+
+        let update = json!({ "a": [["5541.30000", "0.00000000", "1534614335.345903"]] });
+        assert_eq!(book.apply_update(&[&update]), BookUpdate::Applied);
+        assert_eq!(book.best_ask(), Some(5541.8));
+    }
+
+    #[test]
+    fn test_checksum_mismatch() {
+        let mut book = LocalBook::new(10);
+        book.apply_snapshot(&snapshot());
+
+        let update = json!({ "b": [["5541.20000", "1.00000000", "1534614335.345903"]], "c": "1" });
+        match book.apply_update(&[&update]) {
+            BookUpdate::ChecksumMismatch { expected, .. } => assert_eq!(expected, 1),
+            BookUpdate::Applied => panic!("checksum should not match"),
+        }
+    }
+}
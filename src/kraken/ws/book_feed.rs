@@ -0,0 +1,14 @@
+use super::book::LocalBook;
+use std::sync::{atomic::AtomicU64, Arc, Mutex};
+use tokio::task::JoinHandle;
+
+/// Kraken public `book` subscription maintaining a checksum-verified local book
+pub struct KrakenBookFeed {
+    pub pair: String,
+    pub depth: u32,
+    pub book: Arc<Mutex<LocalBook>>,
+    /// Number of checksum mismatches seen, each one triggering a resubscribe.
+    pub checksum_mismatches: Arc<AtomicU64>,
+    /// Background task keeping `book` up to date, aborted by `close`.
+    pub task: JoinHandle<()>,
+}
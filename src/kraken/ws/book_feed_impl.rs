@@ -0,0 +1,157 @@
+/// Kraken public order book feed implementation
+use super::{
+    book::{BookUpdate, LocalBook},
+    book_feed::KrakenBookFeed,
+};
+use crate::tradingbot::order_book::OrderBook;
+use futures_util::{SinkExt, StreamExt};
+use log::{debug, info, trace, warn};
+use serde_json::{json, Value};
+use std::error::Error;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use tokio::time;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+const WS_PUBLIC_URL: &str = "wss://ws.kraken.com";
+const RECONNECT_DELAY_SECS: u64 = 5;
+
+impl KrakenBookFeed {
+    /// Subscribe to the book of `pair` (WebSocket name, e.g. "XBT/USD") and keep it
+    /// up to date in the background, reconnecting when the socket drops.
+    pub fn subscribe(pair: &str, depth: u32) -> KrakenBookFeed {
+        let book = Arc::new(Mutex::new(LocalBook::new(depth as usize)));
+        let checksum_mismatches = Arc::new(AtomicU64::new(0));
+
+        let task_pair = pair.to_owned();
+        let task_book = book.clone();
+        let mismatches = checksum_mismatches.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                if let Err(e) = run_feed(&task_pair, depth, &task_book, &mismatches).await {
+                    warn!("[BOOK] {} feed error: {}", task_pair, e);
+                }
+                task_book.lock().unwrap().reset();
+                time::sleep(time::Duration::from_secs(RECONNECT_DELAY_SECS)).await;
+            }
+        });
+
+        KrakenBookFeed {
+            pair: pair.to_owned(),
+            depth,
+            book,
+            checksum_mismatches,
+            task,
+        }
+    }
+
+    /// Stop updating the book and drop the socket. The book is left unsynced, so it serves
+    /// nothing anymore.
+    pub fn close(&self) {
+        self.task.abort();
+        self.book.lock().unwrap().reset();
+    }
+
+    /// Best bid and ask, once the book holds a verified snapshot.
+    pub fn best_bid_ask(&self) -> Option<(f32, f32)> {
+        let book = self.book.lock().unwrap();
+        if !book.synced {
+            return None;
+        }
+        Some((book.best_bid()?, book.best_ask()?))
+    }
+
+    /// Best `depth` levels, once the book holds a verified snapshot at least that deep.
+    pub fn order_book(&self, depth: u32) -> Option<OrderBook> {
+        let book = self.book.lock().unwrap();
+        if !book.synced || depth > self.depth {
+            return None;
+        }
+        Some(book.order_book(depth as usize))
+    }
+
+    pub fn checksum_mismatches(&self) -> u64 {
+        self.checksum_mismatches.load(Ordering::Relaxed)
+    }
+}
+
+fn subscription(event: &str, pair: &str, depth: u32) -> Message {
+    let msg = json!({
+        "event": event,
+        "pair": [pair],
+        "subscription": { "name": "book", "depth": depth },
+    });
+    Message::Text(msg.to_string())
+}
+
+async fn run_feed(
+    pair: &str,
+    depth: u32,
+    book: &Mutex<LocalBook>,
+    mismatches: &AtomicU64,
+) -> Result<(), Box<dyn Error>> {
+    let (mut stream, _) = connect_async(WS_PUBLIC_URL).await?;
+    info!("[BOOK] connected to {} for {}", WS_PUBLIC_URL, pair);
+    stream.send(subscription("subscribe", pair, depth)).await?;
+
+    while let Some(msg) = stream.next().await {
+        let text = match msg? {
+            Message::Text(text) => text,
+            Message::Close(frame) => {
+                warn!("[BOOK] socket closed: {:?}", frame);
+                return Ok(());
+            }
+            _ => continue,
+        };
+
+        let value: Value = serde_json::from_str(&text)?;
+        // Book data: [channelID, payload, (payload,) channelName, pair]
+        let data = match value.as_array() {
+            Some(data) if data.len() >= 4 => data,
+            _ => {
+                trace!("[BOOK] {}", value);
+                continue;
+            }
+        };
+        let payloads: Vec<&Value> = data[1..data.len() - 2].iter().collect();
+
+        let resync = {
+            let mut book = book.lock().unwrap();
+            if payloads
+                .iter()
+                .any(|p| p.get("as").is_some() || p.get("bs").is_some())
+            {
+                book.apply_snapshot(payloads[0]);
+                debug!("[BOOK] {} snapshot, checksum {}", pair, book.checksum());
+                false
+            } else if !book.synced {
+                // Updates still in flight from before a resubscribe.
+                false
+            } else {
+                match book.apply_update(&payloads) {
+                    BookUpdate::Applied => false,
+                    BookUpdate::ChecksumMismatch { expected, computed } => {
+                        let count = mismatches.fetch_add(1, Ordering::Relaxed) + 1;
+                        warn!(
+                            "[BOOK] {} checksum mismatch: expected {} computed {} (mismatch #{}), resubscribing",
+                            pair, expected, computed, count
+                        );
+                        book.reset();
+                        true
+                    }
+                }
+            }
+        };
+
+        if resync {
+            stream
+                .send(subscription("unsubscribe", pair, depth))
+                .await?;
+            stream.send(subscription("subscribe", pair, depth)).await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,205 @@
+use crate::tradingbot::order::{Fill, OrderSide};
+use chrono::{TimeZone, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Messages pushed on the private WebSocket we care about.
+#[derive(Debug)]
+pub enum PrivateMessage {
+    /// Executions from the `ownTrades` channel.
+    OwnTrades(Vec<Fill>),
+    /// Order id and new status from the `openOrders` channel. The snapshot sent first on
+    /// subscription lists every order resting at that time.
+    OpenOrders {
+        orders: Vec<(String, String)>,
+        snapshot: bool,
+    },
+    /// Reply to an `addOrder`/`cancelOrder` request.
+    OrderStatus {
+        reqid: u64,
+        txid: Option<String>,
+        error: Option<String>,
+    },
+    /// Heartbeats, subscription and system status.
+    Other(Value),
+}
+
+/// Parse one text frame received on the private socket. Fill pairs are renamed from their
+/// WebSocket name to the REST one found in `pair_names`, e.g. "XBT/USD" to "XXBTZUSD".
+pub fn parse_private_message(
+    text: &str,
+    pair_names: &HashMap<String, String>,
+) -> Result<PrivateMessage, serde_json::Error> {
+    let value: Value = serde_json::from_str(text)?;
+
+    if let Some(event) = value.get("event").and_then(Value::as_str) {
+        if event == "addOrderStatus" || event == "cancelOrderStatus" {
+            let reqid = value.get("reqid").and_then(Value::as_u64).unwrap_or(0);
+            let error = if value.get("status").and_then(Value::as_str) == Some("ok") {
+                None
+            } else {
+                Some(
+                    value
+                        .get("errorMessage")
+                        .and_then(Value::as_str)
+                        .unwrap_or("unknown error")
+                        .to_owned(),
+                )
+            };
+            let txid = value
+                .get("txid")
+                .and_then(Value::as_str)
+                .map(|s| s.to_owned());
+            return Ok(PrivateMessage::OrderStatus { reqid, txid, error });
+        }
+        return Ok(PrivateMessage::Other(value));
+    }
+
+    // Channel data: [payload, channelName, {"sequence": n}], the snapshot is sequence 1.
+    let channel = value.get(1).and_then(Value::as_str);
+    let payload = value.get(0).and_then(Value::as_array);
+    let sequence = value
+        .get(2)
+        .and_then(|v| v.get("sequence"))
+        .and_then(Value::as_u64);
+
+    match (channel, payload) {
+        (Some("ownTrades"), Some(payload)) => Ok(PrivateMessage::OwnTrades(
+            payload
+                .iter()
+                .filter_map(Value::as_object)
+                .flat_map(|trades| trades.iter())
+                .filter_map(|(trade_id, trade)| parse_own_trade(trade_id, trade))
+                .map(|mut fill| {
+                    if let Some(name) = pair_names.get(&fill.pair) {
+                        fill.pair = name.clone();
+                    }
+                    fill
+                })
+                .collect(),
+        )),
+        (Some("openOrders"), Some(payload)) => Ok(PrivateMessage::OpenOrders {
+            orders: payload
+                .iter()
+                .filter_map(Value::as_object)
+                .flat_map(|orders| orders.iter())
+                .filter_map(|(order_id, order)| {
+                    order
+                        .get("status")
+                        .and_then(Value::as_str)
+                        .map(|status| (order_id.to_owned(), status.to_owned()))
+                })
+                .collect(),
+            snapshot: sequence == Some(1),
+        }),
+        _ => Ok(PrivateMessage::Other(value)),
+    }
+}
+
+fn parse_own_trade(trade_id: &str, trade: &Value) -> Option<Fill> {
+    let number = |key: &str| {
+        trade
+            .get(key)
+            .and_then(Value::as_str)
+            .and_then(|s| s.parse::<f32>().ok())
+    };
+
+    let side = match trade.get("type").and_then(Value::as_str)? {
+        "buy" => OrderSide::Buy,
+        "sell" => OrderSide::Sell,
+        _ => return None,
+    };
+
+    let time = trade
+        .get("time")
+        .and_then(Value::as_str)
+        .and_then(|s| s.parse::<f64>().ok())?;
+
+    Some(Fill {
+        trade_id: trade_id.to_owned(),
+        order_id: trade.get("ordertxid").and_then(Value::as_str)?.to_owned(),
+        pair: trade.get("pair").and_then(Value::as_str)?.to_owned(),
+        side,
+        price: number("price")?,
+        volume: number("vol")?,
+        fee: number("fee").unwrap_or(0.0),
+        time: Utc.timestamp(time.trunc() as i64, (time.fract() * 1e9) as u32),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_own_trades() {
+        let text = r#"[[{"TDLH43-DVQXD-2KHVYY":{"cost":"1000000.00000","fee":"1600.00000","margin":"0.00000","ordertxid":"TDLH43-DVQXD-2KHVYY","ordertype":"limit","pair":"XBT/EUR","postxid":"OGTT3Y-C6I3P-XRI6HX","price":"100000.00000","time":"1560516023.070651","type":"sell","vol":"1000000000.00000000"}}],"ownTrades",{"sequence":2}]"#;
+
+        let pair_names = [("XBT/EUR".to_owned(), "XXBTZEUR".to_owned())]
+            .iter()
+            .cloned()
+            .collect();
+        match parse_private_message(text, &pair_names).unwrap() {
+            PrivateMessage::OwnTrades(fills) => {
+                assert_eq!(fills.len(), 1);
+                assert_eq!(fills[0].pair, "XXBTZEUR");
+                assert_eq!(fills[0].order_id, "TDLH43-DVQXD-2KHVYY");
+                assert_eq!(fills[0].side, OrderSide::Sell);
+                assert_eq!(fills[0].price, 100000.0);
+                assert_eq!(fills[0].time.timestamp(), 1560516023);
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_open_orders() {
+        let snapshot = r#"[[{"OGTT3Y-C6I3P-XRI6HX":{"status":"open","vol":"1.0"}}],"openOrders",{"sequence":1}]"#;
+        let update =
+            r#"[[{"OGTT3Y-C6I3P-XRI6HX":{"status":"canceled"}}],"openOrders",{"sequence":2}]"#;
+
+        match parse_private_message(snapshot, &HashMap::new()).unwrap() {
+            PrivateMessage::OpenOrders { orders, snapshot } => {
+                assert_eq!(
+                    orders,
+                    vec![("OGTT3Y-C6I3P-XRI6HX".to_owned(), "open".to_owned())]
+                );
+                assert!(snapshot);
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+        match parse_private_message(update, &HashMap::new()).unwrap() {
+            PrivateMessage::OpenOrders { orders, snapshot } => {
+                assert_eq!(orders[0].1, "canceled");
+                assert!(!snapshot);
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_add_order_status() {
+        let text = r#"{"descr":"buy 0.01770000 XBTUSD @ limit 4000","event":"addOrderStatus","reqid":7,"status":"ok","txid":"ONPNXH-KMKMU-F4MR5V"}"#;
+
+        match parse_private_message(text, &HashMap::new()).unwrap() {
+            PrivateMessage::OrderStatus { reqid, txid, error } => {
+                assert_eq!(reqid, 7);
+                assert_eq!(txid.as_deref(), Some("ONPNXH-KMKMU-F4MR5V"));
+                assert!(error.is_none());
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_add_order_error() {
+        let text = r#"{"errorMessage":"EOrder:Order minimum not met","event":"addOrderStatus","reqid":8,"status":"error"}"#;
+
+        match parse_private_message(text, &HashMap::new()).unwrap() {
+            PrivateMessage::OrderStatus { error, .. } => {
+                assert_eq!(error.as_deref(), Some("EOrder:Order minimum not met"));
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,9 @@
+pub mod book;
+pub mod book_feed;
+pub mod book_feed_impl;
+pub mod messages;
+pub mod private_ws;
+pub mod private_ws_impl;
+
+pub use book_feed::KrakenBookFeed;
+pub use private_ws::{KrakenPrivateWs, OrderTracker};
@@ -0,0 +1,29 @@
+use super::super::api::error::KrakenError;
+use std::collections::{HashMap, HashSet};
+use std::sync::{atomic::AtomicU64, Arc, Mutex};
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Reply channels for in-flight `addOrder`/`cancelOrder` requests, keyed by reqid.
+pub type PendingRequests =
+    Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Option<String>, KrakenError>>>>>;
+
+/// Our orders and trades as reported on the private socket, kept by `Kraken` across
+/// reconnects so the snapshots sent on each subscription can be reconciled.
+#[derive(Debug, Default)]
+pub struct OrderTracker {
+    /// Orders placed or seen resting, until the socket reports them closed.
+    pub open: HashSet<String>,
+    /// Orders cancelled or expired, or gone while disconnected, for `take_closed_orders`.
+    pub closed: Vec<String>,
+    /// Trades already sent as fills, the `ownTrades` snapshot repeats them.
+    pub trades: HashSet<String>,
+}
+
+/// Kraken authenticated WebSocket connection
+pub struct KrakenPrivateWs {
+    pub token: String,
+    pub sender: mpsc::UnboundedSender<Message>,
+    pub pending: PendingRequests,
+    pub next_reqid: AtomicU64,
+}
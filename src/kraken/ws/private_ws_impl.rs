@@ -0,0 +1,299 @@
+/// Kraken authenticated WebSocket implementation
+use super::{
+    super::api::error::KrakenError,
+    messages::{parse_private_message, PrivateMessage},
+    private_ws::{KrakenPrivateWs, OrderTracker, PendingRequests},
+};
+use crate::tradingbot::order::Fill;
+use futures_util::{SinkExt, StreamExt};
+use log::{debug, info, trace, warn};
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+const WS_AUTH_URL: &str = "wss://ws-auth.kraken.com";
+/// Kraken sends a heartbeat every second when idle, the connection is dropped after this long
+/// without any message.
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time to wait for the reply to an `addOrder`/`cancelOrder` request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl OrderTracker {
+    /// Fills not sent before, the others are repeated by a snapshot.
+    pub fn new_fills(&mut self, fills: Vec<Fill>) -> Vec<Fill> {
+        fills
+            .into_iter()
+            .filter(|fill| self.trades.insert(fill.trade_id.clone()))
+            .collect()
+    }
+
+    /// Follow status updates of the `openOrders` channel. Cancelled and expired orders are
+    /// closed, so are the open ones missing from a snapshot: they closed while disconnected,
+    /// their fills, if any, come with the `ownTrades` snapshot.
+    pub fn on_open_orders(&mut self, orders: Vec<(String, String)>, snapshot: bool) {
+        if snapshot {
+            let resting: HashSet<&String> = orders.iter().map(|(order_id, _)| order_id).collect();
+            let gone: Vec<String> = self
+                .open
+                .iter()
+                .filter(|order_id| !resting.contains(order_id))
+                .cloned()
+                .collect();
+            for order_id in gone {
+                debug!("Order {} closed while disconnected", order_id);
+                self.open.remove(&order_id);
+                self.closed.push(order_id);
+            }
+        }
+
+        for (order_id, status) in orders {
+            debug!("Order {} is {}", order_id, status);
+            match status.as_str() {
+                "pending" | "open" => {
+                    self.open.insert(order_id);
+                }
+                "closed" => {
+                    self.open.remove(&order_id);
+                }
+                "canceled" | "expired" => {
+                    self.open.remove(&order_id);
+                    self.closed.push(order_id);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl KrakenPrivateWs {
+    /// Connect to the private socket and subscribe to `ownTrades` and `openOrders`.
+    /// Every execution of our orders not yet in `tracker` is sent to `fills`, its pair renamed
+    /// through `pair_names` (REST names by WebSocket name). The connection is dropped, closing
+    /// `fills`, when Kraken stays silent for `HEARTBEAT_TIMEOUT`.
+    pub async fn connect(
+        token: String,
+        fills: mpsc::UnboundedSender<Fill>,
+        pair_names: HashMap<String, String>,
+        tracker: Arc<Mutex<OrderTracker>>,
+    ) -> Result<KrakenPrivateWs, Box<dyn Error>> {
+        let (stream, _) = connect_async(WS_AUTH_URL).await?;
+        info!("Connected to {}", WS_AUTH_URL);
+
+        let (mut sink, mut source) = stream.split();
+        let (sender, mut outgoing) = mpsc::unbounded_channel::<Message>();
+
+        // Writer: everything sent through `sender` goes out on the socket.
+        tokio::spawn(async move {
+            while let Some(msg) = outgoing.recv().await {
+                if let Err(e) = sink.send(msg).await {
+                    warn!("Private websocket send failed: {}", e);
+                    break;
+                }
+            }
+        });
+
+        let pending: PendingRequests = Arc::new(Mutex::new(HashMap::new()));
+
+        // Reader: dispatch fills, order updates and request replies.
+        let reader_pending = pending.clone();
+        tokio::spawn(async move {
+            loop {
+                let msg = match timeout(HEARTBEAT_TIMEOUT, source.next()).await {
+                    Ok(Some(msg)) => msg,
+                    Ok(None) => break,
+                    Err(_) => {
+                        warn!("No heartbeat on the private websocket, dropping it");
+                        break;
+                    }
+                };
+                let text = match msg {
+                    Ok(Message::Text(text)) => text,
+                    Ok(Message::Close(frame)) => {
+                        warn!("Private websocket closed: {:?}", frame);
+                        break;
+                    }
+                    Ok(_) => continue,
+                    Err(e) => {
+                        warn!("Private websocket error: {}", e);
+                        break;
+                    }
+                };
+
+                match parse_private_message(&text, &pair_names) {
+                    Ok(PrivateMessage::OwnTrades(trades)) => {
+                        let trades = tracker.lock().unwrap().new_fills(trades);
+                        for fill in trades {
+                            info!("Fill received: {:?}", fill);
+                            if fills.send(fill).is_err() {
+                                debug!("Fill receiver dropped");
+                            }
+                        }
+                    }
+                    Ok(PrivateMessage::OpenOrders { orders, snapshot }) => {
+                        tracker.lock().unwrap().on_open_orders(orders, snapshot);
+                    }
+                    Ok(PrivateMessage::OrderStatus { reqid, txid, error }) => {
+                        let reply = reader_pending.lock().unwrap().remove(&reqid);
+                        if let Some(reply) = reply {
+                            let result = match error {
+                                Some(error) => Err(KrakenError::new(None, vec![error])),
+                                None => Ok(txid),
+                            };
+                            reply.send(result).ok();
+                        }
+                    }
+                    Ok(PrivateMessage::Other(value)) => trace!("Private websocket: {}", value),
+                    Err(e) => warn!("Unable to parse private websocket message {}: {}", text, e),
+                }
+            }
+
+            // Fail whoever is still waiting for a reply.
+            reader_pending.lock().unwrap().clear();
+        });
+
+        let ws = KrakenPrivateWs {
+            token,
+            sender,
+            pending,
+            next_reqid: AtomicU64::new(1),
+        };
+
+        ws.subscribe("ownTrades")?;
+        ws.subscribe("openOrders")?;
+
+        Ok(ws)
+    }
+
+    /// Place an order on the socket. `params` takes the same fields as the REST `AddOrder` call.
+    /// Returns the order txid.
+    pub async fn add_order(
+        &self,
+        params: &HashMap<String, String>,
+    ) -> Result<String, Box<dyn Error>> {
+        let mut request = Map::new();
+        for (k, v) in params {
+            request.insert(k.to_owned(), Value::String(v.to_owned()));
+        }
+        request.insert("event".to_owned(), json!("addOrder"));
+
+        self.request(request).await?.ok_or_else(|| {
+            KrakenError::new(None, vec!["addOrder returned no txid".to_owned()]).into()
+        })
+    }
+
+    /// Cancel one or more orders by txid.
+    pub async fn cancel_order(&self, txids: &[String]) -> Result<(), Box<dyn Error>> {
+        let mut request = Map::new();
+        request.insert("event".to_owned(), json!("cancelOrder"));
+        request.insert("txid".to_owned(), json!(txids));
+
+        self.request(request).await?;
+        Ok(())
+    }
+
+    fn subscribe(&self, name: &str) -> Result<(), Box<dyn Error>> {
+        let msg = json!({
+            "event": "subscribe",
+            "subscription": {
+                "name": name,
+                "token": self.token,
+                "snapshot": true,
+            }
+        });
+        self.sender.send(Message::Text(msg.to_string()))?;
+        Ok(())
+    }
+
+    async fn request(
+        &self,
+        mut request: Map<String, Value>,
+    ) -> Result<Option<String>, Box<dyn Error>> {
+        let reqid = self.next_reqid.fetch_add(1, Ordering::SeqCst);
+        request.insert("token".to_owned(), json!(self.token));
+        request.insert("reqid".to_owned(), json!(reqid));
+
+        let (reply, response) = oneshot::channel();
+        self.pending.lock().unwrap().insert(reqid, reply);
+
+        trace!("Private websocket request: {:?}", request);
+        self.sender
+            .send(Message::Text(Value::Object(request).to_string()))?;
+
+        match timeout(REQUEST_TIMEOUT, response).await {
+            Ok(Ok(result)) => Ok(result?),
+            Ok(Err(_)) => Err(Box::new(KrakenError::new(
+                None,
+                vec!["Private websocket closed before reply".to_owned()],
+            ))),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&reqid);
+                Err(Box::new(KrakenError::new(
+                    None,
+                    vec![format!(
+                        "No reply to request {} on the private websocket",
+                        reqid
+                    )],
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tradingbot::order::OrderSide;
+    use chrono::Utc;
+
+    fn fill(trade_id: &str) -> Fill {
+        Fill {
+            trade_id: trade_id.to_owned(),
+            order_id: "O1".to_owned(),
+            pair: "XXBTZUSD".to_owned(),
+            side: OrderSide::Buy,
+            price: 100.0,
+            volume: 1.0,
+            fee: 0.0,
+            time: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_fills_are_not_repeated() {
+        let mut tracker = OrderTracker::default();
+        assert_eq!(tracker.new_fills(vec![fill("T1"), fill("T2")]).len(), 2);
+        let fills = tracker.new_fills(vec![fill("T1"), fill("T2"), fill("T3")]);
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].trade_id, "T3");
+    }
+
+    #[test]
+    fn test_closed_orders() {
+        let status = |order_id: &str, status: &str| (order_id.to_owned(), status.to_owned());
+        let mut tracker = OrderTracker::default();
+        tracker.open.insert("FILLED".to_owned());
+        tracker.on_open_orders(
+            vec![
+                status("A", "pending"),
+                status("B", "open"),
+                status("C", "open"),
+            ],
+            false,
+        );
+        tracker.on_open_orders(vec![status("A", "canceled"), status("B", "closed")], false);
+        assert_eq!(tracker.closed, vec!["A".to_owned()]);
+
+        // Reconnected: FILLED and C closed meanwhile, D is new.
+        tracker.on_open_orders(vec![status("D", "open")], true);
+        tracker.closed.sort();
+        assert_eq!(tracker.closed, vec!["A", "C", "FILLED"]);
+        assert_eq!(tracker.open.iter().collect::<Vec<_>>(), vec!["D"]);
+    }
+}
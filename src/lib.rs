@@ -1,5 +1,16 @@
+pub mod arbitrage;
+pub mod backtest;
+pub mod binance;
+pub mod indicators;
 pub mod kraken;
+pub mod marketdata;
+pub mod paper;
+pub mod settings;
+pub mod strategies;
+pub mod supervisor;
 pub mod tradingbot;
 
+pub use binance::Binance;
 pub use kraken::Kraken;
+pub use paper::PaperMarket;
 pub use tradingbot::{TradingBot, TradingConfig};
@@ -0,0 +1,120 @@
+use super::{
+    timeframe::Timeframe,
+    trade::{aggregate_trades, Trade},
+};
+use crate::kraken::Kraken;
+use crate::tradingbot::{candle::Candle, config::ConfigError};
+use chrono::{DateTime, Utc};
+use log::info;
+use std::error::Error;
+use tokio::time;
+
+/// Candle intervals in minutes served by Kraken's `OHLC` endpoint.
+pub const OHLC_INTERVALS: [u32; 9] = [1, 5, 15, 30, 60, 240, 1440, 10080, 21600];
+
+/// Pause between paginated public requests, to stay within Kraken's rate limit.
+const PAGE_DELAY_MS: u64 = 1000;
+
+/// Closed candles of `pair` opened at or after `since`, oldest first.
+///
+/// Kraken's `OHLC` endpoint only serves the 720 most recent candles, anything older
+/// is rebuilt from the `Trades` history.
+pub async fn download_candles(
+    kraken: &Kraken,
+    pair: &str,
+    interval: u32,
+    since: DateTime<Utc>,
+) -> Result<Vec<Candle>, Box<dyn Error>> {
+    if !OHLC_INTERVALS.contains(&interval) {
+        return Err(Box::new(ConfigError(format!(
+            "interval must be one of {:?} minutes, got {}",
+            OHLC_INTERVALS, interval
+        ))));
+    }
+
+    let mut candles = download_ohlc(kraken, pair, interval, since).await?;
+
+    let ohlc_start = candles.first().map(|c| c.time).unwrap_or_else(Utc::now);
+    if since < ohlc_start {
+        info!(
+            "[CANDLES] rebuilding {} to {} from trades",
+            since, ohlc_start
+        );
+        let timeframe = Timeframe::from_minutes(interval).ok_or_else(|| {
+            ConfigError(format!(
+                "candles of {} minutes cannot be rebuilt from trades",
+                interval
+            ))
+        })?;
+        let trades = download_trades(kraken, pair, since, ohlc_start).await?;
+        let mut older = aggregate_trades(&trades, timeframe);
+        // The last bucket may continue into the OHLC data.
+        older.retain(|c| c.time < ohlc_start);
+        older.append(&mut candles);
+        candles = older;
+    }
+
+    candles.retain(|c| c.time >= since);
+    Ok(candles)
+}
+
+/// Page through `OHLC` with its `last` cursor until no closed candle is left.
+async fn download_ohlc(
+    kraken: &Kraken,
+    pair: &str,
+    interval: u32,
+    since: DateTime<Utc>,
+) -> Result<Vec<Candle>, Box<dyn Error>> {
+    let mut candles: Vec<Candle> = Vec::new();
+    let mut cursor = since.timestamp();
+    loop {
+        let (mut page, last) = kraken.get_ohlc_since(pair, interval, Some(cursor)).await?;
+        // The last entry is the candle still in progress.
+        page.pop();
+        let newest = candles.last().map(|c| c.time);
+        page.retain(|c| newest.is_none_or(|newest| c.time > newest));
+        if page.is_empty() || last <= cursor {
+            break;
+        }
+
+        info!(
+            "[CANDLES] {} OHLC candles up to {}",
+            page.len(),
+            page[page.len() - 1].time
+        );
+        candles.append(&mut page);
+        cursor = last;
+        time::sleep(time::Duration::from_millis(PAGE_DELAY_MS)).await;
+    }
+    Ok(candles)
+}
+
+/// Public trades of `pair` between `since` and `until`, oldest first.
+async fn download_trades(
+    kraken: &Kraken,
+    pair: &str,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Result<Vec<Trade>, Box<dyn Error>> {
+    let mut trades = Vec::new();
+    let mut cursor = (since.timestamp() * 1_000_000_000).to_string();
+    loop {
+        let (page, last) = kraken.get_trades(pair, Some(&cursor)).await?;
+        let done = page.is_empty() || last == cursor;
+        for trade in page {
+            if trade.time >= until {
+                return Ok(trades);
+            }
+            trades.push(trade);
+        }
+        if done {
+            return Ok(trades);
+        }
+
+        if let Some(trade) = trades.last() {
+            info!("[CANDLES] {} trades up to {}", trades.len(), trade.time);
+        }
+        cursor = last;
+        time::sleep(time::Duration::from_millis(PAGE_DELAY_MS)).await;
+    }
+}
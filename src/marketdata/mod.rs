@@ -0,0 +1,11 @@
+pub mod downloader;
+pub mod resampler;
+pub mod store;
+pub mod timeframe;
+pub mod trade;
+
+pub use downloader::download_candles;
+pub use resampler::Resampler;
+pub use store::CandleStore;
+pub use timeframe::Timeframe;
+pub use trade::{aggregate_trades, Trade};
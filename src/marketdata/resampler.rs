@@ -0,0 +1,192 @@
+use super::{timeframe::Timeframe, trade::Trade};
+use crate::tradingbot::candle::Candle;
+use chrono::{DateTime, Utc};
+
+/// Builds candles of `timeframe` from closed lower-timeframe candles or from trades.
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    pub timeframe: Timeframe,
+    /// Candle being built, not closed yet.
+    current: Option<Candle>,
+}
+
+impl Resampler {
+    pub fn new(timeframe: Timeframe) -> Self {
+        Resampler {
+            timeframe,
+            current: None,
+        }
+    }
+
+    /// The in-progress candle, if any.
+    pub fn current(&self) -> Option<&Candle> {
+        self.current.as_ref()
+    }
+
+    /// Add a closed candle of `source`, a timeframe `self.timeframe` is a multiple of.
+    /// Returns the candles closed by it: the previous one when `candle` opens a new period,
+    /// the current one when `candle` is the last of its period.
+    pub fn push_candle(&mut self, candle: &Candle, source: Timeframe) -> Vec<Candle> {
+        let mut closed: Vec<Candle> = self.merge(candle).into_iter().collect();
+
+        let end = self.timeframe.start_of(candle.time) + self.timeframe.duration();
+        if candle.time + source.duration() >= end {
+            closed.extend(self.current.take().map(finish));
+        }
+        closed
+    }
+
+    /// Add a trade, returning the previous candle once a trade of the next period arrives.
+    /// Use `close_until` to close a period without waiting for the next trade.
+    pub fn push_trade(&mut self, trade: &Trade) -> Option<Candle> {
+        self.merge(&Candle {
+            time: trade.time,
+            open: trade.price,
+            high: trade.price,
+            low: trade.price,
+            close: trade.price,
+            vwap: trade.price,
+            volume: trade.volume,
+            count: 1,
+        })
+    }
+
+    /// Close the in-progress candle if its period ended by `now`.
+    pub fn close_until(&mut self, now: DateTime<Utc>) -> Option<Candle> {
+        match &self.current {
+            Some(current) if current.time + self.timeframe.duration() <= now => {
+                self.current.take().map(finish)
+            }
+            _ => None,
+        }
+    }
+
+    /// Close the in-progress candle whether its period ended or not, e.g. at the end of input.
+    pub fn flush(&mut self) -> Option<Candle> {
+        self.current.take().map(finish)
+    }
+
+    /// Fold `candle` into its period. The finished previous candle is returned when
+    /// `candle` belongs to a later period. Out of order input is ignored.
+    fn merge(&mut self, candle: &Candle) -> Option<Candle> {
+        let start = self.timeframe.start_of(candle.time);
+        match &mut self.current {
+            Some(current) if current.time == start => {
+                current.high = current.high.max(candle.high);
+                current.low = current.low.min(candle.low);
+                current.close = candle.close;
+                // vwap holds the traded value until the candle is finished
+                current.vwap += candle.vwap * candle.volume;
+                current.volume += candle.volume;
+                current.count += candle.count;
+                None
+            }
+            Some(current) if current.time > start => None,
+            _ => {
+                let previous = self.current.take().map(finish);
+                self.current = Some(Candle {
+                    time: start,
+                    vwap: candle.vwap * candle.volume,
+                    ..candle.clone()
+                });
+                previous
+            }
+        }
+    }
+}
+
+/// Turn the accumulated traded value back into a vwap.
+fn finish(mut candle: Candle) -> Candle {
+    candle.vwap = if candle.volume > 0.0 {
+        candle.vwap / candle.volume
+    } else {
+        candle.close
+    };
+    candle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn candle(hour: u32, close: f32, volume: f32) -> Candle {
+        Candle {
+            time: Utc.ymd(2020, 12, 30).and_hms(hour, 0, 0),
+            open: close - 1.0,
+            high: close + 1.0,
+            low: close - 2.0,
+            close,
+            vwap: close,
+            volume,
+            count: 1,
+        }
+    }
+
+    #[test]
+    fn test_resample_candles() {
+        let mut resampler = Resampler::new(Timeframe::H4);
+
+        // 02:00 and 03:00 belong to the 00:00 candle, which has no 00:00 or 01:00 data.
+        assert!(resampler
+            .push_candle(&candle(2, 10.0, 1.0), Timeframe::H1)
+            .is_empty());
+        let closed = resampler.push_candle(&candle(3, 12.0, 3.0), Timeframe::H1);
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].time, Utc.ymd(2020, 12, 30).and_hms(0, 0, 0));
+        assert_eq!(
+            (
+                closed[0].open,
+                closed[0].high,
+                closed[0].low,
+                closed[0].close
+            ),
+            (9.0, 13.0, 8.0, 12.0)
+        );
+        assert_eq!(closed[0].vwap, 11.5);
+        assert_eq!(closed[0].volume, 4.0);
+        assert_eq!(closed[0].count, 2);
+
+        // A gap: 05:00 is in progress until 09:00 opens the next period.
+        assert!(resampler
+            .push_candle(&candle(5, 20.0, 1.0), Timeframe::H1)
+            .is_empty());
+        assert_eq!(resampler.current().unwrap().close, 20.0);
+        let closed = resampler.push_candle(&candle(9, 30.0, 1.0), Timeframe::H1);
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].time, Utc.ymd(2020, 12, 30).and_hms(4, 0, 0));
+        assert_eq!(
+            resampler.current().unwrap().time,
+            Utc.ymd(2020, 12, 30).and_hms(8, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_resample_trades() {
+        let trade = |minute: u32, price: f32| Trade {
+            time: Utc.ymd(2020, 12, 30).and_hms(10, minute, 0),
+            price,
+            volume: 1.0,
+        };
+        let mut resampler = Resampler::new(Timeframe::M5);
+
+        assert_eq!(resampler.push_trade(&trade(1, 100.0)), None);
+        assert_eq!(resampler.push_trade(&trade(3, 102.0)), None);
+        let closed = resampler.push_trade(&trade(6, 99.0)).unwrap();
+        assert_eq!(closed.time, Utc.ymd(2020, 12, 30).and_hms(10, 0, 0));
+        assert_eq!(
+            (closed.open, closed.close, closed.vwap),
+            (100.0, 102.0, 101.0)
+        );
+
+        assert_eq!(
+            resampler.close_until(Utc.ymd(2020, 12, 30).and_hms(10, 9, 59)),
+            None
+        );
+        let closed = resampler
+            .close_until(Utc.ymd(2020, 12, 30).and_hms(10, 10, 0))
+            .unwrap();
+        assert_eq!(closed.close, 99.0);
+        assert!(resampler.current().is_none());
+    }
+}
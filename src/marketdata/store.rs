@@ -0,0 +1,125 @@
+use crate::backtest::data::{parse_csv, to_csv_line, CSV_HEADER};
+use crate::tradingbot::candle::Candle;
+use chrono::{DateTime, Duration, Utc};
+use std::{
+    error::Error,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Missing candles from the first to the last open time, inclusive.
+pub type Gap = (DateTime<Utc>, DateTime<Utc>);
+
+/// Append-only CSV file of the candles of one pair and interval, oldest first.
+/// The file is readable by `backtest --candles`.
+pub struct CandleStore {
+    pub path: PathBuf,
+    pub interval: u32,
+}
+
+impl CandleStore {
+    /// Store `<dir>/<pair>_<interval>.csv`.
+    pub fn open(dir: &Path, pair: &str, interval: u32) -> Result<Self, Box<dyn Error>> {
+        fs::create_dir_all(dir)?;
+        Ok(CandleStore {
+            path: dir.join(format!("{}_{}.csv", pair, interval)),
+            interval,
+        })
+    }
+
+    pub fn load(&self) -> Result<Vec<Candle>, Box<dyn Error>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        parse_csv(&fs::read_to_string(&self.path)?)
+    }
+
+    /// Open time of the newest stored candle.
+    pub fn last_time(&self) -> Result<Option<DateTime<Utc>>, Box<dyn Error>> {
+        Ok(self.load()?.last().map(|c| c.time))
+    }
+
+    /// Append the candles newer than the stored ones, returning how many were written.
+    pub fn append(&self, candles: &[Candle]) -> Result<usize, Box<dyn Error>> {
+        let last = self.last_time()?;
+        let new: Vec<&Candle> = candles
+            .iter()
+            .filter(|c| last.is_none_or(|last| c.time > last))
+            .collect();
+        if new.is_empty() {
+            return Ok(0);
+        }
+
+        let exists = self.path.exists();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        if !exists {
+            writeln!(file, "{}", CSV_HEADER)?;
+        }
+        for candle in &new {
+            writeln!(file, "{}", to_csv_line(candle))?;
+        }
+        Ok(new.len())
+    }
+
+    /// Missing candle ranges in the stored history.
+    pub fn gaps(&self) -> Result<Vec<Gap>, Box<dyn Error>> {
+        Ok(find_gaps(&self.load()?, self.interval))
+    }
+}
+
+/// Missing candle ranges in time ordered `candles` of `interval` minutes.
+pub fn find_gaps(candles: &[Candle], interval: u32) -> Vec<Gap> {
+    let step = Duration::minutes(i64::from(interval));
+    candles
+        .windows(2)
+        .filter(|pair| pair[1].time - pair[0].time > step)
+        .map(|pair| (pair[0].time + step, pair[1].time - step))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn candle(time: i64) -> Candle {
+        Candle {
+            time: Utc.timestamp(time, 0),
+            open: 1.0,
+            high: 1.0,
+            low: 1.0,
+            close: 1.0,
+            vwap: 1.0,
+            volume: 1.0,
+            count: 1,
+        }
+    }
+
+    #[test]
+    fn test_append_and_gaps() {
+        let dir = std::env::temp_dir().join(format!("trade_bot_store_{}", std::process::id()));
+        let store = CandleStore::open(&dir, "XXBTZUSD", 60).unwrap();
+
+        assert_eq!(store.append(&[candle(0), candle(3600)]).unwrap(), 2);
+        // Only candles newer than the stored ones are appended.
+        assert_eq!(
+            store
+                .append(&[candle(3600), candle(7200), candle(18000)])
+                .unwrap(),
+            2
+        );
+
+        let candles = store.load().unwrap();
+        assert_eq!(candles.len(), 4);
+        assert_eq!(
+            store.gaps().unwrap(),
+            vec![(Utc.timestamp(10800, 0), Utc.timestamp(14400, 0))]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
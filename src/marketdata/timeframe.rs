@@ -0,0 +1,152 @@
+use crate::tradingbot::config::ConfigError;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde_derive::Deserialize;
+use std::{fmt, str::FromStr};
+
+/// Seconds from the unix epoch, a Thursday, to the first Monday.
+const FIRST_MONDAY: i64 = 4 * 86_400;
+
+/// Candle period, written "5m", "4h", "1d" or "1w".
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(try_from = "String")]
+pub enum Timeframe {
+    M1,
+    M5,
+    M15,
+    M30,
+    H1,
+    H4,
+    D1,
+    W1,
+}
+
+impl Timeframe {
+    pub const ALL: [Timeframe; 8] = [
+        Timeframe::M1,
+        Timeframe::M5,
+        Timeframe::M15,
+        Timeframe::M30,
+        Timeframe::H1,
+        Timeframe::H4,
+        Timeframe::D1,
+        Timeframe::W1,
+    ];
+
+    pub fn minutes(self) -> u32 {
+        match self {
+            Timeframe::M1 => 1,
+            Timeframe::M5 => 5,
+            Timeframe::M15 => 15,
+            Timeframe::M30 => 30,
+            Timeframe::H1 => 60,
+            Timeframe::H4 => 240,
+            Timeframe::D1 => 1440,
+            Timeframe::W1 => 10080,
+        }
+    }
+
+    pub fn from_minutes(minutes: u32) -> Option<Timeframe> {
+        Timeframe::ALL
+            .iter()
+            .copied()
+            .find(|t| t.minutes() == minutes)
+    }
+
+    pub fn duration(self) -> Duration {
+        Duration::minutes(i64::from(self.minutes()))
+    }
+
+    /// Whether candles of `self` are made of whole candles of `lower`.
+    pub fn is_multiple_of(self, lower: Timeframe) -> bool {
+        self.minutes().is_multiple_of(lower.minutes())
+    }
+
+    /// Open time of the candle containing `time`. Days start at 00:00 UTC, weeks on Monday.
+    pub fn start_of(self, time: DateTime<Utc>) -> DateTime<Utc> {
+        let seconds = i64::from(self.minutes()) * 60;
+        let offset = match self {
+            Timeframe::W1 => FIRST_MONDAY,
+            _ => 0,
+        };
+        let start = (time.timestamp() - offset).div_euclid(seconds) * seconds + offset;
+        Utc.timestamp(start, 0)
+    }
+}
+
+impl FromStr for Timeframe {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Timeframe::ALL
+            .iter()
+            .copied()
+            .find(|t| t.to_string() == s)
+            .ok_or_else(|| {
+                ConfigError(format!(
+                    "unknown timeframe {}, expected 1m, 5m, 15m, 30m, 1h, 4h, 1d or 1w",
+                    s
+                ))
+            })
+    }
+}
+
+impl std::convert::TryFrom<String> for Timeframe {
+    type Error = ConfigError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for Timeframe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Timeframe::M1 => "1m",
+            Timeframe::M5 => "5m",
+            Timeframe::M15 => "15m",
+            Timeframe::M30 => "30m",
+            Timeframe::H1 => "1h",
+            Timeframe::H4 => "4h",
+            Timeframe::D1 => "1d",
+            Timeframe::W1 => "1w",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_of() {
+        // Wednesday 2020-12-30 13:47:12 UTC
+        let time = Utc.ymd(2020, 12, 30).and_hms(13, 47, 12);
+
+        assert_eq!(
+            Timeframe::M15.start_of(time),
+            Utc.ymd(2020, 12, 30).and_hms(13, 45, 0)
+        );
+        assert_eq!(
+            Timeframe::H4.start_of(time),
+            Utc.ymd(2020, 12, 30).and_hms(12, 0, 0)
+        );
+        assert_eq!(
+            Timeframe::D1.start_of(time),
+            Utc.ymd(2020, 12, 30).and_hms(0, 0, 0)
+        );
+        assert_eq!(
+            Timeframe::W1.start_of(time),
+            Utc.ymd(2020, 12, 28).and_hms(0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("4h".parse::<Timeframe>().unwrap(), Timeframe::H4);
+        assert_eq!(Timeframe::from_minutes(10080), Some(Timeframe::W1));
+        assert!("2h".parse::<Timeframe>().is_err());
+        assert!(Timeframe::D1.is_multiple_of(Timeframe::H4));
+        assert!(!Timeframe::H1.is_multiple_of(Timeframe::H4));
+    }
+}
@@ -0,0 +1,65 @@
+use super::{resampler::Resampler, timeframe::Timeframe};
+use crate::tradingbot::candle::Candle;
+use chrono::{DateTime, Utc};
+
+/// Public trade of a pair.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trade {
+    pub time: DateTime<Utc>,
+    pub price: f32,
+    pub volume: f32,
+}
+
+/// Group time ordered `trades` into candles of `timeframe`.
+/// Periods without trades produce no candle.
+pub fn aggregate_trades(trades: &[Trade], timeframe: Timeframe) -> Vec<Candle> {
+    let mut resampler = Resampler::new(timeframe);
+    let mut candles: Vec<Candle> = trades
+        .iter()
+        .filter_map(|trade| resampler.push_trade(trade))
+        .collect();
+    candles.extend(resampler.flush());
+    candles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn trade(time: i64, price: f32, volume: f32) -> Trade {
+        Trade {
+            time: Utc.timestamp(time, 0),
+            price,
+            volume,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_trades() {
+        let trades = vec![
+            trade(3600, 100.0, 1.0),
+            trade(3700, 104.0, 1.0),
+            trade(3800, 98.0, 2.0),
+            trade(7300, 101.0, 0.5),
+        ];
+        let candles = aggregate_trades(&trades, Timeframe::H1);
+
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[0].time.timestamp(), 3600);
+        assert_eq!(
+            (
+                candles[0].open,
+                candles[0].high,
+                candles[0].low,
+                candles[0].close
+            ),
+            (100.0, 104.0, 98.0, 98.0)
+        );
+        assert_eq!(candles[0].vwap, 100.0);
+        assert_eq!(candles[0].volume, 4.0);
+        assert_eq!(candles[0].count, 3);
+        assert_eq!(candles[1].time.timestamp(), 7200);
+        assert_eq!(candles[1].vwap, 101.0);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod paper_market;
+pub mod paper_market_impl;
+
+pub use paper_market::PaperMarket;
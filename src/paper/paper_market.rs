@@ -0,0 +1,37 @@
+use crate::backtest::FeeModel;
+use crate::tradingbot::{
+    market::Market,
+    order::{Fill, OrderRequest},
+};
+use std::sync::Mutex;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Order resting on the paper market, price resolved when placed.
+#[derive(Debug, Clone)]
+pub struct PaperOrder {
+    pub id: String,
+    pub request: OrderRequest,
+    pub price: Option<f32>,
+}
+
+/// Virtual account of the paper market.
+pub struct PaperState {
+    pub quote: f32,
+    pub base: f32,
+    pub orders: Vec<PaperOrder>,
+    /// Resting orders cancelled for lack of funds, until taken by `take_closed_orders`.
+    pub closed: Vec<String>,
+    pub trades: Vec<Fill>,
+    pub fills: Option<UnboundedSender<Fill>>,
+    pub next_id: u64,
+}
+
+/// Market reading prices and order books from a live `Market`
+/// while filling orders locally against its book with a virtual balance.
+pub struct PaperMarket {
+    pub market: Box<dyn Market>,
+    pub fees: FeeModel,
+    /// Levels of the book fetched to fill orders.
+    pub depth: u32,
+    pub state: Mutex<PaperState>,
+}
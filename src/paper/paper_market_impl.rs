@@ -0,0 +1,441 @@
+/// Paper market implementation
+use super::paper_market::{PaperMarket, PaperOrder, PaperState};
+use crate::backtest::FeeModel;
+use crate::tradingbot::{
+    candle::Candle,
+    market::Market,
+    order::{Fill, InvalidOrder, OpenOrder, OrderFlag, OrderRequest, OrderSide, OrderType},
+    order_book::OrderBook,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use std::error::Error;
+use std::sync::Mutex;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+/// Book levels fetched on each side, the most Kraken's `Depth` endpoint returns is 500.
+const DEFAULT_DEPTH: u32 = 100;
+
+impl PaperMarket {
+    pub fn new(market: Box<dyn Market>, initial_balance: f32, fees: FeeModel) -> Self {
+        PaperMarket {
+            market,
+            fees,
+            depth: DEFAULT_DEPTH,
+            state: Mutex::new(PaperState {
+                quote: initial_balance,
+                base: 0.0,
+                orders: Vec::new(),
+                closed: Vec::new(),
+                trades: Vec::new(),
+                fills: None,
+                next_id: 1,
+            }),
+        }
+    }
+
+    /// Fill resting orders of `pair` the current book reaches.
+    async fn match_orders(&self, pair: &str) -> Result<(), Box<dyn Error>> {
+        if !self
+            .state
+            .lock()
+            .unwrap()
+            .orders
+            .iter()
+            .any(|o| o.request.pair == pair)
+        {
+            return Ok(());
+        }
+        let book = self.market.get_order_book(pair, self.depth).await?;
+        let time = self.market.time();
+
+        let mut state = self.state.lock().unwrap();
+        let orders: Vec<PaperOrder> = state.orders.drain(..).collect();
+        for order in orders {
+            if order.request.pair != pair {
+                state.orders.push(order);
+                continue;
+            }
+            match match_order(&order, &book, false) {
+                Some((price, maker)) => {
+                    if let Err(e) = state.execute(&order, price, self.fee(maker), time) {
+                        warn!("[PAPER] {} cancelled: {}", order.id, e);
+                        state.closed.push(order.id);
+                    }
+                }
+                None => state.orders.push(order),
+            }
+        }
+        Ok(())
+    }
+
+    fn fee(&self, maker: bool) -> f32 {
+        if maker {
+            self.fees.maker_pct
+        } else {
+            self.fees.taker_pct
+        }
+    }
+}
+
+impl PaperState {
+    fn next_id(&mut self) -> String {
+        let id = format!("PAPER-{}", self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    /// Settle `order` at `price`, failing when the virtual balance cannot cover it.
+    fn execute(
+        &mut self,
+        order: &PaperOrder,
+        price: f32,
+        fee_pct: f32,
+        time: DateTime<Utc>,
+    ) -> Result<(), InvalidOrder> {
+        let volume = order.request.volume;
+        let cost = price * volume;
+        let fee = cost * fee_pct / 100.0;
+
+        match order.request.side {
+            OrderSide::Buy => {
+                if cost + fee > self.quote {
+                    return Err(InvalidOrder(format!(
+                        "{} quote needed, {} available",
+                        cost + fee,
+                        self.quote
+                    )));
+                }
+                self.quote -= cost + fee;
+                self.base += volume;
+            }
+            OrderSide::Sell => {
+                // Short selling needs margin.
+                if volume > self.base + f32::EPSILON && order.request.leverage.is_none() {
+                    return Err(InvalidOrder(format!(
+                        "{} base needed, {} available",
+                        volume, self.base
+                    )));
+                }
+                self.quote += cost - fee;
+                self.base -= volume;
+            }
+        }
+
+        let fill = Fill {
+            trade_id: format!("PAPERT-{}", self.trades.len() + 1),
+            order_id: order.id.clone(),
+            pair: order.request.pair.clone(),
+            side: order.request.side,
+            price,
+            volume,
+            fee,
+            time,
+        };
+        info!("[PAPER] fill {:?}", fill);
+        if let Some(fills) = &self.fills {
+            fills.send(fill.clone()).ok();
+        }
+        self.trades.push(fill);
+
+        // The conditional close goes live once its entry is filled.
+        if let Some(close) = &order.request.close {
+            let side = match order.request.side {
+                OrderSide::Buy => OrderSide::Sell,
+                OrderSide::Sell => OrderSide::Buy,
+            };
+            let request = OrderRequest {
+                order_type: close.order_type,
+                price: Some(close.price),
+                price2: close.price2,
+                leverage: order.request.leverage,
+                ..OrderRequest::market(side, &order.request.pair, volume)
+            };
+            let id = self.next_id();
+            self.orders.push(PaperOrder {
+                id,
+                price: Some(close.price.resolve(price)),
+                request,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Fill price and whether the order provided liquidity, if `book` executes it.
+/// `placing` is true on submission, when a crossing limit order takes liquidity.
+fn match_order(order: &PaperOrder, book: &OrderBook, placing: bool) -> Option<(f32, bool)> {
+    let side = order.request.side;
+    let volume = order.request.volume;
+    let take = || {
+        book.fill_price(side, volume, None)
+            .map(|price| (price, false))
+    };
+
+    match (order.request.order_type, side, order.price) {
+        (OrderType::Market, _, _) => take(),
+        (OrderType::Limit, _, Some(limit)) if placing => book
+            .fill_price(side, volume, Some(limit))
+            .map(|price| (price, false)),
+        // A resting limit order is filled at its price once the other side reaches it.
+        (OrderType::Limit, OrderSide::Buy, Some(limit)) if book.best_ask()? <= limit => {
+            Some((limit, true))
+        }
+        (OrderType::Limit, OrderSide::Sell, Some(limit)) if book.best_bid()? >= limit => {
+            Some((limit, true))
+        }
+        (OrderType::StopLoss, OrderSide::Sell, Some(stop)) if book.best_bid()? <= stop => take(),
+        (OrderType::StopLoss, OrderSide::Buy, Some(stop)) if book.best_ask()? >= stop => take(),
+        (OrderType::TakeProfit, OrderSide::Sell, Some(target)) if book.best_bid()? >= target => {
+            take()
+        }
+        (OrderType::TakeProfit, OrderSide::Buy, Some(target)) if book.best_ask()? <= target => {
+            take()
+        }
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl Market for PaperMarket {
+    /// Virtual quote currency balance.
+    async fn get_balances(&self) -> Result<f32, Box<dyn Error>> {
+        Ok(self.state.lock().unwrap().quote)
+    }
+
+    async fn get_base_balance(&self, _pair: &str) -> Result<f32, Box<dyn Error>> {
+        Ok(self.state.lock().unwrap().base)
+    }
+
+    /// Resting orders are matched against the live book before the price is read.
+    async fn get_market_price(&self, pair: &str) -> Result<f32, Box<dyn Error>> {
+        self.match_orders(pair).await?;
+        self.market.get_market_price(pair).await
+    }
+
+    async fn get_candles(&self, pair: &str, interval: u32) -> Result<Vec<Candle>, Box<dyn Error>> {
+        self.market.get_candles(pair, interval).await
+    }
+
+    async fn get_order_book(&self, pair: &str, depth: u32) -> Result<OrderBook, Box<dyn Error>> {
+        self.market.get_order_book(pair, depth).await
+    }
+
+    async fn place_order(&self, order: &OrderRequest) -> Result<String, Box<dyn Error>> {
+        order.validate()?;
+        match order.order_type {
+            OrderType::Market | OrderType::Limit | OrderType::StopLoss | OrderType::TakeProfit => {}
+            other => {
+                return Err(Box::new(InvalidOrder(format!(
+                    "{:?} orders are not supported by the paper market",
+                    other
+                ))))
+            }
+        }
+
+        let book = self.market.get_order_book(&order.pair, self.depth).await?;
+        let reference = book
+            .mid_price()
+            .ok_or_else(|| format!("empty order book for {}", order.pair))?;
+        let time = self.market.time();
+
+        let mut state = self.state.lock().unwrap();
+        let paper_order = PaperOrder {
+            id: state.next_id(),
+            request: order.clone(),
+            price: order.price.map(|p| p.resolve(reference)),
+        };
+
+        match match_order(&paper_order, &book, true) {
+            Some((_, false)) if order.flags.contains(&OrderFlag::PostOnly) => {
+                return Err(Box::new(InvalidOrder(format!(
+                    "post-only order would take liquidity: {:?}",
+                    order
+                ))))
+            }
+            Some((price, maker)) => state.execute(&paper_order, price, self.fee(maker), time)?,
+            None if order.order_type == OrderType::Market => {
+                return Err(Box::new(InvalidOrder(format!(
+                    "not enough liquidity in the top {} levels for {} {}",
+                    self.depth, order.volume, order.pair
+                ))))
+            }
+            None => {
+                info!("[PAPER] resting {} {:?}", paper_order.id, order);
+                state.orders.push(paper_order.clone());
+            }
+        }
+        Ok(paper_order.id)
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<(), Box<dyn Error>> {
+        self.state
+            .lock()
+            .unwrap()
+            .orders
+            .retain(|o| o.id != order_id);
+        Ok(())
+    }
+
+    async fn get_open_orders(&self) -> Result<Vec<OpenOrder>, Box<dyn Error>> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .orders
+            .iter()
+            .map(|o| OpenOrder {
+                id: o.id.clone(),
+                pair: o.request.pair.clone(),
+                side: o.request.side,
+                price: o.price.unwrap_or_default(),
+                volume: o.request.volume,
+                volume_executed: 0.0,
+                userref: o.request.userref,
+            })
+            .collect())
+    }
+
+    fn time(&self) -> DateTime<Utc> {
+        self.market.time()
+    }
+
+    async fn subscribe_fills(&self) -> Result<Option<UnboundedReceiver<Fill>>, Box<dyn Error>> {
+        let (fills, receiver) = mpsc::unbounded_channel();
+        self.state.lock().unwrap().fills = Some(fills);
+        Ok(Some(receiver))
+    }
+
+    fn take_closed_orders(&self) -> Vec<String> {
+        self.state.lock().unwrap().closed.drain(..).collect()
+    }
+
+    async fn subscribe_book(&self, pair: &str, depth: u32) -> Result<(), Box<dyn Error>> {
+        self.market
+            .subscribe_book(pair, depth.max(self.depth))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Live market stand-in serving whatever book the test sets.
+    struct BookMarket(Arc<Mutex<OrderBook>>);
+
+    #[async_trait]
+    impl Market for BookMarket {
+        async fn get_balances(&self) -> Result<f32, Box<dyn Error>> {
+            Ok(0.0)
+        }
+
+        async fn get_base_balance(&self, _pair: &str) -> Result<f32, Box<dyn Error>> {
+            Ok(0.0)
+        }
+
+        async fn get_market_price(&self, _pair: &str) -> Result<f32, Box<dyn Error>> {
+            Ok(self.0.lock().unwrap().mid_price().unwrap())
+        }
+
+        async fn get_candles(
+            &self,
+            _pair: &str,
+            _interval: u32,
+        ) -> Result<Vec<Candle>, Box<dyn Error>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_order_book(
+            &self,
+            _pair: &str,
+            _depth: u32,
+        ) -> Result<OrderBook, Box<dyn Error>> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+
+        async fn place_order(&self, _order: &OrderRequest) -> Result<String, Box<dyn Error>> {
+            panic!("paper orders must not reach the live market")
+        }
+
+        async fn cancel_order(&self, _order_id: &str) -> Result<(), Box<dyn Error>> {
+            panic!("paper orders must not reach the live market")
+        }
+
+        async fn get_open_orders(&self) -> Result<Vec<OpenOrder>, Box<dyn Error>> {
+            panic!("paper orders must not reach the live market")
+        }
+    }
+
+    fn paper_market() -> (PaperMarket, Arc<Mutex<OrderBook>>) {
+        let book = Arc::new(Mutex::new(OrderBook {
+            asks: vec![(101.0, 1.0), (103.0, 1.0)],
+            bids: vec![(99.0, 1.0), (97.0, 1.0)],
+        }));
+        let fees = FeeModel {
+            maker_pct: 0.0,
+            taker_pct: 1.0,
+        };
+        let market = PaperMarket::new(Box::new(BookMarket(book.clone())), 1000.0, fees);
+        (market, book)
+    }
+
+    #[tokio::test]
+    async fn test_market_order_walks_the_book() {
+        let (market, _) = paper_market();
+        let mut fills = market.subscribe_fills().await.unwrap().unwrap();
+
+        market
+            .place_order(&OrderRequest::market(OrderSide::Buy, "XXBTZUSD", 2.0))
+            .await
+            .unwrap();
+
+        let fill = fills.recv().await.unwrap();
+        assert_eq!(fill.price, 102.0);
+        assert!((fill.fee - 2.04).abs() < 1e-4);
+        assert!((market.get_balances().await.unwrap() - (1000.0 - 204.0 - 2.04)).abs() < 1e-3);
+
+        // Selling more than held needs leverage.
+        let short = OrderRequest::market(OrderSide::Sell, "XXBTZUSD", 3.0);
+        assert!(market.place_order(&short).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resting_limit_order() {
+        let (market, book) = paper_market();
+        let mut fills = market.subscribe_fills().await.unwrap().unwrap();
+
+        let id = market
+            .place_order(&OrderRequest::limit(OrderSide::Buy, "XXBTZUSD", 1.0, 95.0))
+            .await
+            .unwrap();
+        market.get_market_price("XXBTZUSD").await.unwrap();
+        assert_eq!(market.state.lock().unwrap().orders.len(), 1);
+
+        book.lock().unwrap().asks.insert(0, (94.5, 2.0));
+        market.get_market_price("XXBTZUSD").await.unwrap();
+
+        let fill = fills.recv().await.unwrap();
+        assert_eq!(fill.order_id, id);
+        assert_eq!(fill.price, 95.0);
+        assert_eq!(fill.fee, 0.0);
+        assert!(market.state.lock().unwrap().orders.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unfunded_orders_are_closed() {
+        let (market, book) = paper_market();
+        let id = market
+            .place_order(&OrderRequest::limit(OrderSide::Buy, "XXBTZUSD", 20.0, 95.0))
+            .await
+            .unwrap();
+
+        book.lock().unwrap().asks.insert(0, (94.5, 20.0));
+        market.get_market_price("XXBTZUSD").await.unwrap();
+        assert!(market.state.lock().unwrap().orders.is_empty());
+        assert_eq!(market.take_closed_orders(), vec![id]);
+        assert!(market.take_closed_orders().is_empty());
+    }
+}
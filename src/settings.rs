@@ -0,0 +1,187 @@
+use crate::tradingbot::config::{ConfigError, TradingConfig};
+use serde_derive::Deserialize;
+use std::{error::Error, fs, path::Path, str::FromStr};
+
+/// Exchange the bot trades on.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Exchange {
+    #[default]
+    Kraken,
+    Binance,
+}
+
+impl Exchange {
+    /// Taker fee of the base tier, in percent.
+    pub fn taker_fee_pct(self) -> f32 {
+        match self {
+            Exchange::Kraken => 0.26,
+            Exchange::Binance => 0.1,
+        }
+    }
+}
+
+impl FromStr for Exchange {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "kraken" => Ok(Exchange::Kraken),
+            "binance" => Ok(Exchange::Binance),
+            _ => Err(ConfigError(format!("unknown exchange {}", s))),
+        }
+    }
+}
+
+/// Content of the `--config` file: market credentials and trading config.
+///
+/// ```toml
+/// exchange = "kraken"
+/// api_key = "..."
+/// api_secret = "..."
+///
+/// [trading]
+/// pair = "XXBTZUSD"
+/// strategy = "hold"
+///
+/// [trading.strategy_params]
+/// fast = 12
+/// ```
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub exchange: Exchange,
+    pub api_key: Option<String>,
+    pub api_secret: Option<String>,
+    pub trading: TradingConfig,
+    /// Bots run side by side instead of the single `trading` one.
+    pub bots: Vec<BotSettings>,
+}
+
+/// One entry of the `[[bots]]` list. The exchange and credentials default to the top-level
+/// ones, the trading config does not inherit from `[trading]`.
+///
+/// ```toml
+/// [[bots]]
+/// name = "eth-grid"
+///
+/// [bots.trading]
+/// pair = "XETHZUSD"
+/// strategy = "grid"
+/// ```
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct BotSettings {
+    pub name: String,
+    pub exchange: Option<Exchange>,
+    pub api_key: Option<String>,
+    pub api_secret: Option<String>,
+    pub trading: TradingConfig,
+}
+
+impl Settings {
+    /// Load a TOML (`.toml`) or YAML (`.yaml`, `.yml`) settings file.
+    pub fn load(path: &Path) -> Result<Settings, Box<dyn Error>> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| ConfigError(format!("cannot read {}: {}", path.display(), e)))?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Ok(toml::from_str(&content)?),
+            Some("yaml") | Some("yml") => Ok(serde_yaml::from_str(&content)?),
+            _ => Err(Box::new(ConfigError(format!(
+                "unsupported config file {}, expected .toml, .yaml or .yml",
+                path.display()
+            )))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::marketdata::Timeframe;
+    use crate::tradingbot::config::TradingMode;
+
+    #[test]
+    fn test_toml_settings() {
+        let settings: Settings = toml::from_str(
+            r#"
+            exchange = "binance"
+            api_key = "key"
+
+            [trading]
+            pair = "XETHZUSD"
+            mode = "paper"
+            take_profit_pct = 2.5
+            timeframe = "1h"
+
+            [trading.strategy_params]
+            fast = 12
+            kind = "ema"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(settings.exchange, Exchange::Binance);
+        assert_eq!(settings.api_key.as_deref(), Some("key"));
+        assert!(settings.api_secret.is_none());
+        assert_eq!(settings.trading.pair, "XETHZUSD");
+        assert_eq!(settings.trading.mode, TradingMode::Paper);
+        assert_eq!(settings.trading.take_profit_pct, 2.5);
+        assert_eq!(settings.trading.timeframe, Timeframe::H1);
+        assert_eq!(settings.trading.param("fast", 0u32).unwrap(), 12);
+        assert_eq!(
+            settings.trading.param("kind", String::new()).unwrap(),
+            "ema"
+        );
+        // Not in the file, keeps the default.
+        assert_eq!(settings.trading.trading_cadence, 10);
+    }
+
+    #[test]
+    fn test_bots_settings() {
+        let settings: Settings = toml::from_str(
+            r#"
+            api_key = "key"
+
+            [[bots]]
+            name = "btc"
+
+            [[bots]]
+            name = "eth"
+            exchange = "binance"
+            api_key = "other"
+
+            [bots.trading]
+            pair = "ETHUSDT"
+            strategy = "grid"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(settings.bots.len(), 2);
+        assert_eq!(settings.bots[0].name, "btc");
+        assert!(settings.bots[0].exchange.is_none());
+        assert_eq!(settings.bots[0].trading.pair, "XXBTZUSD");
+        assert_eq!(settings.bots[1].exchange, Some(Exchange::Binance));
+        assert_eq!(settings.bots[1].api_key.as_deref(), Some("other"));
+        assert_eq!(settings.bots[1].trading.strategy, "grid");
+    }
+
+    #[test]
+    fn test_yaml_settings() {
+        let settings: Settings = serde_yaml::from_str(
+            "
+trading:
+  order_size: 0.002
+  strategy_params:
+    slow: 26
+",
+        )
+        .unwrap();
+
+        assert_eq!(settings.trading.order_size, 0.002);
+        assert_eq!(settings.trading.param("slow", 0u32).unwrap(), 26);
+        assert!(settings.trading.validate().is_ok());
+    }
+}
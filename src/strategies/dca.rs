@@ -0,0 +1,189 @@
+use super::schedule::Schedule;
+use crate::tradingbot::{
+    config::{ConfigError, TradingConfig},
+    order::{Fill, OrderRequest, OrderSide},
+    strategy::{Signal, Strategy, Tick},
+};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+
+/// Share of the quote balance kept aside for the taker fee.
+const FEE_RESERVE_PCT: f32 = 0.5;
+
+/// Dollar-cost averaging: market buys worth `amount` of the quote currency on a cron schedule,
+/// whatever the price. Buys are checked on every cycle, so `trading_cadence` only has to be
+/// shorter than the gap between two scheduled times. The first buy is at the first scheduled
+/// time after start, times missed while the bot was down are skipped.
+///
+/// Params: `schedule` (cron in UTC, default `0 0 * * *`, daily at midnight), `amount`
+/// (default 50). With `dip_pct` set, the amount is multiplied by `dip_multiplier` (default 2)
+/// when the price is `dip_pct` percent or more below the last buy. Buys are capped to the
+/// quote balance and skipped when it cannot cover `amount`.
+pub struct Dca {
+    pair: String,
+    schedule: Schedule,
+    amount: f32,
+    dip_pct: f32,
+    dip_multiplier: f32,
+    next_buy: Option<DateTime<Utc>>,
+    last_buy_price: Option<f32>,
+}
+
+impl Dca {
+    pub fn from_config(config: &TradingConfig) -> Result<Self, ConfigError> {
+        let schedule = config.param("schedule", "0 0 * * *".to_owned())?;
+        let amount = config.param("amount", 50.0f32)?;
+        let dip_pct = config.param("dip_pct", 0.0f32)?;
+        let dip_multiplier = config.param("dip_multiplier", 2.0f32)?;
+
+        if amount <= 0.0 {
+            return Err(ConfigError(format!(
+                "dca amount must be positive, got {}",
+                amount
+            )));
+        }
+        if !(0.0..100.0).contains(&dip_pct) || dip_multiplier < 1.0 {
+            return Err(ConfigError(format!(
+                "dca needs 0 <= dip_pct < 100 and dip_multiplier >= 1, got {} and {}",
+                dip_pct, dip_multiplier
+            )));
+        }
+
+        Ok(Dca {
+            pair: config.pair.clone(),
+            schedule: schedule.parse()?,
+            amount,
+            dip_pct,
+            dip_multiplier,
+            next_buy: None,
+            last_buy_price: None,
+        })
+    }
+
+    /// Quote amount to spend at `price`.
+    fn amount_at(&self, price: f32) -> f32 {
+        match self.last_buy_price {
+            Some(last) if self.dip_pct > 0.0 && price <= last * (1.0 - self.dip_pct / 100.0) => {
+                self.amount * self.dip_multiplier
+            }
+            _ => self.amount,
+        }
+    }
+}
+
+impl Strategy for Dca {
+    fn name(&self) -> &str {
+        "dca"
+    }
+
+    fn on_tick(&mut self, tick: &Tick) -> Vec<Signal> {
+        let due = match self.next_buy {
+            Some(next_buy) if tick.time >= next_buy => true,
+            Some(_) => return Vec::new(),
+            None => false,
+        };
+        self.next_buy = self.schedule.next_after(tick.time);
+        let next_buy = match self.next_buy {
+            Some(next_buy) => next_buy,
+            None => {
+                warn!("[DCA] schedule {:?} never matches", self.schedule);
+                return Vec::new();
+            }
+        };
+        if !due {
+            info!("[DCA] next buy at {}", next_buy);
+            return Vec::new();
+        }
+
+        let available = tick.balance / (1.0 + FEE_RESERVE_PCT / 100.0);
+        if available < self.amount {
+            warn!(
+                "[DCA] balance {} too low to buy {}, next buy at {}",
+                tick.balance, self.amount, next_buy
+            );
+            return Vec::new();
+        }
+        let amount = self.amount_at(tick.price).min(available);
+        info!(
+            "[DCA] buying {} worth at {}, next buy at {}",
+            amount, tick.price, next_buy
+        );
+        self.last_buy_price = Some(tick.price);
+        vec![Signal::Order(OrderRequest::market(
+            OrderSide::Buy,
+            &self.pair,
+            amount / tick.price,
+        ))]
+    }
+
+    fn on_fill(&mut self, fill: &Fill) -> Vec<Signal> {
+        if fill.side == OrderSide::Buy && fill.pair == self.pair {
+            self.last_buy_price = Some(fill.price);
+        }
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn strategy() -> Dca {
+        let mut config = TradingConfig::default();
+        for (name, value) in &[("schedule", "0 9 * * *"), ("dip_pct", "10")] {
+            config
+                .strategy_params
+                .insert(name.to_string(), value.to_string());
+        }
+        Dca::from_config(&config).unwrap()
+    }
+
+    fn tick(hour: u32, minute: u32, price: f32, balance: f32) -> Tick {
+        Tick {
+            time: Utc.ymd(2021, 1, 4).and_hms(hour, minute, 0),
+            price,
+            balance,
+            base_balance: 0.0,
+            book: None,
+        }
+    }
+
+    fn volume(signals: &[Signal]) -> f32 {
+        match signals {
+            [Signal::Order(OrderRequest {
+                side: OrderSide::Buy,
+                volume,
+                ..
+            })] => *volume,
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_scheduled_buys() {
+        let mut dca = strategy();
+        assert!(dca.on_tick(&tick(8, 0, 100.0, 1000.0)).is_empty());
+        assert!(dca.on_tick(&tick(8, 59, 100.0, 1000.0)).is_empty());
+        assert_eq!(volume(&dca.on_tick(&tick(9, 0, 100.0, 1000.0))), 0.5);
+        assert!(dca.on_tick(&tick(9, 1, 100.0, 1000.0)).is_empty());
+
+        // Next day, 10% below the last buy: twice the amount.
+        let mut next = tick(9, 0, 90.0, 1000.0);
+        next.time = next.time + chrono::Duration::days(1);
+        assert!((volume(&dca.on_tick(&next)) - 100.0 / 90.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_respects_balance() {
+        let mut dca = strategy();
+        dca.on_tick(&tick(8, 0, 100.0, 40.0));
+        assert!(dca.on_tick(&tick(9, 0, 100.0, 40.0)).is_empty());
+
+        // A dip buy is capped to the balance, less the fee reserve.
+        let mut dca = strategy();
+        dca.last_buy_price = Some(200.0);
+        dca.on_tick(&tick(8, 0, 100.0, 80.4));
+        assert!((volume(&dca.on_tick(&tick(9, 0, 100.0, 80.4))) - 0.8).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,479 @@
+use crate::tradingbot::{
+    config::{ConfigError, TradingConfig},
+    order::{Fill, OpenOrder, OrderRequest, OrderSide, Price},
+    strategy::{Signal, Strategy, Tick},
+};
+use log::{info, warn};
+use std::collections::HashMap;
+
+/// Tags the grid's orders on the market, so they are recognised after a restart.
+const DEFAULT_USERREF: i32 = 4711;
+
+/// Ladder order of the grid.
+#[derive(Debug, Clone)]
+struct GridOrder {
+    level: usize,
+    side: OrderSide,
+    filled: f32,
+    cost: f32,
+}
+
+/// Keeps limit buys on the grid levels below the price and limit sells above it,
+/// `order_size` each. A filled buy is replaced by a sell one level up and a filled sell by
+/// a buy one level down, every buy-sell round trip earning one grid step. Levels whose order
+/// failed or was closed by the market are placed again on the next tick.
+///
+/// Params: `levels` (default 10) steps between `lower` and `upper`, or when those are not set,
+/// between `range_pct` (default 5) percent below and above the price of the first cycle.
+/// Level prices are rounded to `decimals` (default 1, as for XBT/USD), `userref` (default 4711)
+/// tags the grid's orders.
+///
+/// On startup resting orders of the pair tagged with `userref` are adopted when on a level of
+/// fixed bounds, and cancelled otherwise, the ladder being rebuilt around the current price.
+pub struct Grid {
+    pair: String,
+    volume: f32,
+    levels: usize,
+    bounds: Option<(f32, f32)>,
+    range_pct: f32,
+    decimals: i32,
+    userref: i32,
+    /// Level prices, ascending. Empty until bounds are known.
+    prices: Vec<f32>,
+    /// Level kept empty, at the price on the first tick then the one of the last filled order.
+    /// None while the price is off the grid.
+    gap: Option<usize>,
+    /// Resting ladder orders by id.
+    orders: HashMap<String, GridOrder>,
+    /// Cost of the buys the grid filled by level, until sold one level up.
+    bought: HashMap<usize, f32>,
+    realized_profit: f32,
+}
+
+impl Grid {
+    pub fn from_config(config: &TradingConfig) -> Result<Self, ConfigError> {
+        let levels = config.param("levels", 10usize)?;
+        let lower = config.param("lower", 0.0f32)?;
+        let upper = config.param("upper", 0.0f32)?;
+        let range_pct = config.param("range_pct", 5.0f32)?;
+        let decimals = config.param("decimals", 1i32)?;
+        let userref = config.param("userref", DEFAULT_USERREF)?;
+
+        if levels < 2 {
+            return Err(ConfigError(format!(
+                "grid needs at least 2 levels, got {}",
+                levels
+            )));
+        }
+        let bounds = match (lower, upper) {
+            (lower, upper) if lower == 0.0 && upper == 0.0 => None,
+            (lower, upper) if 0.0 < lower && lower < upper => Some((lower, upper)),
+            _ => {
+                return Err(ConfigError(format!(
+                    "grid needs 0 < lower < upper, got lower {} and upper {}",
+                    lower, upper
+                )))
+            }
+        };
+        if bounds.is_none() && (range_pct <= 0.0 || range_pct >= 100.0) {
+            return Err(ConfigError(format!(
+                "range_pct must be within (0, 100), got {}",
+                range_pct
+            )));
+        }
+
+        let mut grid = Grid {
+            pair: config.pair.clone(),
+            volume: config.order_size,
+            levels,
+            bounds,
+            range_pct,
+            decimals,
+            userref,
+            prices: Vec::new(),
+            gap: None,
+            orders: HashMap::new(),
+            bought: HashMap::new(),
+            realized_profit: 0.0,
+        };
+        if let Some((lower, upper)) = bounds {
+            grid.build(lower, upper);
+        }
+        Ok(grid)
+    }
+
+    /// Profit of the completed round trips, net of fees. Sells of base held before the grid
+    /// bought any count for their fees only.
+    pub fn realized_profit(&self) -> f32 {
+        self.realized_profit
+    }
+
+    fn build(&mut self, lower: f32, upper: f32) {
+        let step = (upper - lower) / self.levels as f32;
+        let unit = 10f32.powi(self.decimals);
+        self.prices = (0..=self.levels)
+            .map(|i| ((lower + step * i as f32) * unit).round() / unit)
+            .collect();
+        info!("[GRID] levels {:?}", self.prices);
+    }
+
+    fn step(&self) -> f32 {
+        self.prices[1] - self.prices[0]
+    }
+
+    /// Level at `price`, within a hundredth of a step.
+    fn level_of(&self, price: f32) -> Option<usize> {
+        let tolerance = self.step() / 100.0;
+        self.prices
+            .iter()
+            .position(|level| (level - price).abs() <= tolerance)
+    }
+
+    fn order(&self, level: usize, side: OrderSide) -> Signal {
+        Signal::Order(OrderRequest {
+            userref: Some(self.userref),
+            ..OrderRequest::limit(side, &self.pair, self.volume, self.prices[level])
+        })
+    }
+}
+
+impl Strategy for Grid {
+    fn name(&self) -> &str {
+        "grid"
+    }
+
+    fn on_open_orders(&mut self, orders: &[OpenOrder]) -> Vec<Signal> {
+        let mut signals = Vec::new();
+        let pair = self.pair.clone();
+        let userref = Some(self.userref);
+        for order in orders
+            .iter()
+            .filter(|o| o.pair == pair && o.userref == userref)
+        {
+            let level = match self.bounds {
+                Some(_) => self.level_of(order.price),
+                None => None,
+            };
+            let taken = self.orders.values().any(|o| Some(o.level) == level);
+            match level {
+                Some(level) if !taken => {
+                    info!("[GRID] adopting {} at {}", order.id, order.price);
+                    signals.push(Signal::Adopt(order.id.clone()));
+                    self.orders.insert(
+                        order.id.clone(),
+                        GridOrder {
+                            level,
+                            side: order.side,
+                            filled: order.volume_executed,
+                            cost: order.volume_executed * order.price,
+                        },
+                    );
+                }
+                _ => {
+                    info!("[GRID] cancelling stale {} at {}", order.id, order.price);
+                    signals.push(Signal::Cancel(order.id.clone()));
+                }
+            }
+        }
+        signals
+    }
+
+    fn on_tick(&mut self, tick: &Tick) -> Vec<Signal> {
+        if self.prices.is_empty() {
+            let width = tick.price * self.range_pct / 100.0;
+            self.build(tick.price - width, tick.price + width);
+        }
+
+        let (lower, upper) = (self.prices[0], self.prices[self.levels]);
+        if tick.price < lower || tick.price > upper {
+            warn!(
+                "[GRID] price {} outside of the grid {} - {}",
+                tick.price, lower, upper
+            );
+        }
+
+        let step = self.step();
+        if self.gap.is_none() {
+            self.gap = self
+                .prices
+                .iter()
+                .position(|price| (price - tick.price).abs() < step / 2.0);
+        }
+
+        let mut signals = Vec::new();
+        for (level, price) in self.prices.iter().enumerate() {
+            if Some(level) == self.gap || self.orders.values().any(|o| o.level == level) {
+                continue;
+            }
+            let below = match self.gap {
+                Some(gap) => level < gap,
+                None => *price < tick.price,
+            };
+            let side = if below {
+                OrderSide::Buy
+            } else {
+                OrderSide::Sell
+            };
+            signals.push(self.order(level, side));
+        }
+        signals
+    }
+
+    fn on_order_placed(&mut self, order: &OrderRequest, order_id: &str) {
+        if order.userref != Some(self.userref) {
+            return;
+        }
+        let level = match order.price {
+            Some(Price::Absolute(price)) => self.level_of(price),
+            _ => None,
+        };
+        if let Some(level) = level {
+            self.orders.insert(
+                order_id.to_owned(),
+                GridOrder {
+                    level,
+                    side: order.side,
+                    filled: 0.0,
+                    cost: 0.0,
+                },
+            );
+        }
+    }
+
+    fn on_fill(&mut self, fill: &Fill) -> Vec<Signal> {
+        let order = match self.orders.get_mut(&fill.order_id) {
+            Some(order) => order,
+            None => return Vec::new(),
+        };
+        order.filled += fill.volume;
+        order.cost += fill.price * fill.volume;
+        self.realized_profit -= fill.fee;
+        if order.filled < self.volume * 0.999 {
+            return Vec::new();
+        }
+
+        let order = self.orders.remove(&fill.order_id).unwrap();
+        self.gap = Some(order.level);
+        let replacement = match order.side {
+            OrderSide::Buy if order.level < self.levels => {
+                self.bought.insert(order.level, order.cost);
+                Some((order.level + 1, OrderSide::Sell))
+            }
+            OrderSide::Sell if order.level > 0 => {
+                // Closes a round trip when the grid bought one level down.
+                if let Some(bought) = self.bought.remove(&(order.level - 1)) {
+                    self.realized_profit += order.cost - bought;
+                    info!(
+                        "[GRID] round trip done, realized profit {:.2}",
+                        self.realized_profit
+                    );
+                }
+                Some((order.level - 1, OrderSide::Buy))
+            }
+            _ => None,
+        };
+
+        match replacement {
+            Some((level, side)) => vec![self.order(level, side)],
+            None => Vec::new(),
+        }
+    }
+
+    fn on_order_closed(&mut self, order_id: &str) {
+        if let Some(order) = self.orders.remove(order_id) {
+            info!(
+                "[GRID] {} closed, level {} placed again",
+                order_id, self.prices[order.level]
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn grid() -> Grid {
+        let mut config = TradingConfig {
+            order_size: 0.5,
+            ..Default::default()
+        };
+        for (name, value) in &[("lower", "90"), ("upper", "110"), ("levels", "4")] {
+            config
+                .strategy_params
+                .insert(name.to_string(), value.to_string());
+        }
+        Grid::from_config(&config).unwrap()
+    }
+
+    fn tick(price: f32) -> Tick {
+        Tick {
+            time: Utc.timestamp(0, 0),
+            price,
+            balance: 1000.0,
+            base_balance: 0.0,
+            book: None,
+        }
+    }
+
+    fn limit(signal: &Signal) -> (OrderSide, f32) {
+        match signal {
+            Signal::Order(OrderRequest {
+                side,
+                price: Some(Price::Absolute(price)),
+                userref: Some(DEFAULT_USERREF),
+                ..
+            }) => (*side, *price),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    fn fill(order_id: &str, side: OrderSide, price: f32) -> Fill {
+        Fill {
+            trade_id: "T".to_owned(),
+            order_id: order_id.to_owned(),
+            pair: "XXBTZUSD".to_owned(),
+            side,
+            price,
+            volume: 0.5,
+            fee: 0.0,
+            time: Utc.timestamp(0, 0),
+        }
+    }
+
+    #[test]
+    fn test_ladder_and_round_trip() {
+        let mut grid = grid();
+        let signals = grid.on_tick(&tick(101.0));
+        let ladder: Vec<(OrderSide, f32)> = signals.iter().map(limit).collect();
+        assert_eq!(
+            ladder,
+            vec![
+                (OrderSide::Buy, 90.0),
+                (OrderSide::Buy, 95.0),
+                (OrderSide::Sell, 105.0),
+                (OrderSide::Sell, 110.0),
+            ]
+        );
+        // Levels are resent until the market acknowledges them.
+        if let Signal::Order(order) = &signals[1] {
+            grid.on_order_placed(order, "B95");
+        }
+        assert_eq!(grid.on_tick(&tick(101.0)).len(), 3);
+        for (signal, id) in signals.iter().zip(&["B90", "B95", "S105", "S110"]) {
+            if let Signal::Order(order) = signal {
+                grid.on_order_placed(order, id);
+            }
+        }
+        assert!(grid.on_tick(&tick(101.0)).is_empty());
+
+        let sell = grid.on_fill(&fill("B95", OrderSide::Buy, 95.0));
+        assert_eq!(limit(&sell[0]), (OrderSide::Sell, 100.0));
+
+        if let Signal::Order(order) = &sell[0] {
+            grid.on_order_placed(order, "S100");
+        }
+        let buy = grid.on_fill(&fill("S100", OrderSide::Sell, 100.0));
+        assert_eq!(limit(&buy[0]), (OrderSide::Buy, 95.0));
+        assert_eq!(grid.realized_profit(), 2.5);
+    }
+
+    #[test]
+    fn test_held_base_sells_are_no_round_trip() {
+        let mut grid = grid();
+        let signals = grid.on_tick(&tick(101.0));
+        if let Signal::Order(order) = &signals[2] {
+            grid.on_order_placed(order, "S105");
+        }
+
+        let buy = grid.on_fill(&fill("S105", OrderSide::Sell, 105.0));
+        assert_eq!(limit(&buy[0]), (OrderSide::Buy, 100.0));
+        assert_eq!(grid.realized_profit(), 0.0);
+    }
+
+    #[test]
+    fn test_closed_levels_are_placed_again() {
+        let mut grid = grid();
+        let signals = grid.on_tick(&tick(101.0));
+        for (signal, id) in signals.iter().zip(&["B90", "B95", "S105", "S110"]) {
+            if let Signal::Order(order) = signal {
+                grid.on_order_placed(order, id);
+            }
+        }
+
+        grid.on_order_closed("S110");
+        let signals = grid.on_tick(&tick(101.0));
+        assert_eq!(
+            signals.iter().map(limit).collect::<Vec<_>>(),
+            vec![(OrderSide::Sell, 110.0)]
+        );
+        if let Signal::Order(order) = &signals[0] {
+            grid.on_order_placed(order, "S110-2");
+        }
+
+        // The level of a filled order stays empty, its replacement takes the previous gap.
+        let sell = grid.on_fill(&fill("B95", OrderSide::Buy, 95.0));
+        if let Signal::Order(order) = &sell[0] {
+            grid.on_order_placed(order, "S100");
+        }
+        assert!(grid.on_tick(&tick(96.0)).is_empty());
+    }
+
+    #[test]
+    fn test_range_levels_are_rounded() {
+        let mut config = TradingConfig::default();
+        config
+            .strategy_params
+            .insert("decimals".to_owned(), "2".to_owned());
+        let mut grid = Grid::from_config(&config).unwrap();
+
+        let signals = grid.on_tick(&tick(101.37));
+        assert_eq!(signals.len(), 10);
+        for (_, price) in signals.iter().map(limit) {
+            assert_eq!((price * 100.0).round() / 100.0, price);
+        }
+        assert_eq!(limit(&signals[0]).1, 96.3);
+    }
+
+    #[test]
+    fn test_reconcile_open_orders() {
+        let mut grid = grid();
+        let open = |id: &str, price: f32, userref: Option<i32>| OpenOrder {
+            id: id.to_owned(),
+            pair: "XXBTZUSD".to_owned(),
+            side: OrderSide::Buy,
+            price,
+            volume: 0.5,
+            volume_executed: 0.0,
+            userref,
+        };
+
+        let signals = grid.on_open_orders(&[
+            open("B95", 95.0, Some(DEFAULT_USERREF)),
+            open("B97", 97.0, Some(DEFAULT_USERREF)),
+            open("B96", 96.0, None),
+            OpenOrder {
+                pair: "XETHZUSD".to_owned(),
+                ..open("E97", 97.0, Some(DEFAULT_USERREF))
+            },
+        ]);
+        assert_eq!(
+            signals,
+            vec![
+                Signal::Adopt("B95".to_owned()),
+                Signal::Cancel("B97".to_owned())
+            ]
+        );
+
+        let ladder: Vec<(OrderSide, f32)> = grid.on_tick(&tick(101.0)).iter().map(limit).collect();
+        assert_eq!(
+            ladder,
+            vec![
+                (OrderSide::Buy, 90.0),
+                (OrderSide::Sell, 105.0),
+                (OrderSide::Sell, 110.0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use crate::indicators::{Ema, Sma, Wma};
+use crate::tradingbot::{
+    candle::Candle,
+    config::{ConfigError, TradingConfig},
+    order::{Fill, OrderSide},
+    strategy::{Signal, Strategy, TargetPosition, Tick},
+};
+use log::info;
+
+/// Moving average kind, `kind` strategy param.
+#[derive(Debug, Clone)]
+enum Average {
+    Sma(Sma),
+    Ema(Ema),
+    Wma(Wma),
+}
+
+impl Average {
+    fn new(kind: &str, period: usize) -> Result<Self, ConfigError> {
+        match kind {
+            "sma" => Ok(Average::Sma(Sma::new(period))),
+            "ema" => Ok(Average::Ema(Ema::new(period))),
+            "wma" => Ok(Average::Wma(Wma::new(period))),
+            other => Err(ConfigError(format!(
+                "unknown moving average {}, expected sma, ema or wma",
+                other
+            ))),
+        }
+    }
+
+    fn next(&mut self, value: f32) -> Option<f32> {
+        match self {
+            Average::Sma(average) => average.next(value),
+            Average::Ema(average) => average.next(value),
+            Average::Wma(average) => average.next(value),
+        }
+    }
+}
+
+/// Goes long `order_size` when the fast moving average of closes crosses above the slow one
+/// (golden cross) and back to flat when it crosses below (death cross), or once the price
+/// reaches the configured take profit or stop loss.
+///
+/// Params: `fast` (default 9), `slow` (default 21) and `kind` (`sma`, `ema` or `wma`, default `sma`).
+pub struct MaCrossover {
+    fast: Average,
+    slow: Average,
+    /// Fast minus slow average of the previous candle.
+    previous_spread: Option<f32>,
+    order_size: f32,
+    take_profit_pct: f32,
+    stop_loss_pct: f32,
+    /// Volume held and its average entry price, from our fills.
+    held: f32,
+    entry_price: f32,
+}
+
+impl MaCrossover {
+    pub fn from_config(config: &TradingConfig) -> Result<Self, ConfigError> {
+        let fast = config.param("fast", 9usize)?;
+        let slow = config.param("slow", 21usize)?;
+        let kind = config.param("kind", "sma".to_owned())?;
+        if fast == 0 || fast >= slow {
+            return Err(ConfigError(format!(
+                "ma_crossover needs 0 < fast < slow, got fast {} and slow {}",
+                fast, slow
+            )));
+        }
+
+        Ok(MaCrossover {
+            fast: Average::new(&kind, fast)?,
+            slow: Average::new(&kind, slow)?,
+            previous_spread: None,
+            order_size: config.order_size,
+            take_profit_pct: config.take_profit_pct,
+            stop_loss_pct: config.stop_loss_pct,
+            held: 0.0,
+            entry_price: 0.0,
+        })
+    }
+}
+
+impl Strategy for MaCrossover {
+    fn name(&self) -> &str {
+        "ma_crossover"
+    }
+
+    fn on_candle(&mut self, candle: &Candle) -> Vec<Signal> {
+        let fast = self.fast.next(candle.close);
+        let slow = self.slow.next(candle.close);
+        let spread = match (fast, slow) {
+            (Some(fast), Some(slow)) => fast - slow,
+            _ => return Vec::new(),
+        };
+
+        let previous = self.previous_spread.replace(spread);
+        match previous {
+            Some(previous) if previous <= 0.0 && spread > 0.0 => {
+                info!("[MA] golden cross at {}", candle.close);
+                vec![Signal::Target(TargetPosition::Long(self.order_size))]
+            }
+            Some(previous) if previous >= 0.0 && spread < 0.0 => {
+                info!("[MA] death cross at {}", candle.close);
+                vec![Signal::Target(TargetPosition::Flat)]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn on_tick(&mut self, tick: &Tick) -> Vec<Signal> {
+        if self.held <= 0.0 {
+            return Vec::new();
+        }
+
+        let change_pct = (tick.price - self.entry_price) / self.entry_price * 100.0;
+        if self.take_profit_pct > 0.0 && change_pct >= self.take_profit_pct {
+            info!("[MA] take profit at {} ({:.2}%)", tick.price, change_pct);
+            return vec![Signal::Target(TargetPosition::Flat)];
+        }
+        if self.stop_loss_pct > 0.0 && change_pct <= -self.stop_loss_pct {
+            info!("[MA] stop loss at {} ({:.2}%)", tick.price, change_pct);
+            return vec![Signal::Target(TargetPosition::Flat)];
+        }
+        Vec::new()
+    }
+
+    fn on_fill(&mut self, fill: &Fill) -> Vec<Signal> {
+        match fill.side {
+            OrderSide::Buy => {
+                self.entry_price = (self.entry_price * self.held + fill.price * fill.volume)
+                    / (self.held + fill.volume);
+                self.held += fill.volume;
+            }
+            OrderSide::Sell => {
+                self.held = (self.held - fill.volume).max(0.0);
+            }
+        }
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn candle(close: f32) -> Candle {
+        Candle {
+            time: Utc.timestamp(0, 0),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            vwap: close,
+            volume: 1.0,
+            count: 1,
+        }
+    }
+
+    fn strategy() -> MaCrossover {
+        let mut config = TradingConfig {
+            take_profit_pct: 5.0,
+            stop_loss_pct: 2.0,
+            ..Default::default()
+        };
+        config
+            .strategy_params
+            .insert("fast".to_owned(), "2".to_owned());
+        config
+            .strategy_params
+            .insert("slow".to_owned(), "3".to_owned());
+        MaCrossover::from_config(&config).unwrap()
+    }
+
+    #[test]
+    fn test_crosses() {
+        let mut strategy = strategy();
+        let signals: Vec<Vec<Signal>> = [10.0, 9.0, 8.0, 9.0, 11.0, 12.0, 9.0, 8.0]
+            .iter()
+            .map(|close| strategy.on_candle(&candle(*close)))
+            .collect();
+
+        let long = vec![Signal::Target(TargetPosition::Long(0.001))];
+        let flat = vec![Signal::Target(TargetPosition::Flat)];
+        // fast - slow from the third candle: -0.5, -0.17, +0.67, +0.83, -0.17, -1.17
+        assert!(signals[..4].iter().all(Vec::is_empty));
+        assert_eq!(signals[4], long);
+        assert!(signals[5].is_empty());
+        assert_eq!(signals[6], flat);
+        assert!(signals[7].is_empty());
+    }
+
+    #[test]
+    fn test_protective_exits() {
+        let mut strategy = strategy();
+        let tick = |price: f32| Tick {
+            time: Utc.timestamp(0, 0),
+            price,
+            balance: 1000.0,
+            base_balance: 0.0,
+            book: None,
+        };
+        assert!(strategy.on_tick(&tick(50.0)).is_empty());
+
+        strategy.on_fill(&Fill {
+            trade_id: "T".to_owned(),
+            order_id: "O".to_owned(),
+            pair: "XXBTZUSD".to_owned(),
+            side: OrderSide::Buy,
+            price: 100.0,
+            volume: 0.001,
+            fee: 0.0,
+            time: Utc.timestamp(0, 0),
+        });
+        let flat = vec![Signal::Target(TargetPosition::Flat)];
+        assert!(strategy.on_tick(&tick(104.0)).is_empty());
+        assert_eq!(strategy.on_tick(&tick(105.0)), flat);
+        assert_eq!(strategy.on_tick(&tick(97.9)), flat);
+
+        let mut config = TradingConfig::default();
+        config
+            .strategy_params
+            .insert("fast".to_owned(), "30".to_owned());
+        assert!(MaCrossover::from_config(&config).is_err());
+    }
+}
@@ -0,0 +1,330 @@
+use crate::indicators::{Atr, Indicator};
+use crate::tradingbot::{
+    candle::Candle,
+    config::{ConfigError, TradingConfig},
+    order::{Fill, OpenOrder, OrderFlag, OrderRequest, OrderSide},
+    strategy::{Signal, Strategy, Tick},
+};
+use log::info;
+use std::collections::HashSet;
+
+/// Tags the quotes on the market, so they are recognised after a restart.
+const DEFAULT_USERREF: i32 = 4712;
+
+/// Book levels sampled every cycle, only the top of the book is used.
+const BOOK_DEPTH: u32 = 10;
+
+/// Quotes a post-only bid and ask of `order_size` around the mid price of the order book.
+///
+/// The half spread is `spread_pct` / 2 percent of the mid plus `atr_multiplier` times the ATR of
+/// the configured timeframe, so quotes widen when the market gets volatile. Both quotes are
+/// shifted by up to `skew` half spreads against the inventory, relative to `target`:
+/// holding more base than the target lowers them to sell more and buy less, holding less raises
+/// them. The inventory is kept within 0 and `max_position`.
+///
+/// Quotes are cancelled and replaced once the mid moves more than `requote_pct` percent away
+/// from the mid they were quoted at, and after a quote gets filled.
+///
+/// Params: `spread_pct` (default 0.2), `atr_period` (default 14), `atr_multiplier` (default 0.1),
+/// `target` (default half of `max_position`), `skew` (default 1), `requote_pct` (default 0.1),
+/// `decimals` of the quote prices (default 1, as for XBT/USD) and `userref` (default 4712), which
+/// with the pair identifies quotes left by a previous run.
+pub struct MarketMaker {
+    pair: String,
+    volume: f32,
+    max_position: f32,
+    spread_pct: f32,
+    atr: Atr,
+    atr_value: Option<f32>,
+    atr_multiplier: f32,
+    target: f32,
+    skew: f32,
+    requote_pct: f32,
+    decimals: i32,
+    userref: i32,
+    /// Ids of our resting quotes.
+    quotes: HashSet<String>,
+    /// Mid price at the last requote.
+    quoted_mid: Option<f32>,
+    /// Whether a quote was filled since the last requote.
+    filled: bool,
+}
+
+impl MarketMaker {
+    pub fn from_config(config: &TradingConfig) -> Result<Self, ConfigError> {
+        let spread_pct = config.param("spread_pct", 0.2f32)?;
+        let atr_period = config.param("atr_period", 14usize)?;
+        let atr_multiplier = config.param("atr_multiplier", 0.1f32)?;
+        let target = config.param("target", config.max_position / 2.0)?;
+        let skew = config.param("skew", 1.0f32)?;
+        let requote_pct = config.param("requote_pct", 0.1f32)?;
+        let decimals = config.param("decimals", 1i32)?;
+        let userref = config.param("userref", DEFAULT_USERREF)?;
+
+        if config.max_position <= 0.0 {
+            return Err(ConfigError(
+                "market_maker needs a positive max_position".to_owned(),
+            ));
+        }
+        if spread_pct <= 0.0 || atr_period == 0 || atr_multiplier < 0.0 || skew < 0.0 {
+            return Err(ConfigError(format!(
+                "market_maker needs spread_pct > 0, atr_period > 0, atr_multiplier >= 0 \
+                 and skew >= 0, got {}, {}, {} and {}",
+                spread_pct, atr_period, atr_multiplier, skew
+            )));
+        }
+        if target < 0.0 || target > config.max_position {
+            return Err(ConfigError(format!(
+                "market_maker target {} must be within 0 and max_position {}",
+                target, config.max_position
+            )));
+        }
+
+        Ok(MarketMaker {
+            pair: config.pair.clone(),
+            volume: config.order_size,
+            max_position: config.max_position,
+            spread_pct,
+            atr: Atr::new(atr_period),
+            atr_value: None,
+            atr_multiplier,
+            target,
+            skew,
+            requote_pct,
+            decimals,
+            userref,
+            quotes: HashSet::new(),
+            quoted_mid: None,
+            filled: false,
+        })
+    }
+
+    /// Bid and ask prices around `mid` for `inventory`.
+    fn quote_prices(&self, mid: f32, inventory: f32) -> (f32, f32) {
+        let half_spread =
+            mid * self.spread_pct / 200.0 + self.atr_multiplier * self.atr_value.unwrap_or(0.0);
+        let deviation = ((inventory - self.target) / self.max_position).clamp(-1.0, 1.0);
+        let center = mid - deviation * self.skew * half_spread;
+
+        let unit = 10f32.powi(self.decimals);
+        (
+            ((center - half_spread) * unit).floor() / unit,
+            ((center + half_spread) * unit).ceil() / unit,
+        )
+    }
+
+    fn needs_requote(&self, mid: f32) -> bool {
+        match self.quoted_mid {
+            Some(quoted) => self.filled || (mid - quoted).abs() / quoted * 100.0 > self.requote_pct,
+            None => true,
+        }
+    }
+
+    fn quote(&self, side: OrderSide, price: f32) -> Signal {
+        Signal::Order(OrderRequest {
+            flags: vec![OrderFlag::PostOnly],
+            userref: Some(self.userref),
+            ..OrderRequest::limit(side, &self.pair, self.volume, price)
+        })
+    }
+}
+
+impl Strategy for MarketMaker {
+    fn name(&self) -> &str {
+        "market_maker"
+    }
+
+    fn on_candle(&mut self, candle: &Candle) -> Vec<Signal> {
+        if let Some(atr) = self.atr.update(candle) {
+            self.atr_value = Some(atr);
+        }
+        Vec::new()
+    }
+
+    fn on_open_orders(&mut self, orders: &[OpenOrder]) -> Vec<Signal> {
+        let userref = Some(self.userref);
+        orders
+            .iter()
+            .filter(|o| o.pair == self.pair && o.userref == userref)
+            .map(|o| {
+                info!("[MM] cancelling stale quote {} at {}", o.id, o.price);
+                Signal::Cancel(o.id.clone())
+            })
+            .collect()
+    }
+
+    fn book_depth(&self) -> Option<u32> {
+        Some(BOOK_DEPTH)
+    }
+
+    fn on_tick(&mut self, tick: &Tick) -> Vec<Signal> {
+        let book = match &tick.book {
+            Some(book) => book,
+            None => return Vec::new(),
+        };
+        let (best_bid, best_ask) = match (book.best_bid(), book.best_ask()) {
+            (Some(bid), Some(ask)) => (bid, ask),
+            _ => return Vec::new(),
+        };
+        let mid = (best_bid + best_ask) / 2.0;
+        if !self.needs_requote(mid) {
+            return Vec::new();
+        }
+
+        let mut signals: Vec<Signal> = self.quotes.drain().map(Signal::Cancel).collect();
+        self.quoted_mid = Some(mid);
+        self.filled = false;
+
+        let (bid, ask) = self.quote_prices(mid, tick.base_balance);
+        // Post-only quotes must not cross the book.
+        let unit = 10f32.powi(-self.decimals);
+        let (bid, ask) = (bid.min(best_ask - unit), ask.max(best_bid + unit));
+        info!(
+            "[MM] mid {} inventory {}, quoting {} / {}",
+            mid, tick.base_balance, bid, ask
+        );
+
+        if tick.base_balance + self.volume <= self.max_position && tick.balance >= self.volume * bid
+        {
+            signals.push(self.quote(OrderSide::Buy, bid));
+        }
+        if tick.base_balance >= self.volume {
+            signals.push(self.quote(OrderSide::Sell, ask));
+        }
+        signals
+    }
+
+    fn on_order_placed(&mut self, order: &OrderRequest, order_id: &str) {
+        if order.userref == Some(self.userref) {
+            self.quotes.insert(order_id.to_owned());
+        }
+    }
+
+    fn on_fill(&mut self, fill: &Fill) -> Vec<Signal> {
+        if self.quotes.contains(&fill.order_id) {
+            info!(
+                "[MM] {:?} {} filled at {}",
+                fill.side, fill.volume, fill.price
+            );
+            self.filled = true;
+        }
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tradingbot::{order::Price, order_book::OrderBook};
+    use chrono::{TimeZone, Utc};
+
+    fn strategy() -> MarketMaker {
+        let mut config = TradingConfig {
+            order_size: 0.1,
+            max_position: 1.0,
+            ..Default::default()
+        };
+        config
+            .strategy_params
+            .insert("spread_pct".to_owned(), "2".to_owned());
+        MarketMaker::from_config(&config).unwrap()
+    }
+
+    fn tick(mid: f32, base_balance: f32) -> Tick {
+        Tick {
+            time: Utc.timestamp(0, 0),
+            price: mid,
+            balance: 1000.0,
+            base_balance,
+            book: Some(OrderBook {
+                asks: vec![(mid + 0.5, 1.0)],
+                bids: vec![(mid - 0.5, 1.0)],
+            }),
+        }
+    }
+
+    fn quotes(signals: &[Signal]) -> Vec<(OrderSide, f32)> {
+        signals
+            .iter()
+            .filter_map(|signal| match signal {
+                Signal::Order(OrderRequest {
+                    side,
+                    price: Some(Price::Absolute(price)),
+                    flags,
+                    ..
+                }) if flags == &[OrderFlag::PostOnly] => Some((*side, *price)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_inventory_skew() {
+        // At the target of 0.5, quotes are centered: half spread 1% of 100.
+        assert_eq!(
+            quotes(&strategy().on_tick(&tick(100.0, 0.5))),
+            vec![(OrderSide::Buy, 99.0), (OrderSide::Sell, 101.0)]
+        );
+        // Long 0.25 above the target: both quotes move down by a quarter of the half spread.
+        assert_eq!(
+            quotes(&strategy().on_tick(&tick(100.0, 0.75))),
+            vec![(OrderSide::Buy, 98.7), (OrderSide::Sell, 100.8)]
+        );
+        // Nothing to sell, and no bid past max_position.
+        assert_eq!(
+            quotes(&strategy().on_tick(&tick(100.0, 0.0))),
+            vec![(OrderSide::Buy, 99.5)]
+        );
+        assert_eq!(
+            quotes(&strategy().on_tick(&tick(100.0, 1.0))),
+            vec![(OrderSide::Sell, 100.5)]
+        );
+    }
+
+    #[test]
+    fn test_requote() {
+        let mut strategy = strategy();
+        let signals = strategy.on_tick(&tick(100.0, 0.5));
+        for (signal, id) in signals.iter().zip(&["BID", "ASK"]) {
+            if let Signal::Order(order) = signal {
+                strategy.on_order_placed(order, id);
+            }
+        }
+
+        assert!(strategy.on_tick(&tick(100.05, 0.5)).is_empty());
+        let signals = strategy.on_tick(&tick(100.2, 0.5));
+        let mut cancelled: Vec<&Signal> = signals
+            .iter()
+            .filter(|s| matches!(s, Signal::Cancel(_)))
+            .collect();
+        cancelled.sort_by_key(|s| format!("{:?}", s));
+        assert_eq!(
+            cancelled,
+            vec![
+                &Signal::Cancel("ASK".to_owned()),
+                &Signal::Cancel("BID".to_owned())
+            ]
+        );
+        assert_eq!(quotes(&signals).len(), 2);
+    }
+
+    #[test]
+    fn test_stale_quotes_of_pair_cancelled() {
+        let open = |id: &str, pair: &str, userref: Option<i32>| OpenOrder {
+            id: id.to_owned(),
+            pair: pair.to_owned(),
+            side: OrderSide::Buy,
+            price: 99.0,
+            volume: 0.1,
+            volume_executed: 0.0,
+            userref,
+        };
+
+        let signals = strategy().on_open_orders(&[
+            open("STALE", "XXBTZUSD", Some(DEFAULT_USERREF)),
+            open("OTHER_PAIR", "XETHZUSD", Some(DEFAULT_USERREF)),
+            open("MANUAL", "XXBTZUSD", None),
+        ]);
+        assert_eq!(signals, vec![Signal::Cancel("STALE".to_owned())]);
+    }
+}
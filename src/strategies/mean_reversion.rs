@@ -0,0 +1,253 @@
+use crate::indicators::{Bollinger, Rsi};
+use crate::tradingbot::{
+    candle::Candle,
+    config::{ConfigError, TradingConfig},
+    order::{Fill, OrderSide},
+    strategy::{Signal, Strategy, TargetPosition, Tick},
+};
+use log::info;
+
+/// Buys `order_size` when a candle closes below the lower Bollinger band with the RSI oversold,
+/// and goes back to flat once a close reaches the middle band. Shorts mirror it: a close above
+/// the upper band with the RSI overbought, covered at the middle band. Positions are also
+/// stopped out once price moves `stop_loss_pct` percent against the entry.
+///
+/// Params: `period` (default 20) and `std_dev` (default 2) of the bands, `rsi_period`
+/// (default 14), `oversold` (default 30), `overbought` (default 70) and `short`
+/// (default true when `leverage` is set, shorts being margin trades).
+pub struct MeanReversion {
+    bands: Bollinger,
+    rsi: Rsi,
+    oversold: f32,
+    overbought: f32,
+    short: bool,
+    order_size: f32,
+    stop_loss_pct: f32,
+    /// Volume held from our fills, negative when short, and its average entry price.
+    held: f32,
+    entry_price: f32,
+}
+
+impl MeanReversion {
+    pub fn from_config(config: &TradingConfig) -> Result<Self, ConfigError> {
+        let period = config.param("period", 20usize)?;
+        let std_dev = config.param("std_dev", 2.0f32)?;
+        let rsi_period = config.param("rsi_period", 14usize)?;
+        let oversold = config.param("oversold", 30.0f32)?;
+        let overbought = config.param("overbought", 70.0f32)?;
+        let short = config.param("short", config.leverage.is_some())?;
+
+        if period < 2 || rsi_period == 0 || std_dev <= 0.0 {
+            return Err(ConfigError(format!(
+                "mean_reversion needs period > 1, rsi_period > 0 and std_dev > 0, got {}, {} and {}",
+                period, rsi_period, std_dev
+            )));
+        }
+        if !(0.0 < oversold && oversold < overbought && overbought < 100.0) {
+            return Err(ConfigError(format!(
+                "mean_reversion needs 0 < oversold < overbought < 100, got {} and {}",
+                oversold, overbought
+            )));
+        }
+        if short && config.leverage.is_none() {
+            return Err(ConfigError(
+                "mean_reversion shorts need leverage set in the config".to_owned(),
+            ));
+        }
+
+        Ok(MeanReversion {
+            bands: Bollinger::new(period, std_dev),
+            rsi: Rsi::new(rsi_period),
+            oversold,
+            overbought,
+            short,
+            order_size: config.order_size,
+            stop_loss_pct: config.stop_loss_pct,
+            held: 0.0,
+            entry_price: 0.0,
+        })
+    }
+}
+
+impl Strategy for MeanReversion {
+    fn name(&self) -> &str {
+        "mean_reversion"
+    }
+
+    fn on_candle(&mut self, candle: &Candle) -> Vec<Signal> {
+        let bands = self.bands.next(candle.close);
+        let rsi = self.rsi.next(candle.close);
+        let (bands, rsi) = match (bands, rsi) {
+            (Some(bands), Some(rsi)) => (bands, rsi),
+            _ => return Vec::new(),
+        };
+
+        let close = candle.close;
+        let target = if self.held > 0.0 {
+            if close >= bands.middle {
+                info!("[MR] long back to the middle band at {}", close);
+                Some(TargetPosition::Flat)
+            } else {
+                None
+            }
+        } else if self.held < 0.0 {
+            if close <= bands.middle {
+                info!("[MR] short back to the middle band at {}", close);
+                Some(TargetPosition::Flat)
+            } else {
+                None
+            }
+        } else if close < bands.lower && rsi < self.oversold {
+            info!(
+                "[MR] close {} below {} with RSI {:.1}",
+                close, bands.lower, rsi
+            );
+            Some(TargetPosition::Long(self.order_size))
+        } else if self.short && close > bands.upper && rsi > self.overbought {
+            info!(
+                "[MR] close {} above {} with RSI {:.1}",
+                close, bands.upper, rsi
+            );
+            Some(TargetPosition::Short(self.order_size))
+        } else {
+            None
+        };
+        target.map(Signal::Target).into_iter().collect()
+    }
+
+    fn on_tick(&mut self, tick: &Tick) -> Vec<Signal> {
+        if self.held == 0.0 || self.stop_loss_pct <= 0.0 {
+            return Vec::new();
+        }
+
+        let change_pct = (tick.price - self.entry_price) / self.entry_price * 100.0;
+        let adverse_pct = if self.held > 0.0 {
+            -change_pct
+        } else {
+            change_pct
+        };
+        if adverse_pct >= self.stop_loss_pct {
+            info!("[MR] stop loss at {} ({:.2}%)", tick.price, change_pct);
+            return vec![Signal::Target(TargetPosition::Flat)];
+        }
+        Vec::new()
+    }
+
+    fn on_fill(&mut self, fill: &Fill) -> Vec<Signal> {
+        let volume = match fill.side {
+            OrderSide::Buy => fill.volume,
+            OrderSide::Sell => -fill.volume,
+        };
+        let held = self.held + volume;
+        if held.abs() < 1e-9 {
+            self.held = 0.0;
+            return Vec::new();
+        }
+        // Adding to the position moves the average entry, reducing it does not.
+        if self.held == 0.0 || self.held.signum() == volume.signum() {
+            self.entry_price =
+                (self.entry_price * self.held.abs() + fill.price * fill.volume) / held.abs();
+        } else if held.signum() != self.held.signum() {
+            self.entry_price = fill.price;
+        }
+        self.held = held;
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn candle(close: f32) -> Candle {
+        Candle {
+            time: Utc.timestamp(0, 0),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            vwap: close,
+            volume: 1.0,
+            count: 1,
+        }
+    }
+
+    fn strategy() -> MeanReversion {
+        let mut config = TradingConfig {
+            stop_loss_pct: 5.0,
+            leverage: Some(2),
+            ..Default::default()
+        };
+        for (name, value) in &[("period", "5"), ("std_dev", "1.5"), ("rsi_period", "3")] {
+            config
+                .strategy_params
+                .insert(name.to_string(), value.to_string());
+        }
+        MeanReversion::from_config(&config).unwrap()
+    }
+
+    fn fill(side: OrderSide, price: f32) -> Fill {
+        Fill {
+            trade_id: "T".to_owned(),
+            order_id: "O".to_owned(),
+            pair: "XXBTZUSD".to_owned(),
+            side,
+            price,
+            volume: 0.001,
+            fee: 0.0,
+            time: Utc.timestamp(0, 0),
+        }
+    }
+
+    fn closes(strategy: &mut MeanReversion, closes: &[f32]) -> Vec<Vec<Signal>> {
+        closes
+            .iter()
+            .map(|close| strategy.on_candle(&candle(*close)))
+            .collect()
+    }
+
+    #[test]
+    fn test_long_round_trip() {
+        let mut strategy = strategy();
+        let signals = closes(&mut strategy, &[100.0, 101.0, 100.0, 101.0, 100.0, 90.0]);
+        assert!(signals[..5].iter().all(Vec::is_empty));
+        assert_eq!(
+            signals[5],
+            vec![Signal::Target(TargetPosition::Long(0.001))]
+        );
+
+        strategy.on_fill(&fill(OrderSide::Buy, 90.0));
+        // Middle band of 101, 100, 90, 92, 99 is 96.4.
+        let signals = closes(&mut strategy, &[92.0, 99.0]);
+        assert!(signals[0].is_empty());
+        assert_eq!(signals[1], vec![Signal::Target(TargetPosition::Flat)]);
+    }
+
+    #[test]
+    fn test_short_stop_loss() {
+        let mut strategy = strategy();
+        let signals = closes(&mut strategy, &[100.0, 99.0, 100.0, 99.0, 100.0, 110.0]);
+        assert_eq!(
+            signals[5],
+            vec![Signal::Target(TargetPosition::Short(0.001))]
+        );
+
+        strategy.on_fill(&fill(OrderSide::Sell, 110.0));
+        let tick = |price: f32| Tick {
+            time: Utc.timestamp(0, 0),
+            price,
+            balance: 1000.0,
+            base_balance: 0.0,
+            book: None,
+        };
+        assert!(strategy.on_tick(&tick(115.0)).is_empty());
+        assert_eq!(
+            strategy.on_tick(&tick(116.0)),
+            vec![Signal::Target(TargetPosition::Flat)]
+        );
+
+        strategy.on_fill(&fill(OrderSide::Buy, 116.0));
+        assert!(strategy.on_tick(&tick(120.0)).is_empty());
+    }
+}
@@ -0,0 +1,13 @@
+pub mod dca;
+pub mod grid;
+pub mod ma_crossover;
+pub mod market_maker;
+pub mod mean_reversion;
+pub mod schedule;
+
+pub use dca::Dca;
+pub use grid::Grid;
+pub use ma_crossover::MaCrossover;
+pub use market_maker::MarketMaker;
+pub use mean_reversion::MeanReversion;
+pub use schedule::Schedule;
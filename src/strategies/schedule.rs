@@ -0,0 +1,193 @@
+use crate::tradingbot::config::ConfigError;
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
+use std::str::FromStr;
+
+/// Cron schedule in UTC: `minute hour day-of-month month day-of-week`, e.g. `0 9 * * 1`
+/// for Mondays at 09:00. Fields take `*`, values, ranges `a-b`, lists `a,b` and steps `*/n`
+/// or `a-b/n`. Sunday is 0 or 7. `@hourly`, `@daily`, `@weekly` and `@monthly` are accepted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether day-of-month and day-of-week were both restricted, any of them then matches.
+    either_day: bool,
+}
+
+impl Schedule {
+    pub fn matches(&self, time: DateTime<Utc>) -> bool {
+        self.minutes & (1 << time.minute()) != 0
+            && self.hours & (1 << time.hour()) != 0
+            && self.months & (1 << time.month()) != 0
+            && self.day_matches(time)
+    }
+
+    /// First scheduled minute strictly after `time`, `None` if the schedule never matches.
+    pub fn next_after(&self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut next =
+            Utc.timestamp(time.timestamp().div_euclid(60) * 60, 0) + Duration::minutes(1);
+        // A schedule matching at all does within 8 years, 29 February skips 2100.
+        let limit = next + Duration::days(8 * 366);
+        while next < limit {
+            if self.matches(next) {
+                return Some(next);
+            }
+            // Skip whole hours and days that cannot match.
+            let day_start = Utc
+                .ymd(next.year(), next.month(), next.day())
+                .and_hms(0, 0, 0);
+            next = if self.months & (1 << next.month()) == 0 || !self.day_matches(next) {
+                day_start + Duration::days(1)
+            } else if self.hours & (1 << next.hour()) == 0 {
+                day_start + Duration::hours(i64::from(next.hour()) + 1)
+            } else {
+                next + Duration::minutes(1)
+            };
+        }
+        None
+    }
+
+    fn day_matches(&self, time: DateTime<Utc>) -> bool {
+        let day = self.days & (1 << time.day()) != 0;
+        let weekday = self.weekdays & (1 << time.weekday().num_days_from_sunday()) != 0;
+        if self.either_day {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expression = match s.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(ConfigError(format!(
+                "schedule {} should have 5 fields: minute hour day-of-month month day-of-week",
+                s
+            )));
+        }
+
+        let mut weekdays = parse_field(fields[4], 0, 7)?;
+        // 7 is Sunday too.
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        let schedule = Schedule {
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)?,
+            days: parse_field(fields[2], 1, 31)?,
+            months: parse_field(fields[3], 1, 12)?,
+            weekdays,
+            either_day: fields[2] != "*" && fields[4] != "*",
+        };
+        // e.g. the 30th of February.
+        if schedule
+            .next_after(Utc.ymd(2000, 1, 1).and_hms(0, 0, 0))
+            .is_none()
+        {
+            return Err(ConfigError(format!("schedule {} never matches", s)));
+        }
+        Ok(schedule)
+    }
+}
+
+/// Bit mask of the values of a cron field.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, ConfigError> {
+    let invalid = || ConfigError(format!("invalid schedule field {}", field));
+    let number = |s: &str| -> Result<u32, ConfigError> {
+        match s.parse::<u32>() {
+            Ok(n) if n >= min && n <= max => Ok(n),
+            _ => Err(invalid()),
+        }
+    };
+
+    let mut mask = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.find('/') {
+            Some(i) => (
+                &part[..i],
+                part[i + 1..].parse::<u32>().map_err(|_| invalid())?,
+            ),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(invalid());
+        }
+        let (start, end) = match range {
+            "*" => (min, max),
+            range => match range.find('-') {
+                Some(i) => (number(&range[..i])?, number(&range[i + 1..])?),
+                None if step > 1 => (number(range)?, max),
+                None => (number(range)?, number(range)?),
+            },
+        };
+        if start > end {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        // January 2021, the 4th is a Monday.
+        Utc.ymd(2021, 1, day).and_hms(hour, minute, 0)
+    }
+
+    #[test]
+    fn test_next_after() {
+        let mondays: Schedule = "0 9 * * 1".parse().unwrap();
+        assert_eq!(mondays.next_after(at(1, 12, 0)), Some(at(4, 9, 0)));
+        assert_eq!(mondays.next_after(at(4, 9, 0)), Some(at(11, 9, 0)));
+
+        let quarter_hours: Schedule = "*/15 8-9 * * *".parse().unwrap();
+        assert_eq!(quarter_hours.next_after(at(1, 8, 14)), Some(at(1, 8, 15)));
+        assert_eq!(quarter_hours.next_after(at(1, 9, 45)), Some(at(2, 8, 0)));
+
+        // Day of month or Sunday.
+        let either: Schedule = "30 0 15 * 0".parse().unwrap();
+        assert_eq!(either.next_after(at(4, 0, 0)), Some(at(10, 0, 30)));
+        assert_eq!(either.next_after(at(10, 0, 30)), Some(at(15, 0, 30)));
+
+        let daily: Schedule = "@daily".parse().unwrap();
+        assert!(daily.matches(at(20, 0, 0)));
+        assert!(!daily.matches(at(20, 0, 1)));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!("0 9 * *".parse::<Schedule>().is_err());
+        assert!("60 * * * *".parse::<Schedule>().is_err());
+        assert!("*/0 * * * *".parse::<Schedule>().is_err());
+        assert!("5-1 * * * *".parse::<Schedule>().is_err());
+
+        assert!("0 0 31 2 *".parse::<Schedule>().is_err());
+        assert!("0 0 30 2 *".parse::<Schedule>().is_err());
+        assert!("0 0 31 4,6 *".parse::<Schedule>().is_err());
+        let leap_days: Schedule = "0 0 29 2 *".parse().unwrap();
+        assert_eq!(
+            leap_days.next_after(at(1, 0, 0)),
+            Some(Utc.ymd(2024, 2, 29).and_hms(0, 0, 0))
+        );
+        // Or any Monday.
+        assert!("0 0 31 2 1".parse::<Schedule>().is_ok());
+    }
+}
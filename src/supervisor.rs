@@ -0,0 +1,226 @@
+use crate::tradingbot::{ShutdownSummary, TradingBot};
+use log::{error, info, warn};
+use std::fmt;
+use std::future::Future;
+use tokio::sync::watch;
+use tokio::task;
+use tokio::time::{self, Duration, Instant};
+
+/// Why a bot of the supervisor stopped.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BotExit {
+    /// Asked to stop, with where it stopped.
+    Stopped(Box<ShutdownSummary>),
+    /// Too many cycles in a row failed, with the last error.
+    Failed(String),
+    Panicked(String),
+}
+
+impl fmt::Display for BotExit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BotExit::Stopped(summary) => write!(f, "stopped: {}", summary),
+            BotExit::Failed(e) => write!(f, "failed: {}", e),
+            BotExit::Panicked(e) => write!(f, "panicked: {}", e),
+        }
+    }
+}
+
+/// Runs several bots concurrently, each on its own spawned task at its own cadence. A bot that keeps
+/// failing or panics is stopped, the others carry on.
+pub struct Supervisor {
+    bots: Vec<(String, TradingBot)>,
+    /// Consecutive failed cycles after which a bot is stopped.
+    pub max_failures: u32,
+}
+
+impl Supervisor {
+    pub fn new(max_failures: u32) -> Self {
+        Supervisor {
+            bots: Vec::new(),
+            max_failures,
+        }
+    }
+
+    pub fn add(&mut self, name: &str, bot: TradingBot) {
+        self.bots.push((name.to_owned(), bot));
+    }
+
+    /// Run the bots until every one of them stopped, returning why each did. Once `shutdown`
+    /// resolves, bots finish their current cycle and shut down.
+    pub async fn run<F>(self, shutdown: F) -> Vec<(String, BotExit)>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let (stop, stopped) = watch::channel(false);
+        task::spawn(async move {
+            shutdown.await;
+            let _ = stop.send(true);
+        });
+
+        let max_failures = self.max_failures;
+        let handles: Vec<_> = self
+            .bots
+            .into_iter()
+            .map(|(name, bot)| {
+                let handle = task::spawn(run_bot(name.clone(), bot, max_failures, stopped.clone()));
+                (name, handle)
+            })
+            .collect();
+
+        let mut exits = Vec::new();
+        for (name, handle) in handles {
+            let exit = match handle.await {
+                Ok(exit) => exit,
+                Err(e) => BotExit::Panicked(e.to_string()),
+            };
+            match &exit {
+                BotExit::Stopped(_) => info!("[{}] {}", name, exit),
+                _ => error!("[{}] {}", name, exit),
+            }
+            exits.push((name, exit));
+        }
+        exits
+    }
+}
+
+/// Trading cycles of one bot until `max_failures` of them fail in a row, or until `stop`.
+async fn run_bot(
+    name: String,
+    mut bot: TradingBot,
+    max_failures: u32,
+    mut stop: watch::Receiver<bool>,
+) -> BotExit {
+    let mut interval = time::interval(Duration::from_secs(bot.trading_config.trading_cadence));
+    let mut failures = 0;
+    loop {
+        tokio::select! {
+            // Stop first, a tick already due must not start one more cycle.
+            biased;
+            // A closed channel means nobody can ask to stop any more.
+            Ok(()) = stop.changed() => {
+                info!("[{}] shutting down", name);
+                return BotExit::Stopped(Box::new(bot.shutdown().await));
+            }
+            _ = interval.tick() => {}
+        }
+
+        let start = Instant::now();
+        match bot.start().await {
+            Ok(()) => {
+                failures = 0;
+                info!("[{}] cycle done in {:?}", name, start.elapsed());
+            }
+            Err(e) => {
+                failures += 1;
+                warn!(
+                    "[{}] cycle failed ({}/{}): {}",
+                    name, failures, max_failures, e
+                );
+                if failures >= max_failures {
+                    return BotExit::Failed(e.to_string());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tradingbot::{
+        candle::Candle,
+        market::Market,
+        order::{OpenOrder, OrderRequest},
+        order_book::OrderBook,
+        strategy, TradingConfig,
+    };
+    use async_trait::async_trait;
+    use std::error::Error;
+
+    /// Market whose first call of a cycle fails, or panics.
+    struct BrokenMarket {
+        panics: bool,
+    }
+
+    #[async_trait]
+    impl Market for BrokenMarket {
+        async fn get_balances(&self) -> Result<f32, Box<dyn Error>> {
+            Ok(0.0)
+        }
+
+        async fn get_base_balance(&self, _pair: &str) -> Result<f32, Box<dyn Error>> {
+            Ok(0.0)
+        }
+
+        async fn get_market_price(&self, _pair: &str) -> Result<f32, Box<dyn Error>> {
+            Ok(0.0)
+        }
+
+        async fn get_candles(
+            &self,
+            _pair: &str,
+            _interval: u32,
+        ) -> Result<Vec<Candle>, Box<dyn Error>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_order_book(
+            &self,
+            _pair: &str,
+            _depth: u32,
+        ) -> Result<OrderBook, Box<dyn Error>> {
+            Ok(OrderBook::default())
+        }
+
+        async fn place_order(&self, _order: &OrderRequest) -> Result<String, Box<dyn Error>> {
+            Err("down".into())
+        }
+
+        async fn cancel_order(&self, _order_id: &str) -> Result<(), Box<dyn Error>> {
+            Err("down".into())
+        }
+
+        async fn get_open_orders(&self) -> Result<Vec<OpenOrder>, Box<dyn Error>> {
+            if self.panics {
+                panic!("market crashed");
+            }
+            Err("down".into())
+        }
+    }
+
+    fn bot(panics: bool) -> TradingBot {
+        let config = TradingConfig::default();
+        let strategy = strategy::from_config(&config).unwrap();
+        TradingBot::new(config, Box::new(BrokenMarket { panics }), strategy)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_failures_are_isolated() {
+        let mut supervisor = Supervisor::new(1);
+        supervisor.add("panics", bot(true));
+        supervisor.add("fails", bot(false));
+
+        let exits = supervisor.run(std::future::pending()).await;
+        assert_eq!(exits.len(), 2);
+        assert!(matches!(&exits[0], (name, BotExit::Panicked(_)) if name == "panics"));
+        assert_eq!(
+            exits[1],
+            ("fails".to_owned(), BotExit::Failed("down".to_owned()))
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_shutdown_stops_bots() {
+        let mut supervisor = Supervisor::new(u32::MAX);
+        supervisor.add("fails", bot(false));
+
+        let exits = supervisor.run(time::sleep(Duration::from_millis(50))).await;
+        let summary = match &exits[0].1 {
+            BotExit::Stopped(summary) => summary,
+            other => panic!("unexpected exit {:?}", other),
+        };
+        assert_eq!(summary.position, "Flat");
+        assert_eq!(summary.errors, vec!["open orders: down".to_owned()]);
+    }
+}
@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+
+/// OHLC candle, `time` is the candle open time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candle {
+    pub time: DateTime<Utc>,
+    pub open: f32,
+    pub high: f32,
+    pub low: f32,
+    pub close: f32,
+    pub vwap: f32,
+    pub volume: f32,
+    pub count: u64,
+}
@@ -0,0 +1,237 @@
+use crate::marketdata::Timeframe;
+use serde::{Deserialize, Deserializer};
+use serde_derive::Deserialize;
+use std::{collections::HashMap, error::Error, fmt, str::FromStr};
+
+/// Whether orders reach the exchange or are simulated locally.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TradingMode {
+    Live,
+    Paper,
+}
+
+impl FromStr for TradingMode {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "live" => Ok(TradingMode::Live),
+            "paper" => Ok(TradingMode::Paper),
+            _ => Err(ConfigError(format!("unknown trading mode {}", s))),
+        }
+    }
+}
+
+/// What happens to the bot's orders and position when it is stopped.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ShutdownAction {
+    /// Leave orders resting and the position open.
+    #[default]
+    Keep,
+    /// Cancel the orders the bot placed.
+    Cancel,
+    /// Cancel the orders the bot placed and close its position.
+    Flatten,
+}
+
+/// Trading Config
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct TradingConfig {
+    /// Exchange pair name, e.g. "XXBTZUSD".
+    pub pair: String,
+    /// Base currency volume of each entry.
+    pub order_size: f32,
+    /// Exit in profit once price moved this many percent in our favour, 0 to disable.
+    /// Checked by the strategy each cycle, no exit rests on the exchange.
+    pub take_profit_pct: f32,
+    /// Exit in loss once price moved this many percent against us, 0 to disable.
+    /// Checked by the strategy each cycle, no exit rests on the exchange.
+    pub stop_loss_pct: f32,
+    /// Largest base currency volume the bot may hold.
+    pub max_position: f32,
+    /// Margin leverage of short positions, shorting needs at least 2. Unset to trade long only.
+    pub leverage: Option<u32>,
+    /// Seconds between trading cycles.
+    pub trading_cadence: u64,
+    /// Timeframe of the candles fetched from the market, e.g. "4h".
+    /// Strategies can ask for higher timeframes built from it.
+    pub timeframe: Timeframe,
+    pub strategy: String,
+    /// Strategy specific parameters, read with `TradingConfig::param`.
+    #[serde(deserialize_with = "params_from_scalars")]
+    pub strategy_params: HashMap<String, String>,
+    pub mode: TradingMode,
+    /// Virtual quote currency balance in paper mode.
+    pub paper_balance: f32,
+    /// Validate orders with the market without submitting them.
+    pub dry_run: bool,
+    /// Orders and position handling on SIGINT or SIGTERM.
+    pub on_shutdown: ShutdownAction,
+    /// JSON file the state of the bot is saved to when it stops, and resumed from on start.
+    pub state_file: Option<String>,
+}
+
+impl Default for TradingConfig {
+    fn default() -> Self {
+        TradingConfig {
+            pair: "XXBTZUSD".to_owned(),
+            order_size: 0.001,
+            take_profit_pct: 0.0,
+            stop_loss_pct: 0.0,
+            max_position: 0.01,
+            leverage: None,
+            trading_cadence: 10,
+            timeframe: Timeframe::H4,
+            strategy: "hold".to_owned(),
+            strategy_params: HashMap::new(),
+            mode: TradingMode::Live,
+            paper_balance: 10_000.0,
+            dry_run: false,
+            on_shutdown: ShutdownAction::Keep,
+            state_file: None,
+        }
+    }
+}
+
+impl TradingConfig {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.pair.is_empty() {
+            return Err(ConfigError("pair is required".to_owned()));
+        }
+        if self.order_size <= 0.0 {
+            return Err(ConfigError(format!(
+                "order_size must be positive, got {}",
+                self.order_size
+            )));
+        }
+        if self.max_position < self.order_size {
+            return Err(ConfigError(format!(
+                "max_position {} is below order_size {}",
+                self.max_position, self.order_size
+            )));
+        }
+        for (name, pct) in &[
+            ("take_profit_pct", self.take_profit_pct),
+            ("stop_loss_pct", self.stop_loss_pct),
+        ] {
+            if *pct < 0.0 || *pct >= 100.0 {
+                return Err(ConfigError(format!(
+                    "{} must be within [0, 100), got {}",
+                    name, pct
+                )));
+            }
+        }
+        if let Some(leverage) = self.leverage {
+            if leverage < 2 {
+                return Err(ConfigError(format!(
+                    "leverage must be at least 2, got {}",
+                    leverage
+                )));
+            }
+        }
+        if self.trading_cadence == 0 {
+            return Err(ConfigError(
+                "trading_cadence must be at least 1s".to_owned(),
+            ));
+        }
+        if self.mode == TradingMode::Paper && self.paper_balance <= 0.0 {
+            return Err(ConfigError(format!(
+                "paper_balance must be positive, got {}",
+                self.paper_balance
+            )));
+        }
+        if self.strategy.is_empty() {
+            return Err(ConfigError("strategy is required".to_owned()));
+        }
+        Ok(())
+    }
+
+    /// Strategy parameter `name`, or `default` when not set.
+    pub fn param<T: FromStr>(&self, name: &str, default: T) -> Result<T, ConfigError> {
+        match self.strategy_params.get(name) {
+            Some(value) => value.parse::<T>().map_err(|_| {
+                ConfigError(format!(
+                    "invalid value {} for strategy param {}",
+                    value, name
+                ))
+            }),
+            None => Ok(default),
+        }
+    }
+}
+
+/// Accept numbers and booleans as strategy params, not only strings.
+fn params_from_scalars<'de, D>(deserializer: D) -> Result<HashMap<String, String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let params = HashMap::<String, serde_json::Value>::deserialize(deserializer)?;
+    Ok(params
+        .into_iter()
+        .map(|(name, value)| match value {
+            serde_json::Value::String(s) => (name, s),
+            other => (name, other.to_string()),
+        })
+        .collect())
+}
+
+#[derive(Debug)]
+pub struct ConfigError(pub String);
+
+impl Error for ConfigError {}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid config: {}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_valid() {
+        assert!(TradingConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate() {
+        let config = TradingConfig {
+            order_size: 0.1,
+            max_position: 0.05,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = TradingConfig {
+            stop_loss_pct: 120.0,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = TradingConfig {
+            leverage: Some(1),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_param() {
+        let mut config = TradingConfig::default();
+        config
+            .strategy_params
+            .insert("fast".to_owned(), "12".to_owned());
+        assert_eq!(config.param("fast", 5u32).unwrap(), 12);
+        assert_eq!(config.param("slow", 26u32).unwrap(), 26);
+
+        config
+            .strategy_params
+            .insert("slow".to_owned(), "abc".to_owned());
+        assert!(config.param("slow", 26u32).is_err());
+    }
+}
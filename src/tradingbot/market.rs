@@ -1,10 +1,134 @@
+use super::{
+    candle::Candle,
+    order::{Fill, OpenOrder, OrderRequest},
+    order_book::OrderBook,
+};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::warn;
 use std::error::Error;
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedReceiver;
 
-#[async_trait(?Send)]
-pub trait Market {
+/// Exchange, or a simulation of one, the bot trades on.
+#[async_trait]
+pub trait Market: Send + Sync {
+    /// Quote currency balance, e.g. USD on Kraken.
     async fn get_balances(&self) -> Result<f32, Box<dyn Error>>;
-    async fn get_market_price(&self) -> Result<f32, Box<dyn Error>>;
-    async fn place_sell_order(&self, amount: f32) -> Result<f32, Box<dyn Error>>;
-    async fn place_buy_order(&self, amount: f32) -> Result<f32, Box<dyn Error>>;
+    /// Balance of the base asset of `pair`, e.g. XBT for XXBTZUSD.
+    async fn get_base_balance(&self, pair: &str) -> Result<f32, Box<dyn Error>>;
+    async fn get_market_price(&self, pair: &str) -> Result<f32, Box<dyn Error>>;
+    /// Closed candles of `interval` minutes, oldest first.
+    async fn get_candles(&self, pair: &str, interval: u32) -> Result<Vec<Candle>, Box<dyn Error>>;
+    /// Best `depth` levels on each side of the book.
+    async fn get_order_book(&self, pair: &str, depth: u32) -> Result<OrderBook, Box<dyn Error>>;
+
+    /// Submit an order, returning its id.
+    async fn place_order(&self, order: &OrderRequest) -> Result<String, Box<dyn Error>>;
+    async fn cancel_order(&self, order_id: &str) -> Result<(), Box<dyn Error>>;
+    /// Our orders resting on the market, of every pair.
+    async fn get_open_orders(&self) -> Result<Vec<OpenOrder>, Box<dyn Error>>;
+
+    /// Current time on this market, simulated markets replay their own clock.
+    fn time(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    /// Only validate orders instead of submitting them.
+    fn set_dry_run(&mut self, _dry_run: bool) {}
+
+    /// Push feed of our own executions. Markets without one return `None`.
+    async fn subscribe_fills(&self) -> Result<Option<UnboundedReceiver<Fill>>, Box<dyn Error>> {
+        Ok(None)
+    }
+
+    /// Ids of our orders the market closed without filling them fully since the last call,
+    /// e.g. cancelled for lack of funds. Markets not reporting them return none.
+    fn take_closed_orders(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Keep a streamed book of `pair` to serve prices and order books from.
+    /// Markets without one keep querying on every call.
+    async fn subscribe_book(&self, _pair: &str, _depth: u32) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+/// A market shared between tasks, e.g. a bot and a data feed.
+#[async_trait]
+impl<T: Market + ?Sized> Market for Arc<T> {
+    async fn get_balances(&self) -> Result<f32, Box<dyn Error>> {
+        (**self).get_balances().await
+    }
+
+    async fn get_base_balance(&self, pair: &str) -> Result<f32, Box<dyn Error>> {
+        (**self).get_base_balance(pair).await
+    }
+
+    async fn get_market_price(&self, pair: &str) -> Result<f32, Box<dyn Error>> {
+        (**self).get_market_price(pair).await
+    }
+
+    async fn get_candles(&self, pair: &str, interval: u32) -> Result<Vec<Candle>, Box<dyn Error>> {
+        (**self).get_candles(pair, interval).await
+    }
+
+    async fn get_order_book(&self, pair: &str, depth: u32) -> Result<OrderBook, Box<dyn Error>> {
+        (**self).get_order_book(pair, depth).await
+    }
+
+    async fn place_order(&self, order: &OrderRequest) -> Result<String, Box<dyn Error>> {
+        (**self).place_order(order).await
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<(), Box<dyn Error>> {
+        (**self).cancel_order(order_id).await
+    }
+
+    async fn get_open_orders(&self) -> Result<Vec<OpenOrder>, Box<dyn Error>> {
+        (**self).get_open_orders().await
+    }
+
+    fn time(&self) -> DateTime<Utc> {
+        (**self).time()
+    }
+
+    /// Only applies while the market is not shared yet.
+    fn set_dry_run(&mut self, dry_run: bool) {
+        match Arc::get_mut(self) {
+            Some(market) => market.set_dry_run(dry_run),
+            None => warn!("Market already shared, dry run left unchanged"),
+        }
+    }
+
+    async fn subscribe_fills(&self) -> Result<Option<UnboundedReceiver<Fill>>, Box<dyn Error>> {
+        (**self).subscribe_fills().await
+    }
+
+    fn take_closed_orders(&self) -> Vec<String> {
+        (**self).take_closed_orders()
+    }
+
+    async fn subscribe_book(&self, pair: &str, depth: u32) -> Result<(), Box<dyn Error>> {
+        (**self).subscribe_book(pair, depth).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Binance, Kraken, PaperMarket, TradingBot};
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_markets_are_send_sync() {
+        assert_send_sync::<Kraken>();
+        assert_send_sync::<Binance>();
+        assert_send_sync::<PaperMarket>();
+        assert_send_sync::<Arc<dyn Market>>();
+        assert_send_sync::<Box<dyn Market>>();
+        assert_send_sync::<TradingBot>();
+    }
 }
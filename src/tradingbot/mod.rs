@@ -1,6 +1,20 @@
+pub mod candle;
+pub mod config;
 pub mod market;
+pub mod order;
+pub mod order_book;
+pub mod position;
+pub mod shutdown;
+pub mod strategy;
 pub mod tradingbot;
 pub mod tradingbot_impl_pub;
 
+pub use candle::Candle;
+pub use config::{ConfigError, ShutdownAction, TradingConfig, TradingMode};
 pub use market::Market;
-pub use tradingbot::{TradingBot, TradingConfig};
+pub use order::{ConditionalClose, Fill, OpenOrder, OrderRequest, OrderSide, OrderType, Price};
+pub use order_book::OrderBook;
+pub use position::{ExitReason, PositionState};
+pub use shutdown::ShutdownSummary;
+pub use strategy::{HoldStrategy, Signal, Strategy, TargetPosition, Tick};
+pub use tradingbot::TradingBot;
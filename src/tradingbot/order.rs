@@ -0,0 +1,451 @@
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use std::{error::Error, fmt};
+
+/// Prefix of the synthetic ids markets return for validate-only orders.
+pub const DRY_RUN_ORDER_PREFIX: &str = "DRYRUN-";
+
+/// Order side
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+impl From<OrderSide> for &str {
+    fn from(s: OrderSide) -> Self {
+        match s {
+            OrderSide::Buy => "buy",
+            OrderSide::Sell => "sell",
+        }
+    }
+}
+
+/// A (partial) execution of one of our orders, as reported by the market.
+#[derive(Debug, Clone)]
+pub struct Fill {
+    pub trade_id: String,
+    pub order_id: String,
+    pub pair: String,
+    pub side: OrderSide,
+    pub price: f32,
+    pub volume: f32,
+    pub fee: f32,
+    pub time: DateTime<Utc>,
+}
+
+/// Order resting on the market.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenOrder {
+    pub id: String,
+    /// Pair, named as in the config.
+    pub pair: String,
+    pub side: OrderSide,
+    /// Limit or trigger price, 0 for market orders.
+    pub price: f32,
+    pub volume: f32,
+    pub volume_executed: f32,
+    pub userref: Option<i32>,
+}
+
+/// Order type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderType {
+    Market,
+    Limit,
+    StopLoss,
+    TakeProfit,
+    StopLossLimit,
+    TakeProfitLimit,
+    TrailingStop,
+    TrailingStopLimit,
+    SettlePosition,
+}
+
+impl From<OrderType> for &str {
+    fn from(t: OrderType) -> Self {
+        match t {
+            OrderType::Market => "market",
+            OrderType::Limit => "limit",
+            OrderType::StopLoss => "stop-loss",
+            OrderType::TakeProfit => "take-profit",
+            OrderType::StopLossLimit => "stop-loss-limit",
+            OrderType::TakeProfitLimit => "take-profit-limit",
+            OrderType::TrailingStop => "trailing-stop",
+            OrderType::TrailingStopLimit => "trailing-stop-limit",
+            OrderType::SettlePosition => "settle-position",
+        }
+    }
+}
+
+impl OrderType {
+    /// Whether `price` must be set.
+    pub fn needs_price(self) -> bool {
+        !matches!(self, OrderType::Market | OrderType::SettlePosition)
+    }
+
+    /// Whether `price2` must be set.
+    pub fn needs_price2(self) -> bool {
+        matches!(
+            self,
+            OrderType::StopLossLimit | OrderType::TakeProfitLimit | OrderType::TrailingStopLimit
+        )
+    }
+}
+
+/// Order price, either absolute or relative to the last traded price.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Price {
+    Absolute(f32),
+    /// Signed offset in quote currency, e.g. `Offset(-50.0)` is "-50".
+    Offset(f32),
+    /// Signed offset in percent, e.g. `Percent(2.5)` is "+2.5%".
+    Percent(f32),
+}
+
+impl Price {
+    /// Absolute price, offsets are taken relative to `reference`.
+    pub fn resolve(self, reference: f32) -> f32 {
+        match self {
+            Price::Absolute(p) => p,
+            Price::Offset(offset) => reference + offset,
+            Price::Percent(pct) => reference * (1.0 + pct / 100.0),
+        }
+    }
+
+    /// The price rounded to `decimals`, percentages are left to the market.
+    pub fn with_decimals(self, decimals: u32) -> Self {
+        let unit = 10f32.powi(decimals as i32);
+        match self {
+            Price::Absolute(p) => Price::Absolute((p * unit).round() / unit),
+            Price::Offset(p) => Price::Offset((p * unit).round() / unit),
+            Price::Percent(_) => self,
+        }
+    }
+}
+
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Price::Absolute(p) => write!(f, "{}", p),
+            Price::Offset(p) => write!(f, "{}{}", sign(*p), p.abs()),
+            Price::Percent(p) => write!(f, "{}{}%", sign(*p), p.abs()),
+        }
+    }
+}
+
+fn sign(v: f32) -> &'static str {
+    if v < 0.0 {
+        "-"
+    } else {
+        "+"
+    }
+}
+
+/// Order flags
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderFlag {
+    /// Post-only, the order is cancelled rather than taking liquidity.
+    PostOnly,
+    /// Prefer fee in base currency.
+    FeeInBase,
+    /// Prefer fee in quote currency.
+    FeeInQuote,
+    /// Disable market price protection for market orders.
+    NoMarketPriceProtection,
+}
+
+impl From<OrderFlag> for &str {
+    fn from(f: OrderFlag) -> Self {
+        match f {
+            OrderFlag::PostOnly => "post",
+            OrderFlag::FeeInBase => "fcib",
+            OrderFlag::FeeInQuote => "fciq",
+            OrderFlag::NoMarketPriceProtection => "nompp",
+        }
+    }
+}
+
+/// Time in force
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeInForce {
+    GoodTillCancelled,
+    ImmediateOrCancel,
+    GoodTillDate,
+}
+
+impl From<TimeInForce> for &str {
+    fn from(t: TimeInForce) -> Self {
+        match t {
+            TimeInForce::GoodTillCancelled => "GTC",
+            TimeInForce::ImmediateOrCancel => "IOC",
+            TimeInForce::GoodTillDate => "GTD",
+        }
+    }
+}
+
+/// Scheduled start or expiration time of an order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderTime {
+    Now,
+    /// Seconds from now.
+    In(u64),
+    At(DateTime<Utc>),
+}
+
+impl fmt::Display for OrderTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrderTime::Now => write!(f, "0"),
+            OrderTime::In(secs) => write!(f, "+{}", secs),
+            OrderTime::At(time) => write!(f, "{}", time.timestamp()),
+        }
+    }
+}
+
+/// Exit order the market places on our behalf once the order it is attached to fills.
+/// It lives on the exchange, so it survives the bot going away.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConditionalClose {
+    pub order_type: OrderType,
+    pub price: Price,
+    pub price2: Option<Price>,
+}
+
+impl ConditionalClose {
+    pub fn take_profit(price: Price) -> Self {
+        ConditionalClose {
+            order_type: OrderType::TakeProfit,
+            price,
+            price2: None,
+        }
+    }
+
+    pub fn stop_loss(price: Price) -> Self {
+        ConditionalClose {
+            order_type: OrderType::StopLoss,
+            price,
+            price2: None,
+        }
+    }
+}
+
+/// Order to submit to a market.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderRequest {
+    pub side: OrderSide,
+    pub pair: String,
+    pub volume: f32,
+    pub order_type: OrderType,
+    pub price: Option<Price>,
+    pub price2: Option<Price>,
+    pub leverage: Option<u32>,
+    pub flags: Vec<OrderFlag>,
+    pub time_in_force: Option<TimeInForce>,
+    pub start_time: Option<OrderTime>,
+    pub expire_time: Option<OrderTime>,
+    pub userref: Option<i32>,
+    /// Kraken accepts a single conditional close per order. Only strategies attach one, through
+    /// `Signal::Order`, the bot's own entries leave exits to the strategy.
+    pub close: Option<ConditionalClose>,
+}
+
+impl OrderRequest {
+    pub fn market(side: OrderSide, pair: &str, volume: f32) -> Self {
+        OrderRequest {
+            side,
+            pair: pair.to_owned(),
+            volume,
+            order_type: OrderType::Market,
+            price: None,
+            price2: None,
+            leverage: None,
+            flags: Vec::new(),
+            time_in_force: None,
+            start_time: None,
+            expire_time: None,
+            userref: None,
+            close: None,
+        }
+    }
+
+    pub fn limit(side: OrderSide, pair: &str, volume: f32, price: f32) -> Self {
+        OrderRequest {
+            order_type: OrderType::Limit,
+            price: Some(Price::Absolute(price)),
+            ..OrderRequest::market(side, pair, volume)
+        }
+    }
+
+    /// Attach a take-profit exit at `price`.
+    pub fn with_take_profit(self, price: Price) -> Self {
+        OrderRequest {
+            close: Some(ConditionalClose::take_profit(price)),
+            ..self
+        }
+    }
+
+    /// Attach a stop-loss exit at `price`.
+    pub fn with_stop_loss(self, price: Price) -> Self {
+        OrderRequest {
+            close: Some(ConditionalClose::stop_loss(price)),
+            ..self
+        }
+    }
+
+    /// The order with its volume rounded down to `lot_decimals`, exchanges reject finer volumes.
+    pub fn with_lot_decimals(self, lot_decimals: u32) -> Self {
+        OrderRequest {
+            volume: round_down(self.volume, lot_decimals),
+            ..self
+        }
+    }
+
+    /// The order with its prices rounded to `price_decimals`, exchanges reject finer prices.
+    pub fn with_price_decimals(self, price_decimals: u32) -> Self {
+        let round = |price: Option<Price>| price.map(|p| p.with_decimals(price_decimals));
+        OrderRequest {
+            price: round(self.price),
+            price2: round(self.price2),
+            close: self.close.map(|close| ConditionalClose {
+                price: close.price.with_decimals(price_decimals),
+                price2: round(close.price2),
+                ..close
+            }),
+            ..self
+        }
+    }
+
+    /// Check the fields required by the order type are set.
+    pub fn validate(&self) -> Result<(), InvalidOrder> {
+        if self.volume <= 0.0 {
+            return Err(InvalidOrder(format!(
+                "volume must be positive, got {}",
+                self.volume
+            )));
+        }
+        if self.order_type.needs_price() && self.price.is_none() {
+            return Err(InvalidOrder(format!(
+                "{:?} order requires a price",
+                self.order_type
+            )));
+        }
+        if self.order_type.needs_price2() && self.price2.is_none() {
+            return Err(InvalidOrder(format!(
+                "{:?} order requires price2",
+                self.order_type
+            )));
+        }
+        if self.time_in_force == Some(TimeInForce::GoodTillDate) && self.expire_time.is_none() {
+            return Err(InvalidOrder("GTD order requires an expire time".to_owned()));
+        }
+        if let Some(close) = &self.close {
+            if !close.order_type.needs_price() {
+                return Err(InvalidOrder(format!(
+                    "{:?} cannot be used as a conditional close",
+                    close.order_type
+                )));
+            }
+            if close.order_type.needs_price2() && close.price2.is_none() {
+                return Err(InvalidOrder(format!(
+                    "{:?} conditional close requires price2",
+                    close.order_type
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `value` rounded up to `decimals`.
+pub fn round_up(value: f32, decimals: u32) -> f32 {
+    let unit = 10f32.powi(decimals as i32);
+    (value * unit).ceil() / unit
+}
+
+/// `value` rounded down to `decimals`.
+pub fn round_down(value: f32, decimals: u32) -> f32 {
+    let unit = 10f32.powi(decimals as i32);
+    (value * unit).floor() / unit
+}
+
+/// Rejected before reaching the market.
+#[derive(Debug)]
+pub struct InvalidOrder(pub String);
+
+impl Error for InvalidOrder {}
+
+impl fmt::Display for InvalidOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid order: {}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_format() {
+        assert_eq!(Price::Absolute(27500.5).to_string(), "27500.5");
+        assert_eq!(Price::Offset(-50.0).to_string(), "-50");
+        assert_eq!(Price::Offset(50.0).to_string(), "+50");
+        assert_eq!(Price::Percent(2.5).to_string(), "+2.5%");
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(OrderRequest::market(OrderSide::Buy, "XXBTZUSD", 0.1)
+            .validate()
+            .is_ok());
+        assert!(
+            OrderRequest::limit(OrderSide::Buy, "XXBTZUSD", 0.1, 20000.0)
+                .validate()
+                .is_ok()
+        );
+
+        let stop_limit = OrderRequest {
+            order_type: OrderType::StopLossLimit,
+            ..OrderRequest::limit(OrderSide::Sell, "XXBTZUSD", 0.1, 20000.0)
+        };
+        assert!(stop_limit.validate().is_err());
+
+        let empty = OrderRequest::market(OrderSide::Buy, "XXBTZUSD", 0.0);
+        assert!(empty.validate().is_err());
+
+        let bracket = OrderRequest::market(OrderSide::Buy, "XXBTZUSD", 0.1)
+            .with_stop_loss(Price::Percent(-2.0));
+        assert!(bracket.validate().is_ok());
+
+        let market_close = OrderRequest {
+            close: Some(ConditionalClose {
+                order_type: OrderType::Market,
+                price: Price::Absolute(0.0),
+                price2: None,
+            }),
+            ..OrderRequest::market(OrderSide::Buy, "XXBTZUSD", 0.1)
+        };
+        assert!(market_close.validate().is_err());
+    }
+
+    #[test]
+    fn test_lot_decimals() {
+        let order = OrderRequest::market(OrderSide::Buy, "XXBTZUSD", 100.0 / 30123.45);
+        assert_eq!(order.with_lot_decimals(8).volume, 0.00331967);
+
+        let dust = OrderRequest::market(OrderSide::Buy, "XXBTZUSD", 0.000_000_004);
+        assert!(dust.with_lot_decimals(8).validate().is_err());
+    }
+
+    #[test]
+    fn test_price_decimals() {
+        let order = OrderRequest::limit(OrderSide::Buy, "XXBTZUSD", 0.01, 29_123.456)
+            .with_stop_loss(Price::Offset(-50.04))
+            .with_price_decimals(1);
+        assert_eq!(order.price, Some(Price::Absolute(29_123.5)));
+        assert_eq!(order.close.unwrap().price, Price::Offset(-50.0));
+
+        let percent = Price::Percent(-2.345);
+        assert_eq!(percent.with_decimals(1), percent);
+    }
+}
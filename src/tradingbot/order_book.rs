@@ -0,0 +1,98 @@
+use super::order::OrderSide;
+
+/// Snapshot of the best price levels of a pair, as `(price, volume)`.
+/// Asks are sorted ascending and bids descending, best level first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrderBook {
+    pub asks: Vec<(f32, f32)>,
+    pub bids: Vec<(f32, f32)>,
+}
+
+impl OrderBook {
+    pub fn best_ask(&self) -> Option<f32> {
+        self.asks.first().map(|(price, _)| *price)
+    }
+
+    pub fn best_bid(&self) -> Option<f32> {
+        self.bids.first().map(|(price, _)| *price)
+    }
+
+    pub fn mid_price(&self) -> Option<f32> {
+        Some((self.best_ask()? + self.best_bid()?) / 2.0)
+    }
+
+    /// Average price of taking `volume` on `side`, only using levels within `limit`.
+    /// `None` when the book does not hold enough volume.
+    pub fn fill_price(&self, side: OrderSide, volume: f32, limit: Option<f32>) -> Option<f32> {
+        let levels = match side {
+            OrderSide::Buy => &self.asks,
+            OrderSide::Sell => &self.bids,
+        };
+
+        let mut remaining = volume;
+        let mut cost = 0.0;
+        for (price, available) in levels {
+            let within = match (side, limit) {
+                (_, None) => true,
+                (OrderSide::Buy, Some(limit)) => *price <= limit,
+                (OrderSide::Sell, Some(limit)) => *price >= limit,
+            };
+            if !within {
+                break;
+            }
+
+            let taken = remaining.min(*available);
+            cost += taken * price;
+            remaining -= taken;
+            if remaining <= f32::EPSILON {
+                return Some(cost / volume);
+            }
+        }
+        None
+    }
+
+    /// Price of the last level reached when taking `volume` on `side`, the limit an
+    /// immediate-or-cancel order needs to fill whole. `None` when the book is too thin.
+    pub fn sweep_price(&self, side: OrderSide, volume: f32) -> Option<f32> {
+        let levels = match side {
+            OrderSide::Buy => &self.asks,
+            OrderSide::Sell => &self.bids,
+        };
+
+        let mut remaining = volume;
+        for (price, available) in levels {
+            remaining -= available;
+            if remaining <= f32::EPSILON {
+                return Some(*price);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_price() {
+        let book = OrderBook {
+            asks: vec![(101.0, 1.0), (102.0, 2.0)],
+            bids: vec![(100.0, 1.0), (99.0, 1.0)],
+        };
+
+        assert_eq!(book.mid_price(), Some(100.5));
+        assert_eq!(book.fill_price(OrderSide::Buy, 0.5, None), Some(101.0));
+        assert_eq!(book.fill_price(OrderSide::Buy, 2.0, None), Some(101.5));
+        assert_eq!(book.fill_price(OrderSide::Buy, 2.0, Some(101.0)), None);
+        assert_eq!(
+            book.fill_price(OrderSide::Sell, 2.0, Some(99.0)),
+            Some(99.5)
+        );
+        assert_eq!(book.fill_price(OrderSide::Sell, 3.0, None), None);
+
+        assert_eq!(book.sweep_price(OrderSide::Buy, 1.0), Some(101.0));
+        assert_eq!(book.sweep_price(OrderSide::Buy, 1.5), Some(102.0));
+        assert_eq!(book.sweep_price(OrderSide::Sell, 2.5), None);
+    }
+}
@@ -0,0 +1,285 @@
+use super::order::{Fill, OrderSide};
+use serde_derive::{Deserialize, Serialize};
+use std::{error::Error, fmt};
+
+/// Fraction of the ordered volume left unfilled under which an order counts as done.
+const FILL_TOLERANCE: f32 = 1e-6;
+
+/// Why a position is exited.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ExitReason {
+    Profit,
+    Loss,
+}
+
+/// Lifecycle of the bot's position. A position is entered and exited through orders,
+/// only fills move it from one state to the next.
+///
+/// Flat -> PendingEntry -> Open -> PendingExit -> Flat
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub enum PositionState {
+    #[default]
+    Flat,
+    PendingEntry {
+        side: OrderSide,
+        order_id: String,
+        volume: f32,
+        filled: f32,
+        cost: f32,
+    },
+    Open {
+        side: OrderSide,
+        volume: f32,
+        entry_price: f32,
+    },
+    PendingExit {
+        side: OrderSide,
+        order_id: String,
+        volume: f32,
+        entry_price: f32,
+        filled: f32,
+        reason: ExitReason,
+    },
+}
+
+/// Transition not allowed from the current state.
+#[derive(Debug)]
+pub struct InvalidTransition(pub String);
+
+impl Error for InvalidTransition {}
+
+impl fmt::Display for InvalidTransition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid position transition: {}", self.0)
+    }
+}
+
+impl PositionState {
+    /// Side of the position held or being entered/exited.
+    pub fn side(&self) -> Option<OrderSide> {
+        match self {
+            PositionState::Flat => None,
+            PositionState::PendingEntry { side, .. }
+            | PositionState::Open { side, .. }
+            | PositionState::PendingExit { side, .. } => Some(*side),
+        }
+    }
+
+    /// Whether an order is waiting for fills.
+    pub fn is_pending(&self) -> bool {
+        matches!(
+            self,
+            PositionState::PendingEntry { .. } | PositionState::PendingExit { .. }
+        )
+    }
+
+    /// Check an entry order may be sent.
+    pub fn can_enter(&self) -> Result<(), InvalidTransition> {
+        match self {
+            PositionState::Flat => Ok(()),
+            other => Err(InvalidTransition(format!("cannot enter from {:?}", other))),
+        }
+    }
+
+    /// Check an exit order may be sent.
+    pub fn can_exit(&self) -> Result<(), InvalidTransition> {
+        match self {
+            PositionState::Open { .. } => Ok(()),
+            other => Err(InvalidTransition(format!("cannot exit from {:?}", other))),
+        }
+    }
+
+    /// Record the entry order sent for `volume`.
+    pub fn begin_entry(
+        &mut self,
+        side: OrderSide,
+        order_id: &str,
+        volume: f32,
+    ) -> Result<(), InvalidTransition> {
+        self.can_enter()?;
+        *self = PositionState::PendingEntry {
+            side,
+            order_id: order_id.to_owned(),
+            volume,
+            filled: 0.0,
+            cost: 0.0,
+        };
+        Ok(())
+    }
+
+    /// Record the exit order sent for the whole position.
+    pub fn begin_exit(
+        &mut self,
+        order_id: &str,
+        reason: ExitReason,
+    ) -> Result<(), InvalidTransition> {
+        self.can_exit()?;
+        if let PositionState::Open {
+            side,
+            volume,
+            entry_price,
+        } = *self
+        {
+            *self = PositionState::PendingExit {
+                side,
+                order_id: order_id.to_owned(),
+                volume,
+                entry_price,
+                filled: 0.0,
+                reason,
+            };
+        }
+        Ok(())
+    }
+
+    /// Apply a fill. Fills of orders other than the pending one are ignored.
+    /// Returns whether the fill belonged to the position.
+    pub fn on_fill(&mut self, fill: &Fill) -> bool {
+        match self {
+            PositionState::PendingEntry {
+                side,
+                order_id,
+                volume,
+                filled,
+                cost,
+            } if *order_id == fill.order_id => {
+                *filled += fill.volume;
+                *cost += fill.volume * fill.price;
+                if *filled >= *volume * (1.0 - FILL_TOLERANCE) {
+                    *self = PositionState::Open {
+                        side: *side,
+                        volume: *filled,
+                        entry_price: *cost / *filled,
+                    };
+                }
+                true
+            }
+            PositionState::PendingExit {
+                order_id,
+                volume,
+                filled,
+                ..
+            } if *order_id == fill.order_id => {
+                *filled += fill.volume;
+                if *filled >= *volume * (1.0 - FILL_TOLERANCE) {
+                    *self = PositionState::Flat;
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The pending order was cancelled or expired: keep whatever was filled.
+    pub fn on_order_closed(&mut self, closed_order_id: &str) {
+        match self {
+            PositionState::PendingEntry {
+                side,
+                order_id,
+                filled,
+                cost,
+                ..
+            } if order_id == closed_order_id => {
+                *self = if *filled > 0.0 {
+                    PositionState::Open {
+                        side: *side,
+                        volume: *filled,
+                        entry_price: *cost / *filled,
+                    }
+                } else {
+                    PositionState::Flat
+                };
+            }
+            PositionState::PendingExit {
+                side,
+                order_id,
+                volume,
+                entry_price,
+                filled,
+                ..
+            } if order_id == closed_order_id => {
+                *self = PositionState::Open {
+                    side: *side,
+                    volume: *volume - *filled,
+                    entry_price: *entry_price,
+                };
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn fill(order_id: &str, volume: f32, price: f32) -> Fill {
+        Fill {
+            trade_id: "T".to_owned(),
+            order_id: order_id.to_owned(),
+            pair: "XXBTZUSD".to_owned(),
+            side: OrderSide::Buy,
+            price,
+            volume,
+            fee: 0.0,
+            time: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_long_lifecycle() {
+        let mut position = PositionState::Flat;
+        position.begin_entry(OrderSide::Buy, "O1", 1.0).unwrap();
+        assert!(position.can_enter().is_err());
+        assert!(position.can_exit().is_err());
+
+        assert!(position.on_fill(&fill("O1", 0.5, 100.0)));
+        assert!(position.is_pending());
+        position.on_fill(&fill("O1", 0.5, 200.0));
+        assert_eq!(
+            position,
+            PositionState::Open {
+                side: OrderSide::Buy,
+                volume: 1.0,
+                entry_price: 150.0
+            }
+        );
+
+        position.begin_exit("O2", ExitReason::Profit).unwrap();
+        assert!(!position.on_fill(&fill("O1", 1.0, 160.0)));
+        position.on_fill(&fill("O2", 1.0, 160.0));
+        assert_eq!(position, PositionState::Flat);
+    }
+
+    #[test]
+    fn test_no_double_entry_or_exit_when_flat() {
+        let mut position = PositionState::Flat;
+        assert!(position.begin_exit("O1", ExitReason::Loss).is_err());
+
+        position.begin_entry(OrderSide::Sell, "O1", 1.0).unwrap();
+        assert!(position.begin_entry(OrderSide::Sell, "O2", 1.0).is_err());
+    }
+
+    #[test]
+    fn test_cancelled_orders() {
+        let mut position = PositionState::Flat;
+        position.begin_entry(OrderSide::Sell, "O1", 1.0).unwrap();
+        position.on_order_closed("O1");
+        assert_eq!(position, PositionState::Flat);
+
+        position.begin_entry(OrderSide::Sell, "O2", 1.0).unwrap();
+        position.on_fill(&fill("O2", 0.4, 100.0));
+        position.on_order_closed("O2");
+        position.begin_exit("O3", ExitReason::Loss).unwrap();
+        position.on_order_closed("O3");
+        assert_eq!(
+            position,
+            PositionState::Open {
+                side: OrderSide::Sell,
+                volume: 0.4,
+                entry_price: 100.0
+            }
+        );
+    }
+}
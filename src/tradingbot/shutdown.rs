@@ -0,0 +1,276 @@
+use super::{
+    config::ShutdownAction, position::PositionState, strategy::TargetPosition,
+    tradingbot::TradingBot,
+};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde_derive::{Deserialize, Serialize};
+use std::{error::Error, fmt, fs, io};
+
+/// Where a bot stopped, printed on shutdown and saved to `state_file`, which the bot resumes
+/// from on its next start.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ShutdownSummary {
+    /// RFC 3339 time of the shutdown.
+    pub time: String,
+    pub pair: String,
+    pub strategy: String,
+    /// Position after the shutdown action, e.g. "Flat".
+    pub position: String,
+    pub balance: Option<f32>,
+    pub base_balance: Option<f32>,
+    /// Ids of the orders cancelled.
+    pub cancelled: Vec<String>,
+    /// Id of the order closing the position, when flattening.
+    pub flatten_order: Option<String>,
+    /// Ids of our orders left resting on the market.
+    pub open_orders: Vec<String>,
+    /// Steps of the shutdown that failed.
+    pub errors: Vec<String>,
+    /// Position to resume from.
+    #[serde(default)]
+    pub state: PositionState,
+}
+
+impl fmt::Display for ShutdownSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let amount = |amount: Option<f32>| match amount {
+            Some(amount) => amount.to_string(),
+            None => "unknown".to_owned(),
+        };
+        write!(
+            f,
+            "{} {}: position {}, balance {} quote and {} base, {} orders cancelled, {} left open",
+            self.pair,
+            self.strategy,
+            self.position,
+            amount(self.balance),
+            amount(self.base_balance),
+            self.cancelled.len(),
+            self.open_orders.len()
+        )?;
+        if let Some(order_id) = &self.flatten_order {
+            write!(f, ", closed by {}", order_id)?;
+        }
+        for error in &self.errors {
+            write!(f, "\n  failed: {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl TradingBot {
+    /// Stop trading: cancel our orders and close the position as `on_shutdown` asks, then save
+    /// the state to `state_file`. Failed steps are reported in the summary, not returned.
+    pub async fn shutdown(&mut self) -> ShutdownSummary {
+        let action = self.trading_config.on_shutdown;
+        let mut summary = ShutdownSummary {
+            time: self.market.time().to_rfc3339(),
+            pair: self.trading_config.pair.clone(),
+            strategy: self.strategy.name().to_owned(),
+            position: String::new(),
+            balance: None,
+            base_balance: None,
+            cancelled: Vec::new(),
+            flatten_order: None,
+            open_orders: Vec::new(),
+            errors: Vec::new(),
+            state: PositionState::Flat,
+        };
+
+        // Fills of the last cycle still move the position, the strategy has no say any more.
+        self.process_fills().await;
+
+        let open_orders = match self.market.get_open_orders().await {
+            Ok(open_orders) => open_orders,
+            Err(e) => {
+                summary.errors.push(format!("open orders: {}", e));
+                Vec::new()
+            }
+        };
+        let ours: Vec<_> = open_orders
+            .into_iter()
+            .filter(|order| self.orders.contains(&order.id))
+            .collect();
+        for order in ours {
+            if action == ShutdownAction::Keep {
+                summary.open_orders.push(order.id);
+                continue;
+            }
+            match self.market.cancel_order(&order.id).await {
+                Ok(()) => {
+                    self.position.on_order_closed(&order.id);
+                    summary.cancelled.push(order.id);
+                }
+                Err(e) => {
+                    summary.errors.push(format!("cancel {}: {}", order.id, e));
+                    summary.open_orders.push(order.id);
+                }
+            }
+        }
+
+        if action == ShutdownAction::Flatten {
+            if let Err(e) = self.flatten(&mut summary).await {
+                summary.errors.push(format!("flatten: {}", e));
+            }
+        }
+
+        match self.market.get_balances().await {
+            Ok(balance) => summary.balance = Some(balance),
+            Err(e) => summary.errors.push(format!("balance: {}", e)),
+        }
+        match self.market.get_base_balance(&summary.pair).await {
+            Ok(balance) => summary.base_balance = Some(balance),
+            Err(e) => summary.errors.push(format!("base balance: {}", e)),
+        }
+        summary.position = format!("{:?}", self.position);
+        summary.state = self.position.clone();
+
+        if let Some(path) = self.trading_config.state_file.clone() {
+            match save_state(&path, &summary) {
+                Ok(()) => info!("State saved to {}", path),
+                Err(e) => summary.errors.push(format!("save {}: {}", path, e)),
+            }
+        }
+        summary
+    }
+
+    /// Resume from the state saved to `state_file` by the last shutdown of the pair, if any:
+    /// the position and the orders left resting are ours again.
+    pub(crate) fn restore_state(&mut self) {
+        let path = match &self.trading_config.state_file {
+            Some(path) => path.clone(),
+            None => return,
+        };
+        let summary = match load_state(&path) {
+            Ok(Some(summary)) => summary,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Unable to restore the state from {}: {}", path, e);
+                return;
+            }
+        };
+        if summary.pair != self.trading_config.pair {
+            warn!("{} holds the state of {}, not restored", path, summary.pair);
+            return;
+        }
+
+        self.orders.extend(summary.open_orders);
+        self.orders.extend(summary.flatten_order);
+        match &summary.state {
+            PositionState::PendingEntry { order_id, .. }
+            | PositionState::PendingExit { order_id, .. } => {
+                self.orders.insert(order_id.clone());
+            }
+            _ => {}
+        }
+        self.position = summary.state;
+        match DateTime::parse_from_rfc3339(&summary.time) {
+            Ok(time) => self.resumed_from = Some(time.with_timezone(&Utc)),
+            Err(e) => warn!("Invalid shutdown time in {}: {}", path, e),
+        }
+        info!(
+            "Resumed from {}: position {:?}, {} orders",
+            path,
+            self.position,
+            self.orders.len()
+        );
+    }
+
+    /// Close the open position with a market order.
+    async fn flatten(&mut self, summary: &mut ShutdownSummary) -> Result<(), Box<dyn Error>> {
+        if let PositionState::PendingEntry { .. } | PositionState::PendingExit { .. } =
+            self.position
+        {
+            warn!(
+                "Position {:?} still waiting on its order, not flattened",
+                self.position
+            );
+            return Ok(());
+        }
+        if self.position == PositionState::Flat {
+            return Ok(());
+        }
+
+        let price = self
+            .market
+            .get_market_price(&self.trading_config.pair)
+            .await?;
+        self.move_to(TargetPosition::Flat, price).await?;
+        if let PositionState::PendingExit { order_id, .. } = &self.position {
+            summary.flatten_order = Some(order_id.clone());
+        }
+        Ok(())
+    }
+}
+
+fn save_state(path: &str, summary: &ShutdownSummary) -> Result<(), Box<dyn Error>> {
+    fs::write(path, serde_json::to_string_pretty(summary)?)?;
+    Ok(())
+}
+
+/// State saved by `save_state`, none when the file does not exist yet.
+fn load_state(path: &str) -> Result<Option<ShutdownSummary>, Box<dyn Error>> {
+    match fs::read_to_string(path) {
+        Ok(json) => Ok(Some(serde_json::from_str(&json)?)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::{simulated_market::SimState, SimConfig, SimulatedMarket};
+    use crate::tradingbot::{order::OrderSide, position::ExitReason, strategy, TradingConfig};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_restore_state() {
+        let path = std::env::temp_dir().join(format!("trade_bot_state_{}", std::process::id()));
+        let path = path.to_str().unwrap().to_owned();
+        let config = TradingConfig {
+            state_file: Some(path.clone()),
+            ..TradingConfig::default()
+        };
+        let bot = || {
+            let market = SimulatedMarket::new(Arc::new(Mutex::new(SimState::new(
+                SimConfig::default(),
+                Vec::new(),
+            ))));
+            let strategy = strategy::from_config(&config).unwrap();
+            TradingBot::new(config.clone(), Box::new(market), strategy)
+        };
+
+        assert_eq!(bot().position, PositionState::Flat);
+
+        let state = PositionState::PendingExit {
+            side: OrderSide::Buy,
+            order_id: "EXIT".to_owned(),
+            volume: 1.0,
+            entry_price: 100.0,
+            filled: 0.5,
+            reason: ExitReason::Profit,
+        };
+        let summary = ShutdownSummary {
+            time: "2021-01-02T03:04:05+00:00".to_owned(),
+            pair: config.pair.clone(),
+            strategy: "hold".to_owned(),
+            position: format!("{:?}", state),
+            balance: None,
+            base_balance: None,
+            cancelled: Vec::new(),
+            flatten_order: None,
+            open_orders: vec!["RESTING".to_owned()],
+            errors: Vec::new(),
+            state: state.clone(),
+        };
+        save_state(&path, &summary).unwrap();
+
+        let bot = bot();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(bot.position, state);
+        assert!(bot.orders.contains("EXIT") && bot.orders.contains("RESTING"));
+        assert_eq!(bot.resumed_from.unwrap().timestamp(), 1609556645);
+    }
+}
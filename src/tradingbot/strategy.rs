@@ -0,0 +1,118 @@
+use super::{
+    candle::Candle,
+    config::{ConfigError, TradingConfig},
+    order::{Fill, OpenOrder, OrderRequest},
+    order_book::OrderBook,
+};
+use crate::marketdata::Timeframe;
+use crate::strategies::{Dca, Grid, MaCrossover, MarketMaker, MeanReversion};
+use chrono::{DateTime, Utc};
+
+/// Market state sampled by `TradingBot` every cycle.
+#[derive(Debug, Clone)]
+pub struct Tick {
+    pub time: DateTime<Utc>,
+    pub price: f32,
+    /// Quote currency balance.
+    pub balance: f32,
+    /// Base currency balance, the inventory.
+    pub base_balance: f32,
+    /// Order book, when the strategy asks for one with `book_depth`.
+    pub book: Option<OrderBook>,
+}
+
+/// Position a strategy wants the bot to hold.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TargetPosition {
+    Flat,
+    /// Long the given volume.
+    Long(f32),
+    /// Short the given volume.
+    Short(f32),
+}
+
+/// What a strategy asks the bot to do.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Signal {
+    /// Move to the target position through the bot's enter/exit orders.
+    Target(TargetPosition),
+    /// Submit an order as is.
+    Order(OrderRequest),
+    /// Cancel a resting order by id.
+    Cancel(String),
+    /// Take over a resting order by id, e.g. left by a previous run, so its fills reach the
+    /// strategy.
+    Adopt(String),
+}
+
+/// Trading logic driven by `TradingBot`. Every hook returns the signals to act on,
+/// hooks a strategy does not need can be left out.
+pub trait Strategy: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Called once per closed candle of the configured timeframe, oldest first.
+    fn on_candle(&mut self, _candle: &Candle) -> Vec<Signal> {
+        Vec::new()
+    }
+
+    /// Higher timeframes to build from the configured one and pass to `on_timeframe_candle`.
+    fn timeframes(&self) -> Vec<Timeframe> {
+        Vec::new()
+    }
+
+    /// Called once per closed candle of each timeframe from `timeframes`,
+    /// after the `on_candle` call of the candle closing it.
+    fn on_timeframe_candle(&mut self, _timeframe: Timeframe, _candle: &Candle) -> Vec<Signal> {
+        Vec::new()
+    }
+
+    /// Levels on each side of the order book to sample into `Tick::book`, none by default.
+    fn book_depth(&self) -> Option<u32> {
+        None
+    }
+
+    /// Called every trading cycle with the latest price.
+    fn on_tick(&mut self, _tick: &Tick) -> Vec<Signal> {
+        Vec::new()
+    }
+
+    /// Called for each execution of one of our orders, placed or adopted by the bot.
+    fn on_fill(&mut self, _fill: &Fill) -> Vec<Signal> {
+        Vec::new()
+    }
+
+    /// Called with the id the market gave to an order sent by `Signal::Order`.
+    fn on_order_placed(&mut self, _order: &OrderRequest, _order_id: &str) {}
+
+    /// Called when the market closed one of our orders without filling it fully,
+    /// e.g. cancelled or expired.
+    fn on_order_closed(&mut self, _order_id: &str) {}
+
+    /// Called once on the first cycle with the orders already resting on the market,
+    /// e.g. left by a previous run.
+    fn on_open_orders(&mut self, _orders: &[OpenOrder]) -> Vec<Signal> {
+        Vec::new()
+    }
+}
+
+/// Never trades, the bot only follows the market.
+pub struct HoldStrategy;
+
+impl Strategy for HoldStrategy {
+    fn name(&self) -> &str {
+        "hold"
+    }
+}
+
+/// Build the strategy named in the config.
+pub fn from_config(config: &TradingConfig) -> Result<Box<dyn Strategy>, ConfigError> {
+    match config.strategy.as_str() {
+        "hold" => Ok(Box::new(HoldStrategy)),
+        "ma_crossover" => Ok(Box::new(MaCrossover::from_config(config)?)),
+        "grid" => Ok(Box::new(Grid::from_config(config)?)),
+        "dca" => Ok(Box::new(Dca::from_config(config)?)),
+        "market_maker" => Ok(Box::new(MarketMaker::from_config(config)?)),
+        "mean_reversion" => Ok(Box::new(MeanReversion::from_config(config)?)),
+        other => Err(ConfigError(format!("unknown strategy {}", other))),
+    }
+}
@@ -1,8 +1,27 @@
-use super::market;
+use super::{
+    config::TradingConfig, market, order::Fill, position::PositionState, strategy::Strategy,
+};
+use crate::marketdata::Resampler;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use tokio::sync::mpsc::UnboundedReceiver;
 
 pub struct TradingBot {
     pub trading_config: TradingConfig,
     pub market: Box<dyn market::Market>,
+    pub strategy: Box<dyn Strategy>,
+    pub fills: Option<UnboundedReceiver<Fill>>,
+    /// Open time of the last candle handed to the strategy.
+    pub last_candle: Option<DateTime<Utc>>,
+    /// Builders of the higher timeframes the strategy asked for.
+    pub resamplers: Vec<Resampler>,
+    /// Whether the strategy was told about the orders resting on the market.
+    pub reconciled: bool,
+    pub position: PositionState,
+    /// Ids of the orders this bot placed or adopted, the ones cancelled on shutdown.
+    pub orders: HashSet<String>,
+    /// Fills simulated for dry run orders, handled on the next cycle.
+    pub dry_run_fills: Vec<Fill>,
+    /// Time of the shutdown the state was restored from, fills until then were handled.
+    pub resumed_from: Option<DateTime<Utc>>,
 }
-
-pub struct TradingConfig {}
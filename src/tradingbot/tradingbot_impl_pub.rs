@@ -1,60 +1,547 @@
+use std::collections::HashSet;
 use std::error::Error;
 
-use log::{debug, info, trace};
+use crate::marketdata::Resampler;
+use futures_util::FutureExt;
+use log::{debug, info, trace, warn};
 
 use super::{
+    config::TradingConfig,
     market::Market,
-    tradingbot::{TradingBot, TradingConfig},
+    order::{Fill, InvalidOrder, OrderRequest, OrderSide, Price, DRY_RUN_ORDER_PREFIX},
+    position::{ExitReason, PositionState},
+    strategy::{Signal, Strategy, TargetPosition, Tick},
+    tradingbot::TradingBot,
 };
 
 impl TradingBot {
     pub async fn start(&mut self) -> Result<(), Box<dyn Error>> {
-        info!("Try to place order");
+        let mut signals = self.process_fills().await;
+
+        if !self.reconciled {
+            let depth = self.strategy.book_depth().unwrap_or(1);
+            if let Err(e) = self
+                .market
+                .subscribe_book(&self.trading_config.pair, depth)
+                .await
+            {
+                warn!("Unable to subscribe to the order book: {}", e);
+            }
+
+            trace!("Getting open orders");
+            let open_orders = self.market.get_open_orders().await?;
+            signals.extend(self.strategy.on_open_orders(&open_orders));
+            self.reconciled = true;
+        }
+
+        trace!("Getting candles");
+        signals.extend(self.process_candles().await?);
 
         trace!("Getting balances");
         let balance = self.market.get_balances().await?;
         trace!("balance is {}", balance);
+        let base_balance = self
+            .market
+            .get_base_balance(&self.trading_config.pair)
+            .await?;
+        trace!("base balance is {}", base_balance);
 
         trace!("Getting market price");
-        let balance = self.market.get_market_price().await?;
+        let price = self
+            .market
+            .get_market_price(&self.trading_config.pair)
+            .await?;
+        trace!("market price is {}", price);
+
+        let book = match self.strategy.book_depth() {
+            Some(depth) => Some(
+                self.market
+                    .get_order_book(&self.trading_config.pair, depth)
+                    .await?,
+            ),
+            None => None,
+        };
+
+        let tick = Tick {
+            time: self.market.time(),
+            price,
+            balance,
+            base_balance,
+            book,
+        };
+        signals.extend(self.strategy.on_tick(&tick));
+
+        for signal in signals {
+            if let Err(e) = self.execute(signal, price).await {
+                warn!("Fail to execute signal: {}", e);
+            }
+        }
 
         Ok(())
     }
 
-    pub fn new(trading_config: TradingConfig, market: Box<dyn Market>) -> Self {
-        TradingBot {
+    pub fn new(
+        trading_config: TradingConfig,
+        mut market: Box<dyn Market>,
+        strategy: Box<dyn Strategy>,
+    ) -> Self {
+        market.set_dry_run(trading_config.dry_run);
+        info!("Trading with strategy {}", strategy.name());
+        let base = trading_config.timeframe;
+        let resamplers = strategy
+            .timeframes()
+            .into_iter()
+            .filter(|timeframe| {
+                let valid = *timeframe > base && timeframe.is_multiple_of(base);
+                if !valid {
+                    warn!(
+                        "Timeframe {} cannot be built from {} candles, ignored",
+                        timeframe, base
+                    );
+                }
+                valid
+            })
+            .map(Resampler::new)
+            .collect();
+        let mut bot = TradingBot {
             trading_config,
             market,
+            strategy,
+            fills: None,
+            last_candle: None,
+            resamplers,
+            reconciled: false,
+            position: PositionState::Flat,
+            orders: HashSet::new(),
+            dry_run_fills: Vec::new(),
+            resumed_from: None,
+        };
+        bot.restore_state();
+        bot
+    }
+
+    /// Handle every fill pushed by the market since the last cycle, then the orders it closed,
+    /// (re)subscribing to the market's fill feed when needed.
+    pub(crate) async fn process_fills(&mut self) -> Vec<Signal> {
+        if self.fills.is_none() {
+            match self.market.subscribe_fills().await {
+                Ok(fills) => self.fills = fills,
+                Err(e) => warn!("Unable to subscribe to fills: {}", e),
+            }
+        }
+
+        let mut received: Vec<Fill> = self.dry_run_fills.drain(..).collect();
+        if let Some(fills) = self.fills.as_mut() {
+            loop {
+                match fills.recv().now_or_never() {
+                    Some(Some(fill)) => received.push(fill),
+                    Some(None) => {
+                        warn!("Fill feed closed, resubscribing next cycle");
+                        self.fills = None;
+                        break;
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        let signals = received
+            .into_iter()
+            .flat_map(|fill| self.on_fill(fill))
+            .collect();
+
+        for order_id in self.market.take_closed_orders() {
+            info!("Order {} closed by the market", order_id);
+            self.orders.remove(&order_id);
+            self.position.on_order_closed(&order_id);
+            self.strategy.on_order_closed(&order_id);
+        }
+        signals
+    }
+
+    /// Dispatch a fill to the position and the strategy. Fills of orders the bot did not place
+    /// or adopt, or on another pair, belong to someone else on the account and are ignored,
+    /// as are fills markets repeat from before the shutdown the bot resumed from.
+    fn on_fill(&mut self, fill: Fill) -> Vec<Signal> {
+        if !self.orders.contains(&fill.order_id) || fill.pair != self.trading_config.pair {
+            debug!("Ignoring fill of foreign order {:?}", fill);
+            return Vec::new();
+        }
+        if self.resumed_from.is_some_and(|time| fill.time <= time) {
+            debug!("Ignoring fill handled before the restart {:?}", fill);
+            return Vec::new();
+        }
+        info!(
+            "[FILL] {:?} {} {} @ {} (order {})",
+            fill.side, fill.volume, fill.pair, fill.price, fill.order_id
+        );
+        debug!("fill detail {:?}", fill);
+        if self.position.on_fill(&fill) {
+            info!("[POSITION] {:?}", self.position);
+        }
+        self.strategy.on_fill(&fill)
+    }
+
+    /// Hand candles closed since the last cycle to the strategy, with the higher timeframe
+    /// candles they close. On the first cycle the whole history warms the strategy up,
+    /// only signals of the latest candle are kept.
+    async fn process_candles(&mut self) -> Result<Vec<Signal>, Box<dyn Error>> {
+        let candles = self
+            .market
+            .get_candles(
+                &self.trading_config.pair,
+                self.trading_config.timeframe.minutes(),
+            )
+            .await?;
+        let warm_up = self.last_candle.is_none();
+        let last_candle = self.last_candle;
+
+        let mut signals = Vec::new();
+        let new_candles = candles
+            .iter()
+            .filter(|c| last_candle.map(|last| c.time > last).unwrap_or(true));
+        for candle in new_candles {
+            let mut candle_signals = self.strategy.on_candle(candle);
+            for resampler in self.resamplers.iter_mut() {
+                for closed in resampler.push_candle(candle, self.trading_config.timeframe) {
+                    candle_signals.extend(
+                        self.strategy
+                            .on_timeframe_candle(resampler.timeframe, &closed),
+                    );
+                }
+            }
+            if warm_up {
+                signals = candle_signals;
+            } else {
+                signals.extend(candle_signals);
+            }
+            self.last_candle = Some(candle.time);
+        }
+
+        Ok(signals)
+    }
+
+    async fn execute(&mut self, signal: Signal, price: f32) -> Result<(), Box<dyn Error>> {
+        debug!("Executing {:?}", signal);
+        match signal {
+            Signal::Order(order) => {
+                let order_id = self.market.place_order(&order).await?;
+                info!("Order {} placed for {:?}", order_id, order);
+                let fill_price = match order.price {
+                    Some(Price::Absolute(limit)) => limit,
+                    _ => price,
+                };
+                self.track_order(&order_id, order.side, order.volume, fill_price);
+                self.strategy.on_order_placed(&order, &order_id);
+            }
+            Signal::Cancel(order_id) => {
+                self.market.cancel_order(&order_id).await?;
+                self.orders.remove(&order_id);
+                self.position.on_order_closed(&order_id);
+            }
+            Signal::Adopt(order_id) => {
+                self.orders.insert(order_id);
+            }
+            Signal::Target(target) => self.move_to(target, price).await?,
+        }
+        Ok(())
+    }
+
+    /// Exit the current position if it does not match the target, then enter the target.
+    /// Nothing is done while an entry or exit order is still waiting for fills.
+    pub(crate) async fn move_to(
+        &mut self,
+        target: TargetPosition,
+        price: f32,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.position.is_pending() {
+            debug!(
+                "Waiting on {:?}, ignoring target {:?}",
+                self.position, target
+            );
+            return Ok(());
+        }
+
+        let wanted = match target {
+            TargetPosition::Flat => None,
+            TargetPosition::Long(_) => Some(OrderSide::Buy),
+            TargetPosition::Short(_) => Some(OrderSide::Sell),
+        };
+
+        if let PositionState::Open {
+            side, entry_price, ..
+        } = self.position
+        {
+            if Some(side) == wanted {
+                return Ok(());
+            }
+            let (order_id, reason) = match side {
+                OrderSide::Buy if price >= entry_price => {
+                    (self.buy_order_exit_profit().await?, ExitReason::Profit)
+                }
+                OrderSide::Buy => (self.buy_order_exit_loss().await?, ExitReason::Loss),
+                OrderSide::Sell if price <= entry_price => {
+                    (self.sell_order_exit_profit().await?, ExitReason::Profit)
+                }
+                OrderSide::Sell => (self.sell_order_exit_loss().await?, ExitReason::Loss),
+            };
+            self.position.begin_exit(&order_id, reason)?;
+            let exit_side = match side {
+                OrderSide::Buy => OrderSide::Sell,
+                OrderSide::Sell => OrderSide::Buy,
+            };
+            if let PositionState::PendingExit { volume, .. } = self.position {
+                self.track_order(&order_id, exit_side, volume, price);
+            }
+            // The entry for the new target is sent once the exit is filled.
+            return Ok(());
+        }
+
+        match target {
+            TargetPosition::Flat => {}
+            TargetPosition::Long(volume) => {
+                self.position.can_enter()?;
+                let volume = self.entry_volume(volume);
+                let order_id = self.buy_order_enter(volume).await?;
+                self.position
+                    .begin_entry(OrderSide::Buy, &order_id, volume)?;
+                self.track_order(&order_id, OrderSide::Buy, volume, price);
+            }
+            TargetPosition::Short(volume) => {
+                self.position.can_enter()?;
+                let volume = self.entry_volume(volume);
+                let order_id = self.sell_order_enter(volume).await?;
+                self.position
+                    .begin_entry(OrderSide::Sell, &order_id, volume)?;
+                self.track_order(&order_id, OrderSide::Sell, volume, price);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remember an order placed by the bot. Dry run orders never reach the market, they are
+    /// filled at `price` on the next cycle so the position and strategy still move on.
+    fn track_order(&mut self, order_id: &str, side: OrderSide, volume: f32, price: f32) {
+        self.orders.insert(order_id.to_owned());
+        if order_id.starts_with(DRY_RUN_ORDER_PREFIX) {
+            self.dry_run_fills.push(Fill {
+                trade_id: order_id.to_owned(),
+                order_id: order_id.to_owned(),
+                pair: self.trading_config.pair.clone(),
+                side,
+                price,
+                volume,
+                fee: 0.0,
+                time: self.market.time(),
+            });
         }
     }
 
-    async fn buy_order_enter(&mut self) -> Result<f32, Box<dyn Error>> {
+    /// Market order closing the whole open position. Shorts are bought back on margin.
+    fn exit_order(&self, side: OrderSide) -> OrderRequest {
+        let volume = match self.position {
+            PositionState::Open { volume, .. } => volume,
+            _ => 0.0,
+        };
+        let leverage = match side {
+            OrderSide::Buy => self.trading_config.leverage,
+            OrderSide::Sell => None,
+        };
+        OrderRequest {
+            leverage,
+            ..OrderRequest::market(side, &self.trading_config.pair, volume)
+        }
+    }
+
+    /// Cap an entry volume to the configured maximum position.
+    fn entry_volume(&self, volume: f32) -> f32 {
+        if volume > self.trading_config.max_position {
+            warn!(
+                "Entry of {} capped to max position {}",
+                volume, self.trading_config.max_position
+            );
+            self.trading_config.max_position
+        } else {
+            volume
+        }
+    }
+
+    async fn buy_order_enter(&mut self, volume: f32) -> Result<String, Box<dyn Error>> {
         info!("buy order enter");
-        Ok(1.0)
+        let order = OrderRequest::market(OrderSide::Buy, &self.trading_config.pair, volume);
+        self.market.place_order(&order).await
     }
 
-    async fn buy_order_exit_profit(&mut self) -> Result<f32, Box<dyn Error>> {
+    async fn buy_order_exit_profit(&mut self) -> Result<String, Box<dyn Error>> {
         info!("buy order exit with profit");
-        Ok(1.0)
+        let order = self.exit_order(OrderSide::Sell);
+        self.market.place_order(&order).await
     }
 
-    async fn buy_order_exit_loss(&mut self) -> Result<f32, Box<dyn Error>> {
+    async fn buy_order_exit_loss(&mut self) -> Result<String, Box<dyn Error>> {
         info!("buy order exit with loss");
-        Ok(1.0)
+        let order = self.exit_order(OrderSide::Sell);
+        self.market.place_order(&order).await
     }
 
-    async fn sell_order_enter(&mut self) -> Result<f32, Box<dyn Error>> {
+    async fn sell_order_enter(&mut self, volume: f32) -> Result<String, Box<dyn Error>> {
         info!("sell order enter");
-        Ok(1.0)
+        let leverage = self.trading_config.leverage.ok_or_else(|| {
+            InvalidOrder("short entries need leverage set in the config".to_owned())
+        })?;
+        let order = OrderRequest {
+            leverage: Some(leverage),
+            ..OrderRequest::market(OrderSide::Sell, &self.trading_config.pair, volume)
+        };
+        self.market.place_order(&order).await
     }
 
-    async fn sell_order_exit_profit(&mut self) -> Result<f32, Box<dyn Error>> {
+    async fn sell_order_exit_profit(&mut self) -> Result<String, Box<dyn Error>> {
         info!("sell order exit with profit");
-        Ok(1.0)
+        let order = self.exit_order(OrderSide::Buy);
+        self.market.place_order(&order).await
     }
 
-    async fn sell_order_exit_loss(&mut self) -> Result<f32, Box<dyn Error>> {
+    async fn sell_order_exit_loss(&mut self) -> Result<String, Box<dyn Error>> {
         info!("sell order exit with loss");
-        Ok(1.0)
+        let order = self.exit_order(OrderSide::Buy);
+        self.market.place_order(&order).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tradingbot::{
+        candle::Candle, order::OpenOrder, order_book::OrderBook, strategy::Tick,
+    };
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Market at a fixed price of 100 returning dry run ids for every order.
+    #[derive(Default)]
+    struct DryRunMarket {
+        placed: AtomicU32,
+    }
+
+    #[async_trait]
+    impl Market for DryRunMarket {
+        async fn get_balances(&self) -> Result<f32, Box<dyn Error>> {
+            Ok(1000.0)
+        }
+
+        async fn get_base_balance(&self, _pair: &str) -> Result<f32, Box<dyn Error>> {
+            Ok(0.0)
+        }
+
+        async fn get_market_price(&self, _pair: &str) -> Result<f32, Box<dyn Error>> {
+            Ok(100.0)
+        }
+
+        async fn get_candles(
+            &self,
+            _pair: &str,
+            _interval: u32,
+        ) -> Result<Vec<Candle>, Box<dyn Error>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_order_book(
+            &self,
+            _pair: &str,
+            _depth: u32,
+        ) -> Result<OrderBook, Box<dyn Error>> {
+            Ok(OrderBook::default())
+        }
+
+        async fn place_order(&self, _order: &OrderRequest) -> Result<String, Box<dyn Error>> {
+            let n = self.placed.fetch_add(1, Ordering::SeqCst);
+            Ok(format!("{}{:08X}", DRY_RUN_ORDER_PREFIX, n))
+        }
+
+        async fn cancel_order(&self, _order_id: &str) -> Result<(), Box<dyn Error>> {
+            Ok(())
+        }
+
+        async fn get_open_orders(&self) -> Result<Vec<OpenOrder>, Box<dyn Error>> {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Strategy always asking for the same position.
+    struct Target(TargetPosition);
+
+    impl Strategy for Target {
+        fn name(&self) -> &str {
+            "target"
+        }
+
+        fn on_tick(&mut self, _tick: &Tick) -> Vec<Signal> {
+            vec![Signal::Target(self.0)]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_orders_fill() {
+        let config = TradingConfig {
+            dry_run: true,
+            ..TradingConfig::default()
+        };
+        let mut bot = TradingBot::new(
+            config,
+            Box::new(DryRunMarket::default()),
+            Box::new(Target(TargetPosition::Long(0.001))),
+        );
+
+        bot.start().await.unwrap();
+        assert!(bot.position.is_pending());
+        bot.start().await.unwrap();
+        assert_eq!(
+            bot.position,
+            PositionState::Open {
+                side: OrderSide::Buy,
+                volume: 0.001,
+                entry_price: 100.0
+            }
+        );
+
+        bot.strategy = Box::new(Target(TargetPosition::Flat));
+        bot.start().await.unwrap();
+        assert!(bot.position.is_pending());
+        bot.start().await.unwrap();
+        assert_eq!(bot.position, PositionState::Flat);
+    }
+
+    #[tokio::test]
+    async fn test_foreign_fills_ignored() {
+        let config = TradingConfig {
+            dry_run: true,
+            ..TradingConfig::default()
+        };
+        let mut bot = TradingBot::new(
+            config,
+            Box::new(DryRunMarket::default()),
+            Box::new(Target(TargetPosition::Long(0.001))),
+        );
+        bot.start().await.unwrap();
+        let fill = bot.dry_run_fills.pop().unwrap();
+
+        bot.dry_run_fills = vec![
+            Fill {
+                order_id: "OTHER".to_owned(),
+                ..fill.clone()
+            },
+            Fill {
+                pair: "XETHZUSD".to_owned(),
+                ..fill.clone()
+            },
+        ];
+        bot.process_fills().await;
+        assert!(bot.position.is_pending());
+
+        bot.dry_run_fills.push(fill);
+        bot.process_fills().await;
+        assert!(!bot.position.is_pending());
     }
 }